@@ -0,0 +1,254 @@
+// Criterion benchmarks for the four point-to-point searches and their
+// priority queues.
+//
+//   cargo bench --bench dijkstra -- --save-baseline main   # record a baseline
+//   cargo bench --bench dijkstra -- --baseline main        # compare against it
+//
+// Every run is also compared with the previous one automatically; reports are
+// written to `target/criterion`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use my_dijkstra_crate::generators::{gnm, grid, random_geometric, rmat, road_network, RoadOptions, Weights, RMAT_GRAPH500};
+use my_dijkstra_crate::binary::write_binary_file;
+use my_dijkstra_crate::{dial_dijkstra_with_max_weight, sequential_sssp, Algorithm, DynamicSssp, Graph, GraphBuilder, GraphProfile, MappedGraph, ParallelConfig, QueueKind};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+const QUERIES: usize = 16;
+
+fn queries(graph: &Graph, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..QUERIES).map(|_| (rng.gen_range(0..graph.num_nodes()), rng.gen_range(0..graph.num_nodes()))).collect()
+}
+
+/// The largest edge weight, found once per graph so that Dial's algorithm is
+/// timed without its scan over every edge.
+fn max_weight(graph: &Graph) -> usize {
+    GraphProfile::of(graph).weight_range.map_or(0, |(_, max)| max)
+}
+
+fn run_queries(graph: &Graph, queries: &[(usize, usize)], algorithm: Algorithm, max_weight: usize) -> usize {
+    queries.iter().map(|&(start, goal)| match algorithm {
+        Algorithm::Dial => dial_dijkstra_with_max_weight(graph, max_weight, start, goal),
+        algorithm => algorithm.run(graph, start, goal),
+    }.0.min(1)).sum()
+}
+
+/// Each graph family at a few sizes, all algorithms side by side.
+fn bench_families(c: &mut Criterion) {
+    let families: Vec<(&str, Vec<(usize, Graph)>)> = vec![
+        ("random", [10_000, 100_000].iter().map(|&n| (n, gnm(n, 4 * n, Weights::Uniform(1, 99), 42))).collect()),
+        ("road", [100, 300].iter().map(|&side| (side * side, road_network(&RoadOptions::new(side, side), 42))).collect()),
+        ("rmat", [14, 17].iter().map(|&scale| (1 << scale, rmat(scale, 8 << scale, RMAT_GRAPH500, Weights::Uniform(1, 99), 42))).collect()),
+    ];
+    for (family, graphs) in &families {
+        let mut group = c.benchmark_group(format!("family/{}", family));
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        for (size, graph) in graphs {
+            let (queries, max_weight) = (queries(graph, 7), max_weight(graph));
+            for algorithm in Algorithm::ALL {
+                group.bench_with_input(BenchmarkId::new(algorithm.name(), size), graph, |b, graph| {
+                    b.iter(|| run_queries(graph, &queries, algorithm, max_weight))
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Fixed node count, increasing average out-degree.
+fn bench_density(c: &mut Criterion) {
+    let nodes = 20_000;
+    let mut group = c.benchmark_group("density");
+    group.throughput(Throughput::Elements(QUERIES as u64));
+    for degree in [2, 8, 32] {
+        let graph = gnm(nodes, degree * nodes, Weights::Uniform(1, 99), 42);
+        let (queries, max_weight) = (queries(&graph, 7), max_weight(&graph));
+        for algorithm in Algorithm::ALL {
+            group.bench_with_input(BenchmarkId::new(algorithm.name(), degree), &graph, |b, graph| {
+                b.iter(|| run_queries(graph, &queries, algorithm, max_weight))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The parallel searches on dedicated pools of 1, 2, 4, ... up to the core
+/// count threads, starting one task per thread (the bidirectional search
+/// always starts two).
+fn bench_threads(c: &mut Criterion) {
+    let graph = road_network(&RoadOptions::new(300, 300), 42);
+    let queries = queries(&graph, 7);
+    let mut thread_counts: Vec<usize> = std::iter::successors(Some(1), |&t| Some(t * 2))
+        .take_while(|&t| t < num_cpus::get())
+        .collect();
+    thread_counts.push(num_cpus::get());
+
+    let mut group = c.benchmark_group("threads");
+    group.throughput(Throughput::Elements(QUERIES as u64));
+    for threads in thread_counts {
+        let config = ParallelConfig::new().dedicated_pool(threads).unwrap();
+        for algorithm in [Algorithm::Parallel, Algorithm::ParallelBidirectional] {
+            group.bench_with_input(BenchmarkId::new(algorithm.name(), threads), &graph, |b, graph| {
+                b.iter(|| queries.iter().map(|&(start, goal)| algorithm.run_with_config(graph, &config, start, goal).0.min(1)).sum::<usize>())
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Sequential and bidirectional search on each priority queue.
+fn bench_queues(c: &mut Criterion) {
+    let graphs = [
+        ("road", road_network(&RoadOptions::new(300, 300), 42)),
+        ("random", gnm(100_000, 400_000, Weights::Uniform(1, 99), 42)),
+    ];
+    for (family, graph) in &graphs {
+        let queries = queries(graph, 7);
+        let mut group = c.benchmark_group(format!("queue/{}", family));
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        for algorithm in [Algorithm::Sequential, Algorithm::Bidirectional] {
+            for queue in QueueKind::ALL {
+                group.bench_with_input(BenchmarkId::new(algorithm.name(), queue), graph, |b, graph| {
+                    b.iter(|| queries.iter().map(|&(start, goal)| algorithm.run_with_queue(graph, queue, start, goal).0.min(1)).sum::<usize>())
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Repairing the shortest-path tree after each edge update against computing
+/// it again from scratch.
+fn bench_dynamic(c: &mut Criterion) {
+    let graph = gnm(20_000, 80_000, Weights::Uniform(1, 99), 42);
+    let mut rng = StdRng::seed_from_u64(7);
+    let updates: Vec<(usize, usize, usize)> = (0..QUERIES)
+        .map(|_| (rng.gen_range(0..graph.num_nodes()), rng.gen_range(0..graph.num_nodes()), rng.gen_range(1..100)))
+        .collect();
+
+    let mut group = c.benchmark_group("dynamic");
+    group.throughput(Throughput::Elements(QUERIES as u64));
+    group.bench_function("repair", |b| {
+        b.iter_batched(
+            || DynamicSssp::new(graph.clone(), 0),
+            |mut sssp| {
+                for &(u, v, weight) in &updates {
+                    sssp.insert_edge(u, v, weight);
+                }
+                sssp
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("recompute", |b| {
+        b.iter_batched(
+            || graph.clone(),
+            |mut graph| {
+                for &(u, v, weight) in &updates {
+                    graph.insert_edge(u, v, weight);
+                    sequential_sssp(&graph, 0);
+                }
+                graph
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// `GraphBuilder`'s sort-based build against pushing into nested vectors.
+fn bench_build(c: &mut Criterion) {
+    let nodes = 20_000;
+    let mut rng = StdRng::seed_from_u64(42);
+    let edges: Vec<(usize, usize, usize)> = (0..200_000)
+        .map(|_| (rng.gen_range(0..nodes), rng.gen_range(0..nodes), rng.gen_range(1..100)))
+        .collect();
+
+    let mut group = c.benchmark_group("build");
+    group.throughput(Throughput::Elements(edges.len() as u64));
+    group.bench_function("builder", |b| {
+        b.iter(|| GraphBuilder::new(nodes).edges(edges.clone()).build().unwrap())
+    });
+    group.bench_function("nested", |b| {
+        b.iter(|| {
+            let mut adj_list = vec![Vec::new(); nodes];
+            for &(u, v, weight) in &edges {
+                adj_list[u].push((v, weight));
+            }
+            Graph::new(adj_list)
+        })
+    });
+    group.finish();
+}
+
+/// Searches on a memory-mapped binary graph against the same graph in memory.
+fn bench_mapped(c: &mut Criterion) {
+    let graph = road_network(&RoadOptions::new(300, 300), 42);
+    let path = std::env::temp_dir().join(format!("bench_mapped_{}.csr", std::process::id()));
+    write_binary_file(&graph, &path).unwrap();
+    let mapped = MappedGraph::open(&path).unwrap();
+    let (queries, max_weight) = (queries(&graph, 7), max_weight(&graph));
+
+    let mut group = c.benchmark_group("mapped");
+    group.bench_function("open", |b| b.iter(|| MappedGraph::open(&path).unwrap()));
+    group.throughput(Throughput::Elements(QUERIES as u64));
+    for algorithm in [Algorithm::Sequential, Algorithm::Bidirectional] {
+        group.bench_function(BenchmarkId::new(algorithm.name(), "mapped"), |b| {
+            b.iter(|| queries.iter().map(|&(start, goal)| algorithm.run(&mapped, start, goal).0.min(1)).sum::<usize>())
+        });
+        group.bench_function(BenchmarkId::new(algorithm.name(), "graph"), |b| {
+            b.iter(|| run_queries(&graph, &queries, algorithm, max_weight))
+        });
+    }
+    group.finish();
+    std::fs::remove_file(path).unwrap();
+}
+
+/// Dial's algorithm against the heap searches on small integer weights: the
+/// mesh-like graphs `shortest_path` sends to it, and random graphs it keeps
+/// on bidirectional search.
+fn bench_dial(c: &mut Criterion) {
+    let graphs = [
+        ("road", road_network(&RoadOptions::new(300, 300), 42)),
+        ("grid", grid(300, 300, Weights::Uniform(1, 10), 42)),
+        ("random", gnm(100_000, 400_000, Weights::Uniform(1, 10), 42)),
+    ];
+    let mut group = c.benchmark_group("dial");
+    group.throughput(Throughput::Elements(QUERIES as u64));
+    for (family, graph) in &graphs {
+        let (queries, max_weight) = (queries(graph, 7), max_weight(graph));
+        for algorithm in [Algorithm::Dial, Algorithm::Sequential, Algorithm::Bidirectional] {
+            group.bench_with_input(BenchmarkId::new(algorithm.name(), family), graph, |b, graph| {
+                b.iter(|| run_queries(graph, &queries, algorithm, max_weight))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The parallel generators at the sizes the tests and harness use.
+fn bench_generators(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generators");
+    group.sample_size(10);
+    group.bench_function("gnm/1M", |b| b.iter(|| gnm(200_000, 1_000_000, Weights::Uniform(1, 99), 42)));
+    group.bench_function("geometric/200k", |b| {
+        b.iter(|| random_geometric(200_000, 0.004, Weights::Euclidean(10_000.0), 42))
+    });
+    group.finish();
+}
+
+fn config() -> Criterion {
+    Criterion::default()
+        .sample_size(20)
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(3))
+}
+
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = bench_families, bench_density, bench_threads, bench_queues, bench_dynamic, bench_build, bench_mapped, bench_generators, bench_dial
+}
+criterion_main!(benches);
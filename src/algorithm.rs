@@ -0,0 +1,199 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::graph::Adjacency;
+use crate::parallel_config::ParallelConfig;
+use crate::priority_queue::QueueKind;
+use crate::search_stats::SearchStats;
+use crate::{bidirectional_dijkstra, dial_dijkstra, dial_dijkstra_with_stats, parallel_bidirectional_dijkstra, parallel_dijkstra, sequential_dijkstra};
+use crate::{bidirectional_dijkstra_with_stats, parallel_bidirectional_dijkstra_with_stats, parallel_dijkstra_with_stats, sequential_dijkstra_with_stats};
+use crate::{parallel_bidirectional_dijkstra_with_config, parallel_dijkstra_with_config};
+use crate::{bidirectional_dijkstra_with_queue, parallel_bidirectional_dijkstra_with_queue, parallel_dijkstra_with_queue, sequential_dijkstra_with_queue};
+
+/// The point-to-point searches, selectable at runtime.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Algorithm {
+    Sequential,
+    Bidirectional,
+    Parallel,
+    ParallelBidirectional,
+    /// Scans every edge for the largest weight on each run; repeated queries
+    /// on one graph can find it once and call `dial_dijkstra_with_max_weight`.
+    Dial,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 5] = [
+        Algorithm::Sequential,
+        Algorithm::Bidirectional,
+        Algorithm::Parallel,
+        Algorithm::ParallelBidirectional,
+        Algorithm::Dial,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Sequential => "sequential",
+            Algorithm::Bidirectional => "bidirectional",
+            Algorithm::Parallel => "parallel",
+            Algorithm::ParallelBidirectional => "parallel-bidirectional",
+            Algorithm::Dial => "dial",
+        }
+    }
+
+    pub fn run<G: Adjacency + Sync + ?Sized>(&self, graph: &G, start: usize, goal: usize) -> (usize, Vec<usize>) {
+        match self {
+            Algorithm::Sequential => sequential_dijkstra(graph, start, goal),
+            Algorithm::Bidirectional => bidirectional_dijkstra(graph, start, goal),
+            Algorithm::Parallel => parallel_dijkstra(graph, start, goal),
+            Algorithm::ParallelBidirectional => parallel_bidirectional_dijkstra(graph, start, goal),
+            Algorithm::Dial => dial_dijkstra(graph, start, goal),
+        }
+    }
+
+    pub fn run_with_stats<G: Adjacency + Sync + ?Sized>(&self, graph: &G, stats: &SearchStats, start: usize, goal: usize) -> (usize, Vec<usize>) {
+        match self {
+            Algorithm::Sequential => sequential_dijkstra_with_stats(graph, stats, start, goal),
+            Algorithm::Bidirectional => bidirectional_dijkstra_with_stats(graph, stats, start, goal),
+            Algorithm::Parallel => parallel_dijkstra_with_stats(graph, stats, &ParallelConfig::default(), start, goal),
+            Algorithm::ParallelBidirectional => parallel_bidirectional_dijkstra_with_stats(graph, stats, &ParallelConfig::default(), start, goal),
+            Algorithm::Dial => dial_dijkstra_with_stats(graph, stats, start, goal),
+        }
+    }
+
+    /// Like `run`, with the parallel searches following `config`; the
+    /// sequential ones ignore it.
+    pub fn run_with_config<G: Adjacency + Sync + ?Sized>(&self, graph: &G, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+        match self {
+            Algorithm::Sequential => sequential_dijkstra(graph, start, goal),
+            Algorithm::Bidirectional => bidirectional_dijkstra(graph, start, goal),
+            Algorithm::Parallel => parallel_dijkstra_with_config(graph, config, start, goal),
+            Algorithm::ParallelBidirectional => parallel_bidirectional_dijkstra_with_config(graph, config, start, goal),
+            Algorithm::Dial => dial_dijkstra(graph, start, goal),
+        }
+    }
+
+    /// Like `run`, on the priority queue `queue`. Dial's algorithm has its
+    /// own bucket queue and ignores it.
+    pub fn run_with_queue<G: Adjacency + Sync + ?Sized>(&self, graph: &G, queue: QueueKind, start: usize, goal: usize) -> (usize, Vec<usize>) {
+        match self {
+            Algorithm::Sequential => sequential_dijkstra_with_queue(graph, queue, start, goal),
+            Algorithm::Bidirectional => bidirectional_dijkstra_with_queue(graph, queue, start, goal),
+            Algorithm::Parallel => parallel_dijkstra_with_queue(graph, queue, &ParallelConfig::default(), start, goal),
+            Algorithm::ParallelBidirectional => parallel_bidirectional_dijkstra_with_queue(graph, queue, &ParallelConfig::default(), start, goal),
+            Algorithm::Dial => dial_dijkstra(graph, start, goal),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL.iter()
+            .find(|algorithm| algorithm.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown algorithm `{}` (expected one of: sequential, bidirectional, parallel, parallel-bidirectional, dial)", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+    use crate::{sequential_sssp, shortest_path, verify_path, verify_tree};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    /// Small graphs, so that zero weights, self-loops, parallel edges and
+    /// unreachable nodes all turn up often.
+    fn graph_and_query() -> impl Strategy<Value = (Graph, usize, usize)> {
+        graph_and_query_up_to(24)
+    }
+
+    fn graph_and_query_up_to(max_nodes: usize) -> impl Strategy<Value = (Graph, usize, usize)> {
+        (1..max_nodes).prop_flat_map(|n| {
+            (vec((0..n, 0..n, 0usize..16), 0..4 * n), 0..n, 0..n).prop_map(move |(edges, start, goal)| {
+                let mut adj_list = vec![Vec::new(); n];
+                for (u, v, weight) in edges {
+                    adj_list[u].push((v, weight));
+                }
+                (Graph::new(adj_list), start, goal)
+            })
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(512))]
+
+        #[test]
+        fn test_all_algorithms_agree((graph, start, goal) in graph_and_query()) {
+            let expected = sequential_sssp(&graph, start).0[goal];
+            for algorithm in Algorithm::ALL {
+                let (cost, path) = algorithm.run(&graph, start, goal);
+                prop_assert_eq!(cost, expected, "{}", algorithm);
+                prop_assert_eq!(verify_path(&graph, start, goal, cost, &path), Ok(()), "{}", algorithm);
+            }
+            let (cost, path) = shortest_path(&graph, start, goal);
+            prop_assert_eq!(cost, expected);
+            prop_assert_eq!(verify_path(&graph, start, goal, cost, &path), Ok(()));
+        }
+
+        #[test]
+        fn test_queues_agree((graph, start, goal) in graph_and_query()) {
+            let expected = sequential_sssp(&graph, start).0[goal];
+            for algorithm in Algorithm::ALL {
+                for queue in QueueKind::ALL {
+                    let (cost, path) = algorithm.run_with_queue(&graph, queue, start, goal);
+                    prop_assert_eq!(cost, expected, "{} on {}", algorithm, queue);
+                    prop_assert_eq!(verify_path(&graph, start, goal, cost, &path), Ok(()), "{} on {}", algorithm, queue);
+                }
+            }
+        }
+
+        #[test]
+        fn test_sssp_is_certified((graph, start, _) in graph_and_query()) {
+            let (dist, prev) = sequential_sssp(&graph, start);
+            prop_assert_eq!(verify_tree(&graph, start, &dist, &prev), Ok(()));
+        }
+
+        #[test]
+        fn test_plain_adjacency_lists_agree((graph, start, goal) in graph_and_query()) {
+            // Without a stored reverse graph the bidirectional searches build their own
+            let adj_list = graph.adj_list.clone();
+            let expected = sequential_sssp(&graph, start).0[goal];
+            for algorithm in Algorithm::ALL {
+                prop_assert_eq!(algorithm.run(&adj_list, start, goal).0, expected, "{}", algorithm);
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_agree_on_larger_graphs((graph, start, goal) in graph_and_query_up_to(400)) {
+            // Long enough for the two sides of the parallel searches to overlap
+            let expected = sequential_sssp(&graph, start).0[goal];
+            for algorithm in Algorithm::ALL {
+                let (cost, path) = algorithm.run(&graph, start, goal);
+                prop_assert_eq!(cost, expected, "{}", algorithm);
+                prop_assert_eq!(verify_path(&graph, start, goal, cost, &path), Ok(()), "{}", algorithm);
+            }
+        }
+    }
+
+    #[test]
+    fn test_names_round_trip() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.name().parse::<Algorithm>(), Ok(algorithm));
+        }
+        assert!("dijkstra".parse::<Algorithm>().is_err());
+    }
+}
@@ -1,76 +1,73 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, HashSet};
-use rand::{distributions::{Distribution, Uniform}, SeedableRng, rngs::StdRng, Rng};
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: usize,
+use std::borrow::Cow;
+use crate::graph::{Adjacency, Neighbors, Reversed};
+use crate::overlay::{effective_weight, is_banned, Overlay};
+use crate::priority_queue::{with_queue, LazyBinaryHeap, PriorityQueue, QueueKind};
+use crate::search_stats::{record_settled, SearchStats};
+
+pub fn bidirectional_dijkstra<G: Adjacency + ?Sized>(graph: &G, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, None, start, goal)
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
+/// `bidirectional_dijkstra` with the bans and weight overrides of `overlay`.
+pub fn bidirectional_dijkstra_with_overlay<G: Adjacency + ?Sized>(graph: &G, overlay: &Overlay, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, Some(overlay), None, start, goal)
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+/// `bidirectional_dijkstra` that counts its work in `stats`.
+pub fn bidirectional_dijkstra_with_stats<G: Adjacency + ?Sized>(graph: &G, stats: &SearchStats, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, Some(stats), start, goal)
 }
 
-fn reverse_adj_list(adj_list: &[Vec<(usize, usize)>]) -> Vec<Vec<(usize, usize)>> {
-    let mut rev_adj_list = vec![Vec::new(); adj_list.len()];
-    for (node, edges) in adj_list.iter().enumerate() {
-        for &(neighbor, weight) in edges {
-            rev_adj_list[neighbor].push((node, weight));
-        }
-    }
-    rev_adj_list
+/// `bidirectional_dijkstra` with both directions on the priority queue `queue`.
+pub fn bidirectional_dijkstra_with_queue<G: Adjacency + ?Sized>(graph: &G, queue: QueueKind, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    with_queue!(queue, search(graph, None, None, start, goal))
 }
 
-pub fn bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usize, goal: usize) -> (usize, Vec<usize>) {
+pub(crate) fn search<G: Adjacency + ?Sized, Q: PriorityQueue>(graph: &G, overlay: Option<&Overlay>, stats: Option<&SearchStats>, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    if is_banned(overlay, start) || is_banned(overlay, goal) {
+        return (usize::MAX, Vec::new());
+    }
     if start == goal {
         return (0, vec![start]);
     }
 
-    let rev_graph = reverse_adj_list(graph);
-    let mut dist_fwd = vec![usize::MAX; graph.len()];
-    let mut dist_bwd = vec![usize::MAX; graph.len()];
-    let mut heap_fwd = BinaryHeap::new();
-    let mut heap_bwd = BinaryHeap::new();
-    let mut prev_fwd = vec![None; graph.len()];
-    let mut prev_bwd = vec![None; graph.len()];
+    // An undirected graph, `Graph` or `MappedGraph` lends its stored reverse
+    // adjacency; the overlay needs its own copy with bans and overrides applied.
+    let rev_graph = match overlay {
+        Some(overlay) => Reversed::Lists(Cow::Owned(overlay.reverse_adj_list(graph))),
+        None => graph.reversed(),
+    };
+    let mut dist_fwd = vec![usize::MAX; graph.num_nodes()];
+    let mut dist_bwd = vec![usize::MAX; graph.num_nodes()];
+    let mut heap_fwd = Q::new(graph.num_nodes());
+    let mut heap_bwd = Q::new(graph.num_nodes());
+    let mut prev_fwd = vec![None; graph.num_nodes()];
+    let mut prev_bwd = vec![None; graph.num_nodes()];
 
     dist_fwd[start] = 0;
     dist_bwd[goal] = 0;
-    heap_fwd.push(State { cost: 0, position: start });
-    heap_bwd.push(State { cost: 0, position: goal });
+    heap_fwd.push_or_decrease(start, 0);
+    heap_bwd.push_or_decrease(goal, 0);
 
     let mut estimate = usize::MAX;
     let mut join_node = None;
 
-    while !heap_fwd.is_empty() && !heap_bwd.is_empty() {
-        let pos_fwd = heap_fwd.peek().unwrap().position;
-        let pos_bwd = heap_bwd.peek().unwrap().position;
-        let cost_fwd = heap_fwd.peek().unwrap().cost;
-        let cost_bwd = heap_bwd.peek().unwrap().cost;
-
+    while let (Some((cost_fwd, _)), Some((cost_bwd, _))) = (heap_fwd.peek(), heap_bwd.peek()) {
         if cost_fwd + cost_bwd >= estimate {
             break;
         }
 
         if cost_fwd < cost_bwd {
-            let State { cost, position } = heap_fwd.pop().unwrap();
-            if let Some((new_estimate, new_join_node)) = discover_nodes(&graph[position], position, &mut dist_fwd, &mut dist_bwd, &mut heap_fwd, &mut prev_fwd, estimate, join_node) {
+            let (_, position) = heap_fwd.pop().unwrap();
+            record_settled(stats);
+            if let Some((new_estimate, new_join_node)) = discover_nodes(forward_edges(graph, overlay, position), position, &mut dist_fwd, &mut dist_bwd, &mut heap_fwd, &mut prev_fwd, estimate, join_node) {
                 estimate = new_estimate;
                 join_node = Some(new_join_node);
             }
         } else {
-            let State { cost, position } = heap_bwd.pop().unwrap();
-            if let Some((new_estimate, new_join_node)) = discover_nodes(&rev_graph[position], position, &mut dist_bwd, &mut dist_fwd, &mut heap_bwd, &mut prev_bwd, estimate, join_node) {
+            let (_, position) = heap_bwd.pop().unwrap();
+            record_settled(stats);
+            if let Some((new_estimate, new_join_node)) = discover_nodes(rev_graph.neighbors(position), position, &mut dist_bwd, &mut dist_fwd, &mut heap_bwd, &mut prev_bwd, estimate, join_node) {
                 estimate = new_estimate;
                 join_node = Some(new_join_node);
             }
@@ -89,23 +86,29 @@ pub fn bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usize, goal:
     }
 }
 
+fn forward_edges<'a, G: Neighbors + ?Sized>(graph: &'a G, overlay: Option<&'a Overlay>, node: usize) -> impl Iterator<Item = (usize, usize)> + 'a {
+    graph.neighbors(node).enumerate()
+        .filter_map(move |(i, (neighbor, weight))| effective_weight(overlay, node, i, neighbor, weight).map(|weight| (neighbor, weight)))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn discover_nodes(
-    edges: &[(usize, usize)],
+    edges: impl Iterator<Item = (usize, usize)>,
     node: usize,
     dist: &mut [usize],
     other_dist: &mut [usize],
-    heap: &mut BinaryHeap<State>,
+    heap: &mut impl PriorityQueue,
     prev: &mut [Option<usize>],
     mut estimate: usize,
     join_node: Option<usize>,
 ) -> Option<(usize, usize)> {
     let mut local_join_node = join_node;
 
-    for &(neighbor, weight) in edges {
+    for (neighbor, weight) in edges {
         let new_cost = dist[node].saturating_add(weight);
         if new_cost < dist[neighbor] {
             dist[neighbor] = new_cost;
-            heap.push(State { cost: new_cost, position: neighbor });
+            heap.push_or_decrease(neighbor, new_cost);
             prev[neighbor] = Some(node);
         }
         if other_dist[neighbor] != usize::MAX {
@@ -132,25 +135,6 @@ fn reconstruct_path(meeting_point: usize, prev: &[Option<usize>]) -> Vec<usize>
 }
 
 
-fn generate_large_graph(nodes: usize, edges_per_node: usize, max_weight: usize, seed: u64) -> Vec<Vec<(usize, usize)>> {
-    let mut rng = StdRng::seed_from_u64(seed);
-    let weight_dist = Uniform::from(1..=max_weight);
-
-    let mut graph = vec![Vec::new(); nodes];
-    for node in 0..nodes {
-        let num_edges = rng.gen_range(1..=edges_per_node);
-        for _ in 0..num_edges {
-            let target = rng.gen_range(0..nodes);
-            let weight = weight_dist.sample(&mut rng);
-            if target != node && !graph[node].iter().any(|&(t, _)| t == target) {
-                graph[node].push((target, weight));
-            }
-        }
-    }
-
-    graph
-}
-
 
 
 
@@ -158,9 +142,8 @@ fn generate_large_graph(nodes: usize, edges_per_node: usize, max_weight: usize,
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::distributions::{Distribution, Uniform};
-    use rand::rngs::StdRng;
-    use rand::SeedableRng;
+    use crate::generators::{self, Weights};
+    use crate::graph::{Graph, UndirectedGraph};
     use std::time::Instant;
 
     #[test]
@@ -236,10 +219,10 @@ mod tests {
         let max_weight = 100; // Maximum weight of 100
         let seed = 42; // Fixed seed for reproducibility
 
-        let graph = generate_large_graph(nodes, edges_per_node, max_weight, seed);
+        let graph = generators::gnm(nodes, nodes * edges_per_node / 2, Weights::Uniform(1, max_weight), seed).adj_list;
 
         let start = 0;
-        let goal = (nodes - nodes/3); // Assuming we want to find path from node 0 to the last node
+        let goal = nodes - nodes/3; // Assuming we want to find path from node 0 to the last node
         let start_time = std::time::Instant::now();
         let (cost, path) = bidirectional_dijkstra(&graph, start, goal);
         let duration = start_time.elapsed();
@@ -248,21 +231,7 @@ mod tests {
     }
 
     fn generate_random_graph(nodes: usize, edges: usize) -> Vec<Vec<(usize, usize)>> {
-        let mut graph = vec![Vec::new(); nodes];
-        let mut rng = StdRng::seed_from_u64(42); // Seed for reproducibility
-        let range = Uniform::from(0..nodes);
-        let weight_range = Uniform::from(1..100); // Random weights between 1 and 100
-
-        for _ in 0..edges {
-            let u = range.sample(&mut rng);
-            let v = range.sample(&mut rng);
-            if u != v {
-                let weight = weight_range.sample(&mut rng);
-                graph[u].push((v, weight));
-            }
-        }
-
-        graph
+        generators::gnm(nodes, edges, Weights::Uniform(1, 99), 42).adj_list
     }
 
     #[test]
@@ -314,27 +283,34 @@ mod tests {
         println!("Bi-Test Large Very Complex Graph - Time elapsed: {:?}", duration);
         println!("Cost: {}, Path length: {}", cost, path.len());
     }
-}
-
-
-/*
-successes:
-
----- bidirectional_dijkstra::tests::test_simple_graph stdout ----
-Test Simple Graph - Time elapsed: 120.375µs
-
----- bidirectional_dijkstra::tests::test_complex_graph stdout ----
-Test Complex Graph - Time elapsed: 376.291µs
-
----- bidirectional_dijkstra::tests::test_larger_graph stdout ----
-Test Larger Graph - Time elapsed: 292.583µs
-
----- bidirectional_dijkstra::tests::test_very_complex_graph stdout ----
-Test Very Complex Graph - Time elapsed: 42.292µs
 
----- bidirectional_dijkstra::tests::test_disconnected_graph stdout ----
-Test Disconnected Graph - Time elapsed: 228.208µs
+    #[test]
+    fn test_undirected_graph() {
+        let graph = UndirectedGraph::from_edges(6, vec![
+            (0, 1, 7), (0, 2, 9), (0, 5, 14),
+            (1, 2, 10), (1, 3, 15),
+            (2, 3, 11), (2, 5, 2),
+            (3, 4, 6), (4, 5, 9),
+        ]);
+        let start_time = Instant::now();
+        let (cost, path) = bidirectional_dijkstra(&graph, 0, 4);
+        let duration = start_time.elapsed();
+        println!("Bi-Test Undirected Graph - Time elapsed: {:?}", duration);
+        assert_eq!(cost, 20);  // Shortest path cost: 20
+        assert_eq!(path, vec![0, 2, 5, 4]);  // Shortest path: 0 -> 2 -> 5 -> 4
+        assert_eq!(bidirectional_dijkstra(&graph, 4, 0), (20, vec![4, 5, 2, 0]));
+    }
 
----- bidirectional_dijkstra::tests::test_huge_graph stdout ----
-Test Huge Graph - Time elapsed: 329.195ms, Cost: 54, Path Length: 13
- */
+    #[test]
+    fn test_graph_reuses_reverse_adjacency() {
+        let adj_list = generate_random_graph(1000, 5000);
+        let graph = Graph::new(adj_list.clone());
+        assert!(matches!(graph.rev_adj_list(), Cow::Borrowed(_)));
+        assert!(matches!(adj_list.rev_adj_list(), Cow::Owned(_)));
+        assert_eq!(bidirectional_dijkstra(&graph, 0, 999).0, bidirectional_dijkstra(&adj_list, 0, 999).0);
+
+        let undirected = UndirectedGraph::from_edges(3, vec![(0, 1, 4), (1, 2, 5)]);
+        assert!(matches!(undirected.rev_adj_list(), Cow::Borrowed(_)));
+        assert_eq!(bidirectional_dijkstra(&undirected, 2, 0), (9, vec![2, 1, 0]));
+    }
+}
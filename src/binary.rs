@@ -0,0 +1,511 @@
+//! Compact binary CSR graph files that are memory-mapped instead of parsed.
+//!
+//! Layout, all little-endian, every section 8-byte aligned:
+//!
+//! | bytes              | content                                          |
+//! |--------------------|--------------------------------------------------|
+//! | 0..8               | magic `DIJKCSR\0`                                |
+//! | 8..12              | format version (`u32`)                           |
+//! | 12..16             | flags (`u32`, bit 0: coordinates present)        |
+//! | 16..24             | node count `n` (`u64`)                           |
+//! | 24..32             | edge count `m` (`u64`)                           |
+//! | 32..40             | FNV-1a checksum of everything after the header   |
+//! | 40..64             | reserved, zero                                   |
+//! | offsets            | `n + 1` × `u64`, edges of `u` are `offsets[u]..offsets[u + 1]` |
+//! | targets            | `m` × `u32`, zero-padded to a multiple of 8 bytes |
+//! | weights            | `m` × `u64`                                      |
+//! | reverse offsets    | `n + 1` × `u64`, edges into `v` (version 2 on)   |
+//! | reverse sources    | `m` × `u32`, zero-padded to a multiple of 8 bytes |
+//! | reverse weights    | `m` × `u64`                                      |
+//! | coordinates        | `n` × `(f64, f64)` if flag bit 0 is set          |
+//!
+//! The reverse sections let bidirectional searches run on the mapping without
+//! building a reverse graph per query. Version 1 files lack them and are still
+//! read; their reverse is built on demand.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+use memmap2::Mmap;
+use crate::graph::{Adjacency, CsrSlices, Graph, Neighbors, Reversed};
+
+pub const MAGIC: [u8; 8] = *b"DIJKCSR\0";
+pub const VERSION: u32 = 2;
+const HEADER_LEN: usize = 64;
+const FLAG_COORDINATES: u32 = 1;
+
+pub fn write_binary<W: Write>(graph: &Graph, writer: W) -> io::Result<()> {
+    if graph.num_nodes() > u32::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "binary graphs are limited to u32::MAX nodes"));
+    }
+    // The checksum sits in the header, so hash the body in a first pass.
+    let mut hasher = Fnv1a::new();
+    write_body(graph, &mut hasher)?;
+
+    let mut writer = BufWriter::new(writer);
+    let flags = if graph.coordinates.is_some() { FLAG_COORDINATES } else { 0 };
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&flags.to_le_bytes())?;
+    writer.write_all(&(graph.num_nodes() as u64).to_le_bytes())?;
+    writer.write_all(&(graph.num_edges() as u64).to_le_bytes())?;
+    writer.write_all(&hasher.0.to_le_bytes())?;
+    writer.write_all(&[0; HEADER_LEN - 40])?;
+    write_body(graph, &mut writer)?;
+    writer.flush()
+}
+
+pub fn write_binary_file<P: AsRef<Path>>(graph: &Graph, path: P) -> io::Result<()> {
+    write_binary(graph, File::create(path)?)
+}
+
+fn write_body<W: Write>(graph: &Graph, writer: &mut W) -> io::Result<()> {
+    write_csr(&graph.adj_list, writer)?;
+    write_csr(&graph.rev_adj_list, writer)?;
+    if let Some(coordinates) = &graph.coordinates {
+        for &(x, y) in coordinates {
+            writer.write_all(&x.to_le_bytes())?;
+            writer.write_all(&y.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn write_csr<W: Write>(adj_list: &[Vec<(usize, usize)>], writer: &mut W) -> io::Result<()> {
+    let mut offset = 0u64;
+    writer.write_all(&offset.to_le_bytes())?;
+    for edges in adj_list {
+        offset += edges.len() as u64;
+        writer.write_all(&offset.to_le_bytes())?;
+    }
+    for edges in adj_list {
+        for &(v, _) in edges {
+            writer.write_all(&(v as u32).to_le_bytes())?;
+        }
+    }
+    if offset % 2 == 1 {
+        writer.write_all(&[0; 4])?;
+    }
+    for edges in adj_list {
+        for &(_, weight) in edges {
+            writer.write_all(&(weight as u64).to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// A graph file mapped into memory. The searches read the CSR arrays straight
+/// from the mapping through `Neighbors`, so opening costs no parsing and no
+/// copies.
+#[derive(Debug)]
+pub struct MappedGraph {
+    mmap: Mmap,
+    num_nodes: usize,
+    num_edges: usize,
+    has_coordinates: bool,
+    sections: Sections,
+}
+
+impl MappedGraph {
+    /// Maps `path` and verifies the checksum, the CSR structure and that the
+    /// reverse section is the transpose of the edges, which reads the whole
+    /// file once and sorts the edges.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let graph = MappedGraph::open_unverified(path)?;
+        let checksum = u64::from_le_bytes(graph.mmap[32..40].try_into().unwrap());
+        if Fnv1a::hash(&graph.mmap[HEADER_LEN..]) != checksum {
+            return Err(invalid("checksum mismatch"));
+        }
+        check_csr(graph.csr(), graph.num_edges)?;
+        if let Some(reverse) = graph.reverse_csr() {
+            check_csr(reverse, graph.num_edges)?;
+            check_transpose(graph.csr(), reverse)?;
+        }
+        Ok(graph)
+    }
+
+    /// Maps `path` checking only the header and file size, so pages are read
+    /// lazily as the search touches them. A corrupt file can make searches
+    /// panic but not read out of bounds.
+    pub fn open_unverified<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        if cfg!(target_endian = "big") {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "binary graphs are little-endian"));
+        }
+        let file = File::open(path)?;
+        // Safety: the mapping is read-only; the file must not be truncated
+        // or rewritten while it is mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+        if mmap.len() < HEADER_LEN || mmap[..8] != MAGIC {
+            return Err(invalid("not a binary graph file"));
+        }
+        let word = |at: usize| u64::from_le_bytes(mmap[at..at + 8].try_into().unwrap());
+        let version = u32::from_le_bytes(mmap[8..12].try_into().unwrap());
+        if version == 0 || version > VERSION {
+            return Err(invalid(&format!("unsupported format version {} (expected {})", version, VERSION)));
+        }
+        let flags = u32::from_le_bytes(mmap[12..16].try_into().unwrap());
+        let (num_nodes, num_edges) = (word(16) as usize, word(24) as usize);
+        let has_coordinates = flags & FLAG_COORDINATES != 0;
+        let sections = Sections::new(num_nodes, num_edges, version >= 2, has_coordinates)
+            .ok_or_else(|| invalid("node or edge count too large"))?;
+        if mmap.len() != sections.end {
+            return Err(invalid("file size does not match the header"));
+        }
+        Ok(MappedGraph { mmap, num_nodes, num_edges, has_coordinates, sections })
+    }
+
+    pub fn offsets(&self) -> &[u64] {
+        cast(&self.mmap[self.sections.offsets.clone()])
+    }
+
+    pub fn targets(&self) -> &[u32] {
+        let targets: &[u32] = cast(&self.mmap[self.sections.targets.clone()]);
+        &targets[..self.num_edges]
+    }
+
+    pub fn weights(&self) -> &[u64] {
+        cast(&self.mmap[self.sections.weights.clone()])
+    }
+
+    /// The forward adjacency as borrowed CSR arrays.
+    pub fn csr(&self) -> CsrSlices<'_> {
+        CsrSlices { offsets: self.offsets(), targets: self.targets(), weights: self.weights() }
+    }
+
+    /// The stored reverse adjacency, or `None` for version 1 files.
+    pub fn reverse_csr(&self) -> Option<CsrSlices<'_>> {
+        let reverse = self.sections.reverse.as_ref()?;
+        let sources: &[u32] = cast(&self.mmap[reverse.targets.clone()]);
+        Some(CsrSlices {
+            offsets: cast(&self.mmap[reverse.offsets.clone()]),
+            targets: &sources[..self.num_edges],
+            weights: cast(&self.mmap[reverse.weights.clone()]),
+        })
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.num_edges
+    }
+
+    pub fn has_coordinates(&self) -> bool {
+        self.has_coordinates
+    }
+
+    pub fn coordinate(&self, node: usize) -> Option<(f64, f64)> {
+        if !self.has_coordinates {
+            return None;
+        }
+        let coordinates: &[f64] = cast(&self.mmap[self.sections.coordinates.clone()]);
+        Some((coordinates[2 * node], coordinates[2 * node + 1]))
+    }
+
+    /// Copies the mapped data into an owned `Graph`.
+    pub fn to_graph(&self) -> Graph {
+        let adj_list = (0..self.num_nodes).map(|u| self.neighbors(u).collect()).collect();
+        let graph = Graph::new(adj_list);
+        if self.has_coordinates {
+            graph.with_coordinates((0..self.num_nodes).map(|u| self.coordinate(u).unwrap()).collect())
+        } else {
+            graph
+        }
+    }
+
+}
+
+/// Byte ranges of the sections, computed once from the header.
+#[derive(Debug)]
+struct Sections {
+    offsets: Range<usize>,
+    targets: Range<usize>,
+    weights: Range<usize>,
+    reverse: Option<CsrSections>,
+    coordinates: Range<usize>,
+    end: usize,
+}
+
+#[derive(Debug)]
+struct CsrSections {
+    offsets: Range<usize>,
+    targets: Range<usize>,
+    weights: Range<usize>,
+}
+
+impl CsrSections {
+    fn new(start: usize, num_nodes: usize, num_edges: usize) -> Option<Self> {
+        let offsets = section(start, num_nodes.checked_add(1)?, 8)?;
+        let targets = section(offsets.end, num_edges.checked_add(num_edges % 2)?, 4)?;
+        let weights = section(targets.end, num_edges, 8)?;
+        Some(CsrSections { offsets, targets, weights })
+    }
+}
+
+impl Sections {
+    /// `None` if the header's counts overflow the address space.
+    fn new(num_nodes: usize, num_edges: usize, has_reverse: bool, has_coordinates: bool) -> Option<Self> {
+        let forward = CsrSections::new(HEADER_LEN, num_nodes, num_edges)?;
+        let reverse = if has_reverse { Some(CsrSections::new(forward.weights.end, num_nodes, num_edges)?) } else { None };
+        let start = reverse.as_ref().map_or(forward.weights.end, |reverse| reverse.weights.end);
+        let coordinates = section(start, if has_coordinates { num_nodes } else { 0 }, 16)?;
+        Some(Sections {
+            end: coordinates.end,
+            offsets: forward.offsets,
+            targets: forward.targets,
+            weights: forward.weights,
+            reverse,
+            coordinates,
+        })
+    }
+}
+
+fn section(start: usize, count: usize, size: usize) -> Option<Range<usize>> {
+    Some(start..start.checked_add(count.checked_mul(size)?)?)
+}
+
+fn check_csr(csr: CsrSlices<'_>, num_edges: usize) -> io::Result<()> {
+    let offsets = csr.offsets;
+    if offsets[0] != 0 || offsets.windows(2).any(|w| w[0] > w[1]) || offsets[offsets.len() - 1] != num_edges as u64 {
+        return Err(invalid("offsets are not a valid CSR index"));
+    }
+    if csr.targets.iter().any(|&v| v as usize >= csr.num_nodes()) {
+        return Err(invalid("edge target out of range"));
+    }
+    Ok(())
+}
+
+/// Checks that `reverse` holds every edge of `forward` turned around, with
+/// the same weight, as many times as `forward` does.
+fn check_transpose(forward: CsrSlices<'_>, reverse: CsrSlices<'_>) -> io::Result<()> {
+    let mismatch = || invalid("reverse adjacency does not match the edges");
+    let mut edges: Vec<(usize, usize, usize)> = (0..forward.num_nodes())
+        .flat_map(|u| forward.edges(u).map(move |(v, weight)| (v, u, weight)))
+        .collect();
+    edges.sort_unstable();
+    let mut incoming = edges.as_slice();
+    let mut sources = Vec::new();
+    for v in 0..reverse.num_nodes() {
+        sources.clear();
+        sources.extend(reverse.edges(v).map(|(u, weight)| (v, u, weight)));
+        sources.sort_unstable();
+        let Some((these, rest)) = incoming.split_at_checked(sources.len()) else {
+            return Err(mismatch());
+        };
+        if these != sources.as_slice() {
+            return Err(mismatch());
+        }
+        incoming = rest;
+    }
+    if incoming.is_empty() { Ok(()) } else { Err(mismatch()) }
+}
+
+impl Neighbors for MappedGraph {
+    fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.csr().edges(node)
+    }
+
+    fn degree(&self, node: usize) -> usize {
+        self.csr().degree(node)
+    }
+}
+
+impl Adjacency for MappedGraph {
+    fn reversed(&self) -> Reversed<'_> {
+        match self.reverse_csr() {
+            Some(reverse) => Reversed::Csr(reverse),
+            None => Reversed::Lists(self.rev_adj_list()),
+        }
+    }
+}
+
+/// Reinterprets an 8-byte aligned section of the mapping. Only used for plain
+/// integer and float types, for which every bit pattern is valid.
+fn cast<T: Copy>(bytes: &[u8]) -> &[T] {
+    // Safety: `T` is one of `u32`, `u64` or `f64`, and `align_to` only hands
+    // out the correctly aligned middle part.
+    let (head, body, tail) = unsafe { bytes.align_to::<T>() };
+    assert!(head.is_empty() && tail.is_empty(), "misaligned section");
+    body
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf29ce484222325)
+    }
+
+    fn hash(bytes: &[u8]) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.update(bytes);
+        hasher.0
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+impl Write for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.update(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bidirectional_dijkstra, parallel_dijkstra, sequential_dijkstra, Algorithm};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("binary_{}_{}.csr", name, std::process::id()))
+    }
+
+    fn sample_graph() -> Graph {
+        Graph::new(vec![
+            vec![(1, 2), (2, 4)],  // Node 0
+            vec![(2, 1)],          // Node 1
+            vec![(3, 3)],          // Node 2
+            vec![],                // Node 3
+        ]).with_coordinates(vec![(0.0, 0.0), (1.0, 0.5), (2.0, 0.0), (3.5, -1.25)])
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = temp_path("round_trip");
+        let graph = sample_graph();
+        write_binary_file(&graph, &path).unwrap();
+        let mapped = MappedGraph::open(&path).unwrap();
+
+        assert_eq!(mapped.num_nodes(), 4);
+        assert_eq!(mapped.num_edges(), 4);
+        assert_eq!(mapped.offsets(), &[0, 2, 3, 4, 4]);
+        assert_eq!(mapped.coordinate(3), Some((3.5, -1.25)));
+        assert_eq!(mapped.to_graph().adj_list, graph.adj_list);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_searches_run_on_mapped_graph() {
+        let path = temp_path("searches");
+        let graph = sample_graph();
+        write_binary_file(&graph, &path).unwrap();
+        let mapped = MappedGraph::open(&path).unwrap();
+
+        assert_eq!(sequential_dijkstra(&mapped, 0, 3), (6, vec![0, 1, 2, 3]));
+        assert_eq!(bidirectional_dijkstra(&mapped, 0, 3), (6, vec![0, 1, 2, 3]));
+        assert_eq!(parallel_dijkstra(&mapped, 0, 3), (6, vec![0, 1, 2, 3]));
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.run(&mapped, 0, 3).0, 6);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_corrupt_files() {
+        let path = temp_path("corrupt");
+        let mut bytes = Vec::new();
+        write_binary(&sample_graph(), &mut bytes).unwrap();
+
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, &flipped).unwrap();
+        assert!(MappedGraph::open(&path).unwrap_err().to_string().contains("checksum"));
+        assert!(MappedGraph::open_unverified(&path).is_ok());
+
+        let mut versioned = bytes.clone();
+        versioned[8] = 99;
+        std::fs::write(&path, &versioned).unwrap();
+        assert!(MappedGraph::open(&path).unwrap_err().to_string().contains("version 99"));
+
+        std::fs::write(&path, &bytes[..bytes.len() - 8]).unwrap();
+        assert!(MappedGraph::open(&path).is_err());
+
+        // Counts that overflow the section sizes are rejected, not wrapped
+        for (at, count) in [(16, u64::MAX), (24, u64::MAX / 4), (24, u64::MAX)] {
+            let mut huge = bytes.clone();
+            huge[at..at + 8].copy_from_slice(&count.to_le_bytes());
+            std::fs::write(&path, &huge).unwrap();
+            assert_eq!(MappedGraph::open_unverified(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+
+        // A reverse section with the right degrees but another weight or
+        // source, under a valid checksum
+        let reverse = Sections::new(4, 4, true, true).unwrap().reverse.unwrap();
+        for at in [reverse.weights.start, reverse.targets.start] {
+            let mut transposed = bytes.clone();
+            transposed[at] ^= 1;
+            let checksum = Fnv1a::hash(&transposed[HEADER_LEN..]);
+            transposed[32..40].copy_from_slice(&checksum.to_le_bytes());
+            std::fs::write(&path, &transposed).unwrap();
+            assert!(MappedGraph::open(&path).unwrap_err().to_string().contains("reverse adjacency"));
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_lends_reverse_adjacency() {
+        let path = temp_path("reverse");
+        let graph = sample_graph();
+        write_binary_file(&graph, &path).unwrap();
+        let mapped = MappedGraph::open(&path).unwrap();
+        let Reversed::Csr(reverse) = mapped.reversed() else {
+            panic!("reverse adjacency was rebuilt");
+        };
+        for node in 0..graph.num_nodes() {
+            assert_eq!(reverse.edges(node).collect::<Vec<_>>(), graph.rev_adj_list[node]);
+        }
+
+        // A version 1 file has no reverse sections; its reverse is built on demand
+        let mut bytes = Vec::new();
+        write_binary(&graph, &mut bytes).unwrap();
+        let forward = Sections::new(4, 4, false, false).unwrap();
+        let reverse = Sections::new(4, 4, true, false).unwrap().reverse.unwrap();
+        let mut old = bytes[..forward.end].to_vec();
+        old[8..12].copy_from_slice(&1u32.to_le_bytes());
+        old[12..16].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(bytes.len(), reverse.weights.end + 16 * 4);
+        std::fs::write(&path, &old).unwrap();
+        let mapped = MappedGraph::open_unverified(&path).unwrap();
+        assert!(matches!(mapped.reversed(), Reversed::Lists(_)));
+        assert_eq!(bidirectional_dijkstra(&mapped, 0, 3), (6, vec![0, 1, 2, 3]));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_large_mapped_graph() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let nodes = 20000;
+        let mut adj_list = vec![Vec::new(); nodes];
+        for _ in 0..100001 {
+            adj_list[rng.gen_range(0..nodes)].push((rng.gen_range(0..nodes), rng.gen_range(1..100)));
+        }
+        let graph = Graph::new(adj_list);
+        let path = temp_path("large");
+        write_binary_file(&graph, &path).unwrap();
+
+        let mapped = MappedGraph::open(&path).unwrap();
+        for goal in [1, nodes / 2, nodes - 1] {
+            assert_eq!(sequential_dijkstra(&mapped, 0, goal), sequential_dijkstra(&graph.adj_list, 0, goal));
+            assert_eq!(bidirectional_dijkstra(&mapped, 0, goal).0, bidirectional_dijkstra(&graph, 0, goal).0);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use rayon::prelude::*;
+use crate::graph::{Graph, UndirectedGraph};
+use crate::turn_costs::TurnCostTable;
+
+/// What to do with several edges between the same pair of nodes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParallelEdges {
+    KeepAll,
+    KeepMin,
+    Sum,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SelfLoops {
+    Keep,
+    Drop,
+    Reject,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BuildError {
+    NodeOutOfRange { node: usize, num_nodes: usize },
+    SelfLoop { node: usize },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::NodeOutOfRange { node, num_nodes } => {
+                write!(f, "node {} is out of range for a graph with {} nodes", node, num_nodes)
+            }
+            BuildError::SelfLoop { node } => write!(f, "self-loop on node {}", node),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Collects `(from, to, weight)` edges and builds adjacency lists by sorting
+/// the edge list in parallel, instead of pushing into nested `Vec`s by hand.
+#[derive(Clone, Debug)]
+pub struct GraphBuilder {
+    num_nodes: usize,
+    edges: Vec<(usize, usize, usize)>,
+    parallel_edges: ParallelEdges,
+    self_loops: SelfLoops,
+}
+
+impl GraphBuilder {
+    pub fn new(num_nodes: usize) -> Self {
+        GraphBuilder {
+            num_nodes,
+            edges: Vec::new(),
+            parallel_edges: ParallelEdges::KeepAll,
+            self_loops: SelfLoops::Keep,
+        }
+    }
+
+    pub fn parallel_edges(mut self, policy: ParallelEdges) -> Self {
+        self.parallel_edges = policy;
+        self
+    }
+
+    pub fn self_loops(mut self, policy: SelfLoops) -> Self {
+        self.self_loops = policy;
+        self
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: usize) -> &mut Self {
+        self.edges.push((from, to, weight));
+        self
+    }
+
+    pub fn edges<I: IntoIterator<Item = (usize, usize, usize)>>(mut self, edges: I) -> Self {
+        self.edges.extend(edges);
+        self
+    }
+
+    pub fn build_adj_list(self) -> Result<Vec<Vec<(usize, usize)>>, BuildError> {
+        let num_nodes = self.num_nodes;
+        let policy = self.parallel_edges;
+        let edges = self.validate()?;
+        Ok(group_by_source(edges, num_nodes, policy))
+    }
+
+    pub fn build(self) -> Result<Graph, BuildError> {
+        let num_nodes = self.num_nodes;
+        let adj_list = self.build_adj_list()?;
+        let reversed = adj_list.par_iter().enumerate()
+            .flat_map_iter(|(u, edges)| edges.iter().map(move |&(v, weight)| (v, u, weight)))
+            .collect();
+        let rev_adj_list = group_by_source(reversed, num_nodes, ParallelEdges::KeepAll);
+        Ok(Graph {
+            adj_list,
+            rev_adj_list,
+            turn_costs: TurnCostTable::new(),
+            coordinates: None,
+        })
+    }
+
+    /// Treats every edge as undirected, so `(u, v)` and `(v, u)` count as
+    /// parallel edges for the policy.
+    pub fn build_undirected(self) -> Result<UndirectedGraph, BuildError> {
+        let num_nodes = self.num_nodes;
+        let policy = self.parallel_edges;
+        let edges = self.validate()?;
+        let edges = edges.into_par_iter()
+            .flat_map_iter(|(u, v, weight)| {
+                let back = (u != v).then_some((v, u, weight));
+                std::iter::once((u, v, weight)).chain(back)
+            })
+            .collect();
+        Ok(UndirectedGraph { adj_list: group_by_source(edges, num_nodes, policy) })
+    }
+
+    fn validate(self) -> Result<Vec<(usize, usize, usize)>, BuildError> {
+        let num_nodes = self.num_nodes;
+        let out_of_range = self.edges.par_iter()
+            .flat_map_iter(|&(u, v, _)| [u, v])
+            .filter(|&node| node >= num_nodes)
+            .min();
+        if let Some(node) = out_of_range {
+            return Err(BuildError::NodeOutOfRange { node, num_nodes });
+        }
+
+        match self.self_loops {
+            SelfLoops::Keep => Ok(self.edges),
+            SelfLoops::Drop => Ok(self.edges.into_par_iter().filter(|&(u, v, _)| u != v).collect()),
+            SelfLoops::Reject => match self.edges.par_iter().filter(|&&(u, v, _)| u == v).map(|&(u, _, _)| u).min() {
+                Some(node) => Err(BuildError::SelfLoop { node }),
+                None => Ok(self.edges),
+            },
+        }
+    }
+}
+
+fn group_by_source(mut edges: Vec<(usize, usize, usize)>, num_nodes: usize, policy: ParallelEdges) -> Vec<Vec<(usize, usize)>> {
+    // Stable for `KeepAll` so parallel edges keep their insertion order.
+    match policy {
+        ParallelEdges::KeepAll => edges.par_sort_by_key(|&(u, v, _)| (u, v)),
+        _ => edges.par_sort_unstable(),
+    }
+
+    (0..num_nodes).into_par_iter().map(|u| {
+        let begin = edges.partition_point(|&(from, _, _)| from < u);
+        let end = edges.partition_point(|&(from, _, _)| from <= u);
+        let outgoing = &edges[begin..end];
+        match policy {
+            ParallelEdges::KeepAll => outgoing.iter().map(|&(_, v, weight)| (v, weight)).collect(),
+            ParallelEdges::KeepMin => outgoing.chunk_by(|a, b| a.1 == b.1)
+                .map(|group| (group[0].1, group[0].2))
+                .collect(),
+            ParallelEdges::Sum => outgoing.chunk_by(|a, b| a.1 == b.1)
+                .map(|group| (group[0].1, group.iter().fold(0usize, |sum, e| sum.saturating_add(e.2))))
+                .collect(),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_dijkstra::sequential_dijkstra;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_parallel_edge_policies() {
+        let edges = vec![(0, 1, 5), (0, 2, 4), (0, 1, 2), (1, 2, 1), (0, 1, 7)];
+
+        let keep_all = GraphBuilder::new(3).edges(edges.clone()).build_adj_list().unwrap();
+        assert_eq!(keep_all[0], vec![(1, 5), (1, 2), (1, 7), (2, 4)]);
+
+        let keep_min = GraphBuilder::new(3).edges(edges.clone())
+            .parallel_edges(ParallelEdges::KeepMin)
+            .build_adj_list().unwrap();
+        assert_eq!(keep_min, vec![vec![(1, 2), (2, 4)], vec![(2, 1)], vec![]]);
+
+        let sum = GraphBuilder::new(3).edges(edges)
+            .parallel_edges(ParallelEdges::Sum)
+            .build_adj_list().unwrap();
+        assert_eq!(sum[0], vec![(1, 14), (2, 4)]);
+    }
+
+    #[test]
+    fn test_validation() {
+        let err = GraphBuilder::new(3).edges(vec![(0, 1, 1), (2, 3, 1)]).build().unwrap_err();
+        assert_eq!(err, BuildError::NodeOutOfRange { node: 3, num_nodes: 3 });
+
+        let loops = vec![(0, 0, 1), (0, 1, 1), (1, 1, 2)];
+        let err = GraphBuilder::new(2).edges(loops.clone()).self_loops(SelfLoops::Reject).build().unwrap_err();
+        assert_eq!(err, BuildError::SelfLoop { node: 0 });
+
+        let dropped = GraphBuilder::new(2).edges(loops).self_loops(SelfLoops::Drop).build_adj_list().unwrap();
+        assert_eq!(dropped, vec![vec![(1, 1)], vec![]]);
+    }
+
+    #[test]
+    fn test_build_graph_and_undirected() {
+        let mut builder = GraphBuilder::new(3);
+        builder.add_edge(0, 1, 2).add_edge(1, 2, 3).add_edge(2, 1, 1);
+
+        let graph = builder.clone().build().unwrap();
+        assert_eq!(graph.rev_adj_list, Graph::new(graph.adj_list.clone()).rev_adj_list);
+
+        let undirected = builder.parallel_edges(ParallelEdges::KeepMin).build_undirected().unwrap();
+        assert_eq!(undirected.adj_list, vec![vec![(1, 2)], vec![(0, 2), (2, 1)], vec![(1, 1)]]);
+    }
+
+    #[test]
+    fn test_large_build_matches_nested_vecs() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let nodes = 20000;
+        let edges: Vec<_> = (0..200000)
+            .map(|_| (rng.gen_range(0..nodes), rng.gen_range(0..nodes), rng.gen_range(1..100)))
+            .collect();
+
+        let mut nested = vec![Vec::new(); nodes];
+        for &(u, v, weight) in &edges {
+            nested[u].push((v, weight));
+        }
+
+        let graph = GraphBuilder::new(nodes).edges(edges).build().unwrap();
+
+        for (u, edges) in nested.iter_mut().enumerate() {
+            edges.sort_by_key(|&(v, _)| v);
+            assert_eq!(&graph.adj_list[u], edges);
+        }
+        assert_eq!(sequential_dijkstra(&graph.adj_list, 0, nodes - 1).0, sequential_dijkstra(&nested, 0, nodes - 1).0);
+    }
+}
@@ -0,0 +1,576 @@
+// Command-line front end: run queries on graph files and convert between formats.
+
+use my_dijkstra_crate::binary::{write_binary_file, MappedGraph};
+use my_dijkstra_crate::dimacs::{read_gr, read_p2p, write_gr};
+use my_dijkstra_crate::dot::{read_dot, write_dot};
+use my_dijkstra_crate::edge_list::{read_edges_file, write_edges_file, CsvOptions};
+use my_dijkstra_crate::graphml::{read_graphml, write_graphml};
+use my_dijkstra_crate::harness::json_string;
+use my_dijkstra_crate::matrix_market::{read_matrix_market, write_matrix_market};
+use my_dijkstra_crate::osm::{load_osm, OsmOptions};
+use my_dijkstra_crate::{dial_dijkstra_with_max_weight, select_algorithm, sequential_sssp, Adjacency, Algorithm, Graph, GraphProfile, IdMap, Neighbors};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Instant;
+
+/// Evaluates `$body` with `$graph` bound to the loaded graph, whichever kind it is.
+macro_rules! on_graph {
+    ($loaded:expr, |$graph:ident| $body:expr) => {
+        match &$loaded.graph {
+            LoadedGraph::Owned($graph) => $body,
+            LoadedGraph::Mapped($graph) => $body,
+        }
+    };
+}
+
+const USAGE: &str = "\
+Usage: dijkstra <command> [arguments] [options]
+
+Commands:
+  query <graph> <start> <goal>   Shortest path between two nodes
+  batch <graph> <queries>        One query per line: `<start> <goal>` (or a DIMACS .p2p file)
+  sssp <graph> <source>          Distances from one node to every node
+  stats <graph>                  Node, edge, degree and weight statistics
+  convert <input> <output>       Convert between graph formats
+
+Options:
+  --algorithm <name>   auto, sequential, bidirectional, parallel, parallel-bidirectional or dial (default: auto,
+                       which picks one from the graph's size, density and weights)
+  --output <kind>      table or json (default: table)
+  --format <format>    Input format, if the extension does not tell
+  --to <format>        Output format for `convert`, if the extension does not tell
+
+Formats: dimacs (.gr), edges (.txt, .edges), csv (.csv), binary (.csr, .bin), osm (.osm, .pbf, read only),
+graphml (.graphml), dot (.dot, .gv), mtx (.mtx).
+Nodes are given by label when the format has labels, otherwise by 0-based id.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let stdout = io::stdout();
+    match run(&args, &mut stdout.lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String], out: &mut dyn Write) -> Result<(), String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(format!("missing command\n\n{}", USAGE));
+    };
+    if matches!(command.as_str(), "help" | "-h" | "--help") {
+        writeln!(out, "{}", USAGE).map_err(|err| err.to_string())?;
+        return Ok(());
+    }
+    let args = Args::parse(rest)?;
+    let output = match args.option("output") {
+        None | Some("table") => Output::Table,
+        Some("json") => Output::Json,
+        Some(other) => return Err(format!("unknown output `{}` (expected table or json)", other)),
+    };
+    let algorithm = match args.option("algorithm") {
+        None | Some("auto") => None,
+        Some(name) => Some(Algorithm::from_str(name)?),
+    };
+
+    let result = match command.as_str() {
+        "query" => {
+            let [graph, start, goal] = args.positional(["graph", "start", "goal"])?;
+            let loaded = load(graph, args.option("format"))?;
+            let algorithm = algorithm.unwrap_or_else(|| on_graph!(loaded, |graph| select_algorithm(graph).algorithm));
+            query(&loaded, loaded.node(start)?, loaded.node(goal)?, algorithm, output, out)
+        }
+        "batch" => {
+            let [graph, queries] = args.positional(["graph", "queries"])?;
+            let loaded = load(graph, args.option("format"))?;
+            let queries = read_queries(&loaded, queries)?;
+            let algorithm = algorithm.unwrap_or_else(|| on_graph!(loaded, |graph| select_algorithm(graph).algorithm));
+            batch(&loaded, &queries, algorithm, output, out)
+        }
+        "sssp" => {
+            let [graph, source] = args.positional(["graph", "source"])?;
+            let loaded = load(graph, args.option("format"))?;
+            sssp(&loaded, loaded.node(source)?, output, out)
+        }
+        "stats" => {
+            let [graph] = args.positional(["graph"])?;
+            let loaded = load(graph, args.option("format"))?;
+            match &loaded.graph {
+                LoadedGraph::Owned(graph) => stats(graph, graph.coordinates.is_some(), output, out),
+                LoadedGraph::Mapped(graph) => stats(graph, graph.has_coordinates(), output, out),
+            }
+        }
+        "convert" => {
+            let [input, output_path] = args.positional(["input", "output"])?;
+            let loaded = load(input, args.option("format"))?;
+            let format = Format::resolve(output_path, args.option("to"))?;
+            save(&loaded, output_path, format).map_err(|err| format!("{}: {}", output_path, err))?;
+            writeln!(out, "wrote {} nodes and {} edges to {}", loaded.num_nodes(), loaded.num_edges(), output_path)
+        }
+        other => return Err(format!("unknown command `{}`\n\n{}", other, USAGE)),
+    };
+    result.map_err(|err| err.to_string())
+}
+
+/// Positional arguments and `--name value` / `--name=value` options.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                positional.push(arg.clone());
+                continue;
+            };
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name, value.to_string()),
+                None => (name, iter.next().ok_or_else(|| format!("option --{} needs a value", name))?.clone()),
+            };
+            if !matches!(name, "algorithm" | "output" | "format" | "to") {
+                return Err(format!("unknown option --{}", name));
+            }
+            options.insert(name.to_string(), value);
+        }
+        Ok(Args { positional, options })
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn positional<const N: usize>(&self, names: [&str; N]) -> Result<[&str; N], String> {
+        if self.positional.len() != N {
+            return Err(format!("expected arguments: <{}>", names.join("> <")));
+        }
+        Ok(std::array::from_fn(|i| self.positional[i].as_str()))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Output {
+    Table,
+    Json,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Format {
+    Dimacs,
+    Edges,
+    Csv,
+    Binary,
+    Osm,
+    GraphMl,
+    Dot,
+    MatrixMarket,
+}
+
+impl Format {
+    /// `explicit` (from `--format` / `--to`) wins over the file extension.
+    fn resolve(path: &str, explicit: Option<&str>) -> Result<Format, String> {
+        let name = match explicit {
+            Some(name) => name.to_string(),
+            None => Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("").to_ascii_lowercase(),
+        };
+        match name.as_str() {
+            "dimacs" | "gr" => Ok(Format::Dimacs),
+            "edges" | "txt" => Ok(Format::Edges),
+            "csv" => Ok(Format::Csv),
+            "binary" | "csr" | "bin" => Ok(Format::Binary),
+            "osm" | "pbf" => Ok(Format::Osm),
+            "graphml" => Ok(Format::GraphMl),
+            "dot" | "gv" => Ok(Format::Dot),
+            "mtx" => Ok(Format::MatrixMarket),
+            _ => Err(format!("cannot tell the format of `{}`; pass --format or --to", path)),
+        }
+    }
+}
+
+/// A graph plus the node labels of its file, for formats that have them.
+struct Loaded {
+    graph: LoadedGraph,
+    labels: Option<IdMap>,
+}
+
+/// Binary graphs stay mapped; the searches run on either kind.
+enum LoadedGraph {
+    Owned(Graph),
+    Mapped(MappedGraph),
+}
+
+impl Loaded {
+    fn owned(graph: Graph, labels: Option<IdMap>) -> Self {
+        Loaded { graph: LoadedGraph::Owned(graph), labels }
+    }
+
+    fn num_nodes(&self) -> usize {
+        on_graph!(self, |graph| graph.num_nodes())
+    }
+
+    fn num_edges(&self) -> usize {
+        on_graph!(self, |graph| graph.num_edges())
+    }
+
+    /// The graph as a `Graph`, copying a mapped one.
+    fn to_graph(&self) -> Cow<'_, Graph> {
+        match &self.graph {
+            LoadedGraph::Owned(graph) => Cow::Borrowed(graph),
+            LoadedGraph::Mapped(mapped) => Cow::Owned(mapped.to_graph()),
+        }
+    }
+
+    fn node(&self, name: &str) -> Result<usize, String> {
+        let node = match &self.labels {
+            Some(labels) => labels.id(name),
+            None => name.parse().ok(),
+        };
+        node.filter(|&node| node < self.num_nodes())
+            .ok_or_else(|| format!("unknown node `{}`", name))
+    }
+
+    fn name(&self, node: usize) -> String {
+        match &self.labels {
+            Some(labels) => labels.label(node).to_string(),
+            None => node.to_string(),
+        }
+    }
+}
+
+fn load(path: &str, format: Option<&str>) -> Result<Loaded, String> {
+    let format = Format::resolve(path, format)?;
+    let open = || File::open(path).map(BufReader::new);
+    let loaded = match format {
+        Format::Dimacs => read_gr(open().map_err(|err| format!("{}: {}", path, err))?).map(|graph| Loaded::owned(graph, None)),
+        Format::Edges => read_edges_file(path, &CsvOptions::edge_list()).map(|(graph, _)| Loaded::owned(graph, None)),
+        Format::Csv => read_edges_file(path, &CsvOptions::csv()).map(|(graph, labels)| Loaded::owned(graph, Some(labels))),
+        Format::Binary => MappedGraph::open(path).map(|mapped| Loaded { graph: LoadedGraph::Mapped(mapped), labels: None }),
+        Format::Osm => load_osm(path, &OsmOptions::default()).map(|osm| {
+            let labels = IdMap::try_from(osm.osm_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>())
+                .expect("OSM node ids are unique");
+            Loaded::owned(osm.graph, Some(labels))
+        }),
+        Format::GraphMl => open().and_then(read_graphml).map(|(graph, labels)| Loaded::owned(graph, Some(labels))),
+        Format::Dot => open().and_then(read_dot).map(|(graph, labels)| Loaded::owned(graph, Some(labels))),
+        Format::MatrixMarket => open().and_then(read_matrix_market).map(|graph| Loaded::owned(graph, None)),
+    };
+    loaded.map_err(|err| format!("{}: {}", path, err))
+}
+
+fn save(loaded: &Loaded, path: &str, format: Format) -> io::Result<()> {
+    let graph = &*loaded.to_graph();
+    // Label formats get the ids as labels, so the same node names work on the output.
+    let identity;
+    let labels = match &loaded.labels {
+        Some(labels) => labels,
+        None => {
+            identity = IdMap::identity(graph.num_nodes());
+            &identity
+        }
+    };
+    let labels = Some(labels);
+    match format {
+        Format::Dimacs => write_gr(graph, File::create(path)?),
+        Format::Edges => write_edges_file(graph, None, &CsvOptions::edge_list(), path),
+        Format::Csv => write_edges_file(graph, labels, &CsvOptions::csv(), path),
+        Format::Binary => write_binary_file(graph, path),
+        Format::Osm => Err(io::Error::new(io::ErrorKind::Unsupported, "OSM files cannot be written")),
+        Format::GraphMl => write_graphml(graph, labels, File::create(path)?),
+        Format::Dot => write_dot(graph, labels, File::create(path)?),
+        Format::MatrixMarket => write_matrix_market(graph, File::create(path)?),
+    }
+}
+
+fn read_queries(loaded: &Loaded, path: &str) -> Result<Vec<(usize, usize)>, String> {
+    let reader = BufReader::new(File::open(path).map_err(|err| format!("{}: {}", path, err))?);
+    if path.ends_with(".p2p") {
+        let queries = read_p2p(reader).map_err(|err| format!("{}: {}", path, err))?;
+        // Report the 1-based id as written in the file
+        if let Some(node) = queries.iter().flat_map(|&(start, goal)| [start, goal]).find(|&node| node >= loaded.num_nodes()) {
+            return Err(format!("{}: unknown node `{}`", path, node + 1));
+        }
+        return Ok(queries);
+    }
+    let mut queries = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| format!("{}: {}", path, err))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() != 2 {
+            return Err(format!("{}: line {}: expected `<start> <goal>`", path, index + 1));
+        }
+        let node = |name| loaded.node(name).map_err(|err| format!("{}: line {}: {}", path, index + 1, err));
+        queries.push((node(parts[0])?, node(parts[1])?));
+    }
+    Ok(queries)
+}
+
+fn query(loaded: &Loaded, start: usize, goal: usize, algorithm: Algorithm, output: Output, out: &mut dyn Write) -> io::Result<()> {
+    let start_time = Instant::now();
+    let (cost, path) = on_graph!(loaded, |graph| algorithm.run(graph, start, goal));
+    let millis = start_time.elapsed().as_secs_f64() * 1000.0;
+    let path: Vec<String> = path.iter().map(|&node| loaded.name(node)).collect();
+
+    match output {
+        Output::Table => {
+            write_table(out, &["", ""], &[
+                vec!["algorithm".to_string(), algorithm.to_string()],
+                vec!["start".to_string(), loaded.name(start)],
+                vec!["goal".to_string(), loaded.name(goal)],
+                vec!["cost".to_string(), format_cost(cost)],
+                vec!["path".to_string(), path.join(" -> ")],
+                vec!["time (ms)".to_string(), format!("{:.3}", millis)],
+            ], false)
+        }
+        Output::Json => {
+            let path: Vec<String> = path.iter().map(|name| json_string(name)).collect();
+            writeln!(out, "{{\"algorithm\": {}, \"start\": {}, \"goal\": {}, \"cost\": {}, \"path\": [{}], \"time_ms\": {:.3}}}",
+                json_string(algorithm.name()), json_string(&loaded.name(start)), json_string(&loaded.name(goal)),
+                json_cost(cost), path.join(", "), millis)
+        }
+    }
+}
+
+fn batch(loaded: &Loaded, queries: &[(usize, usize)], algorithm: Algorithm, output: Output, out: &mut dyn Write) -> io::Result<()> {
+    let total_time = Instant::now();
+    // Find the largest weight once rather than in every Dial query
+    let results: Vec<(usize, usize, usize, usize, f64)> = on_graph!(loaded, |graph| {
+        let dial_max_weight = (algorithm == Algorithm::Dial).then(|| GraphProfile::of(graph).weight_range.map_or(0, |(_, max)| max));
+        queries.iter().map(|&(start, goal)| {
+            let start_time = Instant::now();
+            let (cost, path) = match dial_max_weight {
+                Some(max_weight) => dial_dijkstra_with_max_weight(graph, max_weight, start, goal),
+                None => algorithm.run(graph, start, goal),
+            };
+            (start, goal, cost, path.len(), start_time.elapsed().as_secs_f64() * 1000.0)
+        }).collect()
+    });
+    let total_millis = total_time.elapsed().as_secs_f64() * 1000.0;
+
+    match output {
+        Output::Table => {
+            let rows: Vec<Vec<String>> = results.iter().map(|&(start, goal, cost, nodes, millis)| {
+                vec![loaded.name(start), loaded.name(goal), format_cost(cost), nodes.to_string(), format!("{:.3}", millis)]
+            }).collect();
+            write_table(out, &["start", "goal", "cost", "nodes", "time (ms)"], &rows, true)?;
+            writeln!(out, "{} queries with {} in {:.3} ms", results.len(), algorithm, total_millis)
+        }
+        Output::Json => {
+            let entries: Vec<String> = results.iter().map(|&(start, goal, cost, nodes, millis)| {
+                format!("{{\"start\": {}, \"goal\": {}, \"cost\": {}, \"nodes\": {}, \"time_ms\": {:.3}}}",
+                    json_string(&loaded.name(start)), json_string(&loaded.name(goal)), json_cost(cost), nodes, millis)
+            }).collect();
+            writeln!(out, "{{\"algorithm\": {}, \"time_ms\": {:.3}, \"results\": [{}]}}",
+                json_string(algorithm.name()), total_millis, entries.join(", "))
+        }
+    }
+}
+
+fn sssp(loaded: &Loaded, source: usize, output: Output, out: &mut dyn Write) -> io::Result<()> {
+    let (dist, _) = on_graph!(loaded, |graph| sequential_sssp(graph, source));
+    match output {
+        Output::Table => {
+            let rows: Vec<Vec<String>> = dist.iter().enumerate()
+                .map(|(node, &cost)| vec![loaded.name(node), format_cost(cost)])
+                .collect();
+            write_table(out, &["node", "distance"], &rows, true)
+        }
+        Output::Json => {
+            let entries: Vec<String> = dist.iter().enumerate()
+                .map(|(node, &cost)| format!("{}: {}", json_string(&loaded.name(node)), json_cost(cost)))
+                .collect();
+            writeln!(out, "{{\"source\": {}, \"distances\": {{{}}}}}", json_string(&loaded.name(source)), entries.join(", "))
+        }
+    }
+}
+
+fn stats<G: Adjacency + ?Sized>(graph: &G, coordinates: bool, output: Output, out: &mut dyn Write) -> io::Result<()> {
+    let nodes = graph.num_nodes();
+    let degrees: Vec<usize> = (0..nodes).map(|u| graph.degree(u)).collect();
+    let weights = || (0..nodes).flat_map(|u| graph.neighbors(u)).map(|(_, weight)| weight);
+    let self_loops = (0..nodes)
+        .map(|u| graph.neighbors(u).filter(|&(v, _)| v == u).count())
+        .sum::<usize>();
+    let edges = degrees.iter().sum::<usize>();
+    let average = |total: usize, count: usize| if count == 0 { 0.0 } else { total as f64 / count as f64 };
+
+    let fields: Vec<(&str, String)> = vec![
+        ("nodes", nodes.to_string()),
+        ("edges", edges.to_string()),
+        ("min_out_degree", degrees.iter().min().copied().unwrap_or(0).to_string()),
+        ("max_out_degree", degrees.iter().max().copied().unwrap_or(0).to_string()),
+        ("avg_out_degree", format!("{:.3}", average(edges, nodes))),
+        ("sinks", degrees.iter().filter(|&&d| d == 0).count().to_string()),
+        ("self_loops", self_loops.to_string()),
+        ("min_weight", weights().min().unwrap_or(0).to_string()),
+        ("max_weight", weights().max().unwrap_or(0).to_string()),
+        ("avg_weight", format!("{:.3}", average(weights().sum(), edges))),
+        ("weak_components", weak_components(graph).to_string()),
+        ("coordinates", coordinates.to_string()),
+    ];
+    match output {
+        Output::Table => {
+            let rows: Vec<Vec<String>> = fields.into_iter().map(|(name, value)| vec![name.to_string(), value]).collect();
+            write_table(out, &["", ""], &rows, false)
+        }
+        Output::Json => {
+            let entries: Vec<String> = fields.iter().map(|(name, value)| format!("{}: {}", json_string(name), value)).collect();
+            writeln!(out, "{{{}}}", entries.join(", "))
+        }
+    }
+}
+
+fn weak_components<G: Adjacency + ?Sized>(graph: &G) -> usize {
+    let reversed = graph.reversed();
+    let mut seen = vec![false; graph.num_nodes()];
+    let mut components = 0;
+    let mut stack = Vec::new();
+    for root in 0..graph.num_nodes() {
+        if seen[root] {
+            continue;
+        }
+        components += 1;
+        seen[root] = true;
+        stack.push(root);
+        while let Some(u) = stack.pop() {
+            for (v, _) in graph.neighbors(u).chain(reversed.neighbors(u)) {
+                if !seen[v] {
+                    seen[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+    }
+    components
+}
+
+/// Left-aligned columns separated by two spaces.
+fn write_table(out: &mut dyn Write, header: &[&str], rows: &[Vec<String>], show_header: bool) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|name| if show_header { name.len() } else { 0 }).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells.iter().zip(&widths).map(|(cell, &width)| format!("{:<width$}", cell, width = width)).collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    if show_header {
+        writeln!(out, "{}", line(header.to_vec()))?;
+    }
+    for row in rows {
+        writeln!(out, "{}", line(row.iter().map(String::as_str).collect()))?;
+    }
+    Ok(())
+}
+
+fn format_cost(cost: usize) -> String {
+    if cost == usize::MAX { "unreachable".to_string() } else { cost.to_string() }
+}
+
+fn json_cost(cost: usize) -> String {
+    if cost == usize::MAX { "null".to_string() } else { cost.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_to_string(args: &[&str]) -> Result<String, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut out = Vec::new();
+        run(&args, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn temp_file(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("cli_{}_{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_query_and_batch() {
+        let graph = temp_file("roads.csv", "source,target,weight\nHarbour,Market,4\nMarket,\"Station, North\",3\nHarbour,\"Station, North\",9\nDepot,Harbour,1\n");
+        let table = run_to_string(&["query", &graph, "Harbour", "Station, North"]).unwrap();
+        assert!(table.contains("algorithm  sequential\n"), "{}", table);  // Picked for a tiny graph
+        assert!(table.contains("cost       7\n"), "{}", table);
+        assert!(table.contains("path       Harbour -> Market -> Station, North\n"), "{}", table);
+
+        let json = run_to_string(&["query", &graph, "Station, North", "Depot", "--output=json", "--algorithm", "sequential"]).unwrap();
+        assert!(json.starts_with("{\"algorithm\": \"sequential\", \"start\": \"Station, North\", \"goal\": \"Depot\", \"cost\": null, \"path\": [],"), "{}", json);
+
+        let queries = temp_file("queries.txt", "# start goal\nHarbour Market\nDepot Market\n");
+        let table = run_to_string(&["batch", &graph, &queries]).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[0].starts_with("start    goal    cost  nodes  time (ms)"), "{}", table);
+        assert!(lines[2].starts_with("Depot    Market  5     3"), "{}", table);
+        let dial = run_to_string(&["batch", &graph, &queries, "--algorithm", "dial"]).unwrap();
+        assert!(dial.lines().nth(2).unwrap().starts_with("Depot    Market  5     3"), "{}", dial);
+
+        assert!(run_to_string(&["query", &graph, "Harbour", "Nowhere"]).unwrap_err().contains("unknown node `Nowhere`"));
+        let dimacs = temp_file("line.gr", "p sp 3 2\na 1 2 1\na 2 3 1\n");
+        let p2p = temp_file("line.p2p", "p aux sp p2p 2\nq 1 3\nq 1 9\n");
+        assert!(run_to_string(&["batch", &dimacs, &p2p]).unwrap_err().contains("unknown node `9`"));
+        assert!(run_to_string(&["query", &graph, "Harbour"]).unwrap_err().contains("expected arguments"));
+        assert!(run_to_string(&["query", &graph, "Harbour", "Market", "--algorithm", "astar"]).is_err());
+    }
+
+    #[test]
+    fn test_sssp_and_stats() {
+        let graph = temp_file("small.txt", "0 1 2\n1 2 3\n0 2 9\n3 3 1\n");
+        let table = run_to_string(&["sssp", &graph, "0"]).unwrap();
+        assert_eq!(table, "node  distance\n0     0\n1     2\n2     5\n3     unreachable\n");
+        let json = run_to_string(&["sssp", &graph, "1", "--output", "json"]).unwrap();
+        assert_eq!(json, "{\"source\": \"1\", \"distances\": {\"0\": null, \"1\": 0, \"2\": 3, \"3\": null}}\n");
+
+        let json = run_to_string(&["stats", &graph, "--output", "json"]).unwrap();
+        assert!(json.contains("\"nodes\": 4, \"edges\": 4,"), "{}", json);
+        assert!(json.contains("\"self_loops\": 1,"), "{}", json);
+        assert!(json.contains("\"max_weight\": 9, \"avg_weight\": 3.750, \"weak_components\": 2,"), "{}", json);
+
+        // A mapped binary graph is searched in place and gives the same answers.
+        let mapped = std::env::temp_dir().join(format!("cli_{}_small.csr", std::process::id()));
+        let mapped = mapped.to_str().unwrap();
+        run_to_string(&["convert", &graph, mapped]).unwrap();
+        assert_eq!(run_to_string(&["sssp", mapped, "0"]).unwrap(), table);
+        assert_eq!(run_to_string(&["stats", mapped, "--output", "json"]).unwrap(), json);
+    }
+
+    #[test]
+    fn test_convert() {
+        let graph = temp_file("convert.txt", "0 1 2\n1 2 3\n0 2 9\n");
+        for (extension, format) in [("gr", None), ("csr", None), ("mtx", None), ("graphml", None), ("dot", None), ("out", Some("edges"))] {
+            let output = std::env::temp_dir().join(format!("cli_{}_converted.{}", std::process::id(), extension));
+            let output = output.to_str().unwrap();
+            let mut args = vec!["convert", &graph, output];
+            if let Some(format) = format {
+                args.extend(["--to", format]);
+            }
+            assert!(run_to_string(&args).unwrap().starts_with("wrote 3 nodes and 3 edges"));
+
+            let mut args = vec!["query", output, "0", "2", "--output", "json"];
+            if let Some(format) = format {
+                args.extend(["--format", format]);
+            }
+            let json = run_to_string(&args).unwrap();
+            assert!(json.contains("\"cost\": 5, \"path\": [\"0\", \"1\", \"2\"]"), "{}: {}", extension, json);
+        }
+        assert!(run_to_string(&["convert", &graph, "out.unknown"]).unwrap_err().contains("cannot tell the format"));
+    }
+}
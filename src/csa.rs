@@ -0,0 +1,105 @@
+use crate::timetable::Timetable;
+
+#[derive(Copy, Clone)]
+enum Leg {
+    Ride(usize),
+    Walk(usize),
+}
+
+/// Earliest-arrival query with the Connection Scan Algorithm. Returns the
+/// arrival time at `target` and the stops where the journey boards, alights or
+/// walks, or `(usize::MAX, [])` if `target` cannot be reached.
+pub fn csa_earliest_arrival(timetable: &Timetable, source: usize, target: usize, departure: usize) -> (usize, Vec<usize>) {
+    if source == target {
+        return (departure, vec![source]);
+    }
+
+    let num_stops = timetable.stops.len();
+    let mut arrival = vec![usize::MAX; num_stops];
+    let mut legs: Vec<Option<Leg>> = vec![None; num_stops];
+    let mut boarded_at: Vec<Option<usize>> = vec![None; timetable.trip_ids.len()];
+
+    arrival[source] = departure;
+    for &(stop, walk) in &timetable.footpaths[source] {
+        arrival[stop] = departure.saturating_add(walk);
+        legs[stop] = Some(Leg::Walk(source));
+    }
+
+    let first = timetable.connections.partition_point(|c| c.dep_time < departure);
+    for c in &timetable.connections[first..] {
+        if arrival[target] <= c.dep_time {
+            break;
+        }
+
+        if boarded_at[c.trip].is_none() && arrival[c.dep_stop] <= c.dep_time {
+            boarded_at[c.trip] = Some(c.dep_stop);
+        }
+        let Some(board) = boarded_at[c.trip] else {
+            continue;
+        };
+
+        if c.arr_time < arrival[c.arr_stop] {
+            arrival[c.arr_stop] = c.arr_time;
+            legs[c.arr_stop] = Some(Leg::Ride(board));
+            for &(stop, walk) in &timetable.footpaths[c.arr_stop] {
+                let walk_arrival = c.arr_time.saturating_add(walk);
+                if walk_arrival < arrival[stop] {
+                    arrival[stop] = walk_arrival;
+                    legs[stop] = Some(Leg::Walk(c.arr_stop));
+                }
+            }
+        }
+    }
+
+    if arrival[target] == usize::MAX {
+        return (usize::MAX, Vec::new());
+    }
+    (arrival[target], reconstruct_journey(target, &legs))
+}
+
+fn reconstruct_journey(target: usize, legs: &[Option<Leg>]) -> Vec<usize> {
+    let mut path = vec![target];
+    let mut current = target;
+    // Zero-duration hops could form a cycle of equal arrival times.
+    while path.len() <= legs.len() {
+        match legs[current] {
+            Some(Leg::Ride(stop)) | Some(Leg::Walk(stop)) => {
+                path.push(stop);
+                current = stop;
+            }
+            None => break,
+        }
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timetable::sample_timetable;
+
+    #[test]
+    fn test_walk_transfer_beats_direct_trip() {
+        let timetable = sample_timetable();
+        let (arrival, path) = csa_earliest_arrival(&timetable, 0, 2, 90);
+        assert_eq!(arrival, 210);  // T1 arrives at 3 at 150, walk 60
+        assert_eq!(path, vec![0, 3, 2]);
+    }
+
+    #[test]
+    fn test_trip_transfer() {
+        let timetable = sample_timetable();
+        let (arrival, path) = csa_earliest_arrival(&timetable, 0, 4, 90);
+        assert_eq!(arrival, 300);
+        assert_eq!(path, vec![0, 1, 4]);
+    }
+
+    #[test]
+    fn test_missed_departure() {
+        let timetable = sample_timetable();
+        let (arrival, path) = csa_earliest_arrival(&timetable, 0, 4, 130);
+        assert_eq!(arrival, usize::MAX);
+        assert!(path.is_empty());
+    }
+}
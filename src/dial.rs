@@ -0,0 +1,190 @@
+use crate::graph::Neighbors;
+use crate::overlay::{effective_weight, is_banned, Overlay};
+use crate::priority_queue::LazyBinaryHeap;
+use crate::search_stats::{record_settled, SearchStats};
+
+/// Largest ring of buckets Dial's algorithm builds. Beyond it the ring, and
+/// the walk over its empty buckets, cost more than a heap saves.
+pub const DIAL_MAX_BUCKETS: usize = 1 << 16;
+
+/// Dial's algorithm: Dijkstra with a bucket queue instead of a heap. All
+/// queued distances lie within `max_weight` of the current one, so a ring of
+/// `max_weight + 1` buckets indexed by `distance % (max_weight + 1)` holds
+/// them without collisions, and each queue operation is O(1). Worth it when
+/// weights are small integers. Finding `max_weight` takes a scan over every
+/// edge, and with weights of `DIAL_MAX_BUCKETS` or more the search runs on a
+/// binary heap instead.
+pub fn dial_dijkstra<G: Neighbors + ?Sized>(graph: &G, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search(graph, None, None, None, start, goal)
+}
+
+/// `dial_dijkstra` without the scan for the largest weight, for callers
+/// that already know it. An edge heavier than `max_weight` lands in a bucket
+/// the search has already passed: debug builds panic on it, release builds
+/// may skip nodes behind it and return a longer path or none.
+pub fn dial_dijkstra_with_max_weight<G: Neighbors + ?Sized>(graph: &G, max_weight: usize, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search(graph, None, None, Some(max_weight), start, goal)
+}
+
+/// `dial_dijkstra` with the bans and weight overrides of `overlay`.
+pub fn dial_dijkstra_with_overlay<G: Neighbors + ?Sized>(graph: &G, overlay: &Overlay, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search(graph, Some(overlay), None, None, start, goal)
+}
+
+/// `dial_dijkstra` that counts its work in `stats`.
+pub fn dial_dijkstra_with_stats<G: Neighbors + ?Sized>(graph: &G, stats: &SearchStats, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search(graph, None, Some(stats), None, start, goal)
+}
+
+pub(crate) fn search<G: Neighbors + ?Sized>(graph: &G, overlay: Option<&Overlay>, stats: Option<&SearchStats>, max_weight: Option<usize>, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    if is_banned(overlay, start) || is_banned(overlay, goal) {
+        return (usize::MAX, Vec::new());
+    }
+    if start == goal {
+        return (0, vec![start]);
+    }
+
+    let max_weight = max_weight.unwrap_or_else(|| {
+        (0..graph.num_nodes())
+            .flat_map(|node| {
+                graph.neighbors(node).enumerate()
+                    .filter_map(move |(i, (neighbor, weight))| effective_weight(overlay, node, i, neighbor, weight))
+            })
+            .max()
+            .unwrap_or(0)
+    });
+    if max_weight >= DIAL_MAX_BUCKETS {
+        return crate::standard_dijkstra::search::<_, LazyBinaryHeap>(graph, overlay, stats, start, goal);
+    }
+    let ring = max_weight + 1;
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); ring];
+    let mut dist = vec![usize::MAX; graph.num_nodes()];
+    let mut prev = vec![None; graph.num_nodes()];
+
+    dist[start] = 0;
+    buckets[0].push(start);
+    let mut queued = 1;
+    let mut cost = 0;
+
+    while queued > 0 {
+        let bucket = cost % ring;
+        while let Some(position) = buckets[bucket].pop() {
+            queued -= 1;
+            // Entries left behind by a later decrease are skipped
+            if dist[position] != cost {
+                continue;
+            }
+            if position == goal {
+                return (cost, reconstruct_path(goal, &prev));
+            }
+            record_settled(stats);
+
+            for (i, (neighbor, weight)) in graph.neighbors(position).enumerate() {
+                let Some(weight) = effective_weight(overlay, position, i, neighbor, weight) else {
+                    continue;
+                };
+                debug_assert!(weight <= max_weight, "edge {} -> {} weighs {}, more than max_weight {}", position, neighbor, weight, max_weight);
+                let next_cost = cost + weight;
+                if next_cost < dist[neighbor] {
+                    dist[neighbor] = next_cost;
+                    prev[neighbor] = Some(position);
+                    buckets[next_cost % ring].push(neighbor);
+                    queued += 1;
+                }
+            }
+        }
+        cost += 1;
+    }
+
+    (usize::MAX, Vec::new())
+}
+
+fn reconstruct_path(goal: usize, prev: &[Option<usize>]) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut current = Some(goal);
+    while let Some(node) = current {
+        path.push(node);
+        current = prev[node];
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{gnm, road_network, RoadOptions, Weights};
+    use crate::graph::Graph;
+    use crate::sequential_dijkstra;
+    use crate::verify::verify_path;
+
+    #[test]
+    fn test_simple_graph() {
+        let graph = vec![
+            vec![(1, 2), (2, 4)],
+            vec![(2, 1)],
+            vec![(3, 0)],  // Zero weights stay in the current bucket
+            vec![],
+            vec![(0, 1)],
+        ];
+        assert_eq!(dial_dijkstra(&graph, 0, 3), (3, vec![0, 1, 2, 3]));
+        assert_eq!(dial_dijkstra(&graph, 0, 0), (0, vec![0]));
+        assert_eq!(dial_dijkstra(&graph, 0, 4), (usize::MAX, vec![]));
+
+        let mut overlay = Overlay::new(&graph);
+        overlay.override_weight(&graph, 1, 2, 9);
+        assert_eq!(dial_dijkstra_with_overlay(&graph, &overlay, 0, 3), (4, vec![0, 2, 3]));
+        overlay.ban_node(2);
+        assert_eq!(dial_dijkstra_with_overlay(&graph, &overlay, 0, 3), (usize::MAX, vec![]));
+    }
+
+    #[test]
+    fn test_matches_dijkstra() {
+        // Every third edge weighs zero
+        let mut zero_weights = gnm(5_000, 20_000, Weights::Uniform(1, 10), 11).adj_list;
+        for (i, edge) in zero_weights.iter_mut().flatten().enumerate() {
+            if i % 3 == 0 {
+                edge.1 = 0;
+            }
+        }
+        let graphs = [
+            Graph::new(zero_weights),
+            gnm(5_000, 20_000, Weights::Uniform(1, 1_000), 12),
+            road_network(&RoadOptions::new(60, 60), 13),
+        ];
+        for graph in &graphs {
+            for goal in [1, 777, graph.num_nodes() - 1] {
+                let (cost, path) = dial_dijkstra(graph, 0, goal);
+                assert_eq!(cost, sequential_dijkstra(graph, 0, goal).0);
+                assert_eq!(verify_path(graph, 0, goal, cost, &path), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_small_weights() {
+        let graph = gnm(200_000, 800_000, Weights::Uniform(1, 10), 42);
+        let (cost, path) = dial_dijkstra(&graph, 0, 199_999);
+        assert_eq!(cost, sequential_dijkstra(&graph, 0, 199_999).0);
+        assert_eq!(dial_dijkstra_with_max_weight(&graph, 10, 0, 199_999), (cost, path));
+    }
+
+    #[test]
+    fn test_large_weights_fall_back_to_a_heap() {
+        let graph = gnm(2_000, 8_000, Weights::Uniform(1, 10 * DIAL_MAX_BUCKETS), 5);
+        for goal in [1, 1_999] {
+            assert_eq!(dial_dijkstra(&graph, 0, goal).0, sequential_dijkstra(&graph, 0, goal).0);
+        }
+        let huge = vec![vec![(1, usize::MAX)], vec![(2, 1)], vec![]];
+        assert_eq!(dial_dijkstra(&huge, 1, 2), (1, vec![1, 2]));
+        assert_eq!(dial_dijkstra(&huge, 0, 2), sequential_dijkstra(&huge, 0, 2));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "more than max_weight")]
+    fn test_max_weight_too_small() {
+        let graph = vec![vec![(1, 3)], vec![]];
+        dial_dijkstra_with_max_weight(&graph, 2, 0, 1);
+    }
+}
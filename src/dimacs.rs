@@ -0,0 +1,300 @@
+//! Readers and writers for the 9th DIMACS Implementation Challenge formats:
+//! `.gr` graphs, `.co` coordinates and `.ss` / `.p2p` query files. Node ids
+//! are 1-based in the files and 0-based in memory.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+use crate::algorithm::Algorithm;
+use crate::graph::Graph;
+
+pub fn read_gr<R: BufRead>(reader: R) -> io::Result<Graph> {
+    let mut adj_list: Vec<Vec<(usize, usize)>> = Vec::new();
+    let mut num_edges = None;
+    let mut seen_edges = 0;
+
+    for_each_line(reader, |line_no, tokens| {
+        match tokens[0] {
+            "p" => {
+                expect_tokens(line_no, tokens, &["p", "sp", "n", "m"])?;
+                adj_list = vec![Vec::new(); parse(line_no, tokens[2])?];
+                num_edges = Some(parse::<usize>(line_no, tokens[3])?);
+            }
+            "a" => {
+                expect_tokens(line_no, tokens, &["a", "u", "v", "w"])?;
+                if num_edges.is_none() {
+                    return Err(invalid(line_no, "arc before problem line"));
+                }
+                let u = node_id(line_no, tokens[1], adj_list.len())?;
+                let v = node_id(line_no, tokens[2], adj_list.len())?;
+                adj_list[u].push((v, parse(line_no, tokens[3])?));
+                seen_edges += 1;
+            }
+            other => return Err(invalid(line_no, &format!("unexpected line type `{}`", other))),
+        }
+        Ok(())
+    })?;
+
+    match num_edges {
+        None => Err(invalid(0, "missing problem line")),
+        Some(m) if m != seen_edges => Err(invalid(0, &format!("expected {} arcs, found {}", m, seen_edges))),
+        Some(_) => Ok(Graph::new(adj_list)),
+    }
+}
+
+pub fn write_gr<W: Write>(graph: &Graph, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "p sp {} {}", graph.num_nodes(), graph.num_edges())?;
+    for (u, edges) in graph.adj_list.iter().enumerate() {
+        for &(v, weight) in edges {
+            writeln!(writer, "a {} {} {}", u + 1, v + 1, weight)?;
+        }
+    }
+    writer.flush()
+}
+
+pub fn read_co<R: BufRead>(reader: R) -> io::Result<Vec<(f64, f64)>> {
+    let mut coordinates = Vec::new();
+    for_each_line(reader, |line_no, tokens| {
+        match tokens[0] {
+            "p" => {
+                expect_tokens(line_no, tokens, &["p", "aux", "sp", "co", "n"])?;
+                coordinates = vec![(0.0, 0.0); parse(line_no, tokens[4])?];
+            }
+            "v" => {
+                expect_tokens(line_no, tokens, &["v", "id", "x", "y"])?;
+                let id = node_id(line_no, tokens[1], coordinates.len())?;
+                coordinates[id] = (parse(line_no, tokens[2])?, parse(line_no, tokens[3])?);
+            }
+            other => return Err(invalid(line_no, &format!("unexpected line type `{}`", other))),
+        }
+        Ok(())
+    })?;
+    Ok(coordinates)
+}
+
+pub fn write_co<W: Write>(coordinates: &[(f64, f64)], writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "p aux sp co {}", coordinates.len())?;
+    for (id, &(x, y)) in coordinates.iter().enumerate() {
+        writeln!(writer, "v {} {} {}", id + 1, x, y)?;
+    }
+    writer.flush()
+}
+
+/// Reads the sources of a single-source query file.
+pub fn read_ss<R: BufRead>(reader: R) -> io::Result<Vec<usize>> {
+    let mut sources = Vec::new();
+    for_each_line(reader, |line_no, tokens| {
+        match tokens[0] {
+            "p" => expect_tokens(line_no, tokens, &["p", "aux", "sp", "ss", "count"]),
+            "s" => {
+                expect_tokens(line_no, tokens, &["s", "id"])?;
+                sources.push(node_id(line_no, tokens[1], usize::MAX)?);
+                Ok(())
+            }
+            other => Err(invalid(line_no, &format!("unexpected line type `{}`", other))),
+        }
+    })?;
+    Ok(sources)
+}
+
+pub fn write_ss<W: Write>(sources: &[usize], writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "p aux sp ss {}", sources.len())?;
+    for &source in sources {
+        writeln!(writer, "s {}", source + 1)?;
+    }
+    writer.flush()
+}
+
+/// Reads the `(start, goal)` pairs of a point-to-point query file.
+pub fn read_p2p<R: BufRead>(reader: R) -> io::Result<Vec<(usize, usize)>> {
+    let mut queries = Vec::new();
+    for_each_line(reader, |line_no, tokens| {
+        match tokens[0] {
+            "p" => expect_tokens(line_no, tokens, &["p", "aux", "sp", "p2p", "count"]),
+            "q" => {
+                expect_tokens(line_no, tokens, &["q", "s", "t"])?;
+                queries.push((node_id(line_no, tokens[1], usize::MAX)?, node_id(line_no, tokens[2], usize::MAX)?));
+                Ok(())
+            }
+            other => Err(invalid(line_no, &format!("unexpected line type `{}`", other))),
+        }
+    })?;
+    Ok(queries)
+}
+
+pub fn write_p2p<W: Write>(queries: &[(usize, usize)], writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "p aux sp p2p {}", queries.len())?;
+    for &(start, goal) in queries {
+        writeln!(writer, "q {} {}", start + 1, goal + 1)?;
+    }
+    writer.flush()
+}
+
+/// Loads a `.gr` file, and the matching `.co` file if `coordinates` is given.
+pub fn load_graph<P: AsRef<Path>>(gr: P, coordinates: Option<P>) -> io::Result<Graph> {
+    let graph = read_gr(BufReader::new(File::open(gr)?))?;
+    match coordinates {
+        Some(co) => {
+            let coordinates = read_co(BufReader::new(File::open(co)?))?;
+            if coordinates.len() != graph.num_nodes() {
+                return Err(invalid(0, "coordinate count does not match node count"));
+            }
+            Ok(graph.with_coordinates(coordinates))
+        }
+        None => Ok(graph),
+    }
+}
+
+/// Answers every query with `algorithm` and writes a DIMACS-style result
+/// file: a `p res sp p2p` header, one `d <s> <t> <dist>` line per query
+/// (`inf` if unreachable) and the total query time in milliseconds.
+pub fn run_p2p<W: Write>(graph: &Graph, queries: &[(usize, usize)], algorithm: Algorithm, graph_name: &str, query_name: &str, writer: W) -> io::Result<()> {
+    for &(start, goal) in queries {
+        if start >= graph.num_nodes() || goal >= graph.num_nodes() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("query {} {} is out of range", start + 1, goal + 1)));
+        }
+    }
+
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "c algorithm {}", algorithm)?;
+    writeln!(writer, "p res sp p2p {} {}", graph_name, query_name)?;
+
+    let start_time = Instant::now();
+    let results: Vec<usize> = queries.iter().map(|&(start, goal)| algorithm.run(graph, start, goal).0).collect();
+    let duration = start_time.elapsed();
+
+    for (&(start, goal), &cost) in queries.iter().zip(&results) {
+        if cost == usize::MAX {
+            writeln!(writer, "d {} {} inf", start + 1, goal + 1)?;
+        } else {
+            writeln!(writer, "d {} {} {}", start + 1, goal + 1, cost)?;
+        }
+    }
+    writeln!(writer, "t {:.3}", duration.as_secs_f64() * 1000.0)?;
+    writer.flush()
+}
+
+/// `run_p2p` on files, naming the inputs in the header by their file names.
+pub fn run_p2p_files<P: AsRef<Path>>(gr: P, p2p: P, algorithm: Algorithm, out: P) -> io::Result<()> {
+    let graph = load_graph(gr.as_ref(), None)?;
+    let queries = read_p2p(BufReader::new(File::open(p2p.as_ref())?))?;
+    let file_name = |path: &Path| path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    run_p2p(&graph, &queries, algorithm, &file_name(gr.as_ref()), &file_name(p2p.as_ref()), File::create(out)?)
+}
+
+fn for_each_line<R, F>(reader: R, mut f: F) -> io::Result<()>
+where
+    R: BufRead,
+    F: FnMut(usize, &[&str]) -> io::Result<()>,
+{
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() || tokens[0] == "c" {
+            continue;
+        }
+        f(i + 1, &tokens)?;
+    }
+    Ok(())
+}
+
+fn expect_tokens(line_no: usize, tokens: &[&str], expected: &[&str]) -> io::Result<()> {
+    if tokens.len() != expected.len() {
+        return Err(invalid(line_no, &format!("expected `{}`", expected.join(" "))));
+    }
+    // Fixed keywords such as `sp` or `aux` are written lowercase in `expected`.
+    for (token, keyword) in tokens.iter().zip(expected).skip(1) {
+        if matches!(*keyword, "sp" | "aux" | "co" | "ss" | "p2p") && token != keyword {
+            return Err(invalid(line_no, &format!("expected `{}`", expected.join(" "))));
+        }
+    }
+    Ok(())
+}
+
+fn node_id(line_no: usize, token: &str, num_nodes: usize) -> io::Result<usize> {
+    let id: usize = parse(line_no, token)?;
+    if id == 0 || id > num_nodes {
+        return Err(invalid(line_no, &format!("node id {} is out of range", id)));
+    }
+    Ok(id - 1)
+}
+
+fn parse<T: std::str::FromStr>(line_no: usize, token: &str) -> io::Result<T> {
+    token.parse().map_err(|_| invalid(line_no, &format!("invalid number `{}`", token)))
+}
+
+fn invalid(line_no: usize, message: &str) -> io::Error {
+    let message = if line_no == 0 { message.to_string() } else { format!("line {}: {}", line_no, message) };
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_dijkstra::sequential_dijkstra;
+
+    const GR: &str = "c sample graph\n\
+        p sp 4 5\n\
+        a 1 2 2\n\
+        a 1 3 5\n\
+        a 2 3 1\n\
+        a 3 4 2\n\
+        a 4 1 1\n";
+
+    #[test]
+    fn test_read_gr() {
+        let graph = read_gr(GR.as_bytes()).unwrap();
+        assert_eq!(graph.adj_list, vec![vec![(1, 2), (2, 5)], vec![(2, 1)], vec![(3, 2)], vec![(0, 1)]]);
+        assert_eq!(sequential_dijkstra(&graph.adj_list, 0, 3), (5, vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let graph = read_gr(GR.as_bytes()).unwrap();
+        let mut out = Vec::new();
+        write_gr(&graph, &mut out).unwrap();
+        assert_eq!(read_gr(out.as_slice()).unwrap().adj_list, graph.adj_list);
+
+        let coordinates = vec![(-73530767.0, 41085396.0), (-73530538.0, 41086098.0)];
+        let mut out = Vec::new();
+        write_co(&coordinates, &mut out).unwrap();
+        assert_eq!(read_co(out.as_slice()).unwrap(), coordinates);
+
+        let mut out = Vec::new();
+        write_ss(&[0, 3], &mut out).unwrap();
+        assert_eq!(read_ss(out.as_slice()).unwrap(), vec![0, 3]);
+
+        let mut out = Vec::new();
+        write_p2p(&[(0, 3), (2, 1)], &mut out).unwrap();
+        assert_eq!(read_p2p(out.as_slice()).unwrap(), vec![(0, 3), (2, 1)]);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(read_gr("p sp 2 1\na 1 3 1\n".as_bytes()).is_err());  // Node out of range
+        assert!(read_gr("p sp 2 2\na 1 2 1\n".as_bytes()).is_err());  // Arc count mismatch
+        assert!(read_gr("a 1 2 1\n".as_bytes()).is_err());  // Missing problem line
+        let err = read_p2p("p aux sp p2p 1\nq 1 x\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_run_p2p() {
+        let graph = Graph::new(vec![vec![(1, 2)], vec![(2, 1)], vec![], vec![]]);
+        for algorithm in Algorithm::ALL {
+            let mut out = Vec::new();
+            run_p2p(&graph, &[(0, 2), (0, 3)], algorithm, "g.gr", "q.p2p", &mut out).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            let lines: Vec<&str> = out.lines().collect();
+            assert_eq!(lines[1], "p res sp p2p g.gr q.p2p");
+            assert_eq!(lines[2], "d 1 3 3");
+            assert_eq!(lines[3], "d 1 4 inf");
+            assert!(lines[4].starts_with("t "));
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Graphviz DOT import and export.
+//!
+//! The reader understands the statements Graphviz itself writes for plain
+//! graphs: node statements, edge chains (`a -> b -> c`), attribute lists and
+//! comments. Subgraphs are not supported. The weight is taken from the
+//! `weight` attribute, falling back to a numeric `label`, then 1; attributes
+//! of an earlier `edge [...]` statement apply to the edges after it.
+
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use crate::edge_list::IdMap;
+use crate::graph::Graph;
+use crate::GraphBuilder;
+
+pub fn read_dot<R: Read>(mut reader: R) -> io::Result<(Graph, IdMap)> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    let mut tokens = tokenize(&input)?.into_iter().peekable();
+    let mut labels = IdMap::new();
+    let mut edges = Vec::new();
+    let mut edge_defaults = Vec::new();
+
+    let mut keyword = expect_id(tokens.next())?;
+    if keyword.eq_ignore_ascii_case("strict") {
+        keyword = expect_id(tokens.next())?;
+    }
+    let directed = match keyword.to_ascii_lowercase().as_str() {
+        "digraph" => true,
+        "graph" => false,
+        _ => return Err(invalid(&format!("expected `graph` or `digraph`, found `{}`", keyword))),
+    };
+    if matches!(tokens.peek(), Some(Token::Id(_))) {
+        tokens.next();  // Graph name
+    }
+    if tokens.next() != Some(Token::Symbol("{")) {
+        return Err(invalid("expected `{`"));
+    }
+
+    loop {
+        let first = match tokens.next() {
+            Some(Token::Symbol("}")) => break,
+            Some(Token::Symbol(";")) => continue,
+            Some(Token::Symbol("{")) => return Err(invalid("subgraphs are not supported")),
+            Some(Token::Id(id)) => id,
+            Some(Token::Symbol(symbol)) => return Err(invalid(&format!("unexpected `{}`", symbol))),
+            None => return Err(invalid("missing closing `}`")),
+        };
+        // `graph [...]`, `node [...]`, `edge [...]` and `key = value` set defaults.
+        let is_default = ["graph", "node", "edge"].contains(&first.to_ascii_lowercase().as_str())
+            && tokens.peek() == Some(&Token::Symbol("["));
+        if is_default {
+            let attributes = attribute_list(&mut tokens)?;
+            if first.eq_ignore_ascii_case("edge") {
+                edge_defaults.extend(attributes);
+            }
+            continue;
+        }
+        if tokens.peek() == Some(&Token::Symbol("=")) {
+            tokens.next();
+            expect_id(tokens.next())?;
+            continue;
+        }
+
+        let mut chain = vec![labels.get_or_insert(&first)];
+        skip_port(&mut tokens)?;
+        while let Some(&Token::Symbol(op @ ("->" | "--"))) = tokens.peek() {
+            if (op == "->") != directed {
+                return Err(invalid(&format!("`{}` in a {}", op, if directed { "digraph" } else { "graph" })));
+            }
+            tokens.next();
+            let node = expect_id(tokens.next())?;
+            chain.push(labels.get_or_insert(&node));
+            skip_port(&mut tokens)?;
+        }
+        let attributes = attribute_list(&mut tokens)?;
+        if chain.len() > 1 {
+            // The edge's own attributes come last, so they win over the defaults.
+            let attributes: Vec<_> = edge_defaults.iter().chain(&attributes).collect();
+            let weight = attributes.iter().rev().find(|(key, _)| key == "weight")
+                .or_else(|| attributes.iter().rev().find(|(key, value)| key == "label" && value.parse::<usize>().is_ok()))
+                .map(|(_, value)| value.parse().map_err(|_| invalid(&format!("invalid weight `{}`", value))))
+                .transpose()?
+                .unwrap_or(1);
+            for pair in chain.windows(2) {
+                edges.push((pair[0], pair[1], weight));
+                if !directed && pair[0] != pair[1] {
+                    edges.push((pair[1], pair[0], weight));
+                }
+            }
+        }
+    }
+
+    let graph = GraphBuilder::new(labels.len()).edges(edges).build()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok((graph, labels))
+}
+
+pub fn write_dot<W: Write>(graph: &Graph, labels: Option<&IdMap>, writer: W) -> io::Result<()> {
+    write_dot_with_path(graph, labels, &[], writer)
+}
+
+/// Like `write_dot`, but draws the nodes and edges of `path` (as returned by
+/// the searches) in red, for checking a route by eye.
+pub fn write_dot_with_path<W: Write>(graph: &Graph, labels: Option<&IdMap>, path: &[usize], writer: W) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(writer);
+    let on_path: HashSet<usize> = path.iter().copied().collect();
+    let path_edges: HashSet<(usize, usize)> = path.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    let name = |u: usize| match labels {
+        Some(labels) => quote(labels.label(u)),
+        None => u.to_string(),
+    };
+
+    writeln!(writer, "digraph G {{")?;
+    for u in 0..graph.num_nodes() {
+        let mut attributes = Vec::new();
+        if let Some(coordinates) = &graph.coordinates {
+            let (x, y) = coordinates[u];
+            attributes.push(format!("pos=\"{},{}\"", x, y));
+        }
+        if on_path.contains(&u) {
+            attributes.push("color=red".to_string());
+            attributes.push("style=bold".to_string());
+        }
+        if attributes.is_empty() {
+            writeln!(writer, "  {};", name(u))?;
+        } else {
+            writeln!(writer, "  {} [{}];", name(u), attributes.join(", "))?;
+        }
+    }
+    // A path uses one edge per hop, so only the cheapest parallel edge is highlighted.
+    let mut highlighted = HashSet::new();
+    for (u, edges) in graph.adj_list.iter().enumerate() {
+        let cheapest = |v: usize| edges.iter().filter(|&&(t, _)| t == v).map(|&(_, w)| w).min();
+        for &(v, weight) in edges {
+            let highlight = path_edges.contains(&(u, v)) && cheapest(v) == Some(weight) && highlighted.insert((u, v));
+            let style = if highlight { ", color=red, penwidth=3" } else { "" };
+            writeln!(writer, "  {} -> {} [weight={}, label=\"{}\"{}];", name(u), name(v), weight, weight, style)?;
+        }
+    }
+    writeln!(writer, "}}")?;
+    writer.flush()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Id(String),
+    Symbol(&'static str),
+}
+
+fn tokenize(input: &str) -> io::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    let mut at_line_start = true;
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\n' => {
+                at_line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            // `#` lines are C preprocessor output, which Graphviz ignores.
+            '#' if at_line_start => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '/' if chars.peek().is_some_and(|&(_, c)| c == '/') => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '/' if chars.peek().is_some_and(|&(_, c)| c == '*') => {
+                let end = input[i + 2..].find("*/").ok_or_else(|| invalid("unterminated comment"))?;
+                while chars.next_if(|&(j, _)| j < i + 2 + end + 2).is_some() {}
+            }
+            '-' if chars.peek().is_some_and(|&(_, c)| c == '>' || c == '-') => {
+                let (_, next) = chars.next().unwrap();
+                tokens.push(Token::Symbol(if next == '>' { "->" } else { "--" }));
+            }
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' => {
+                let symbol = ["{", "}", "[", "]", "=", ";", ",", ":"].into_iter().find(|s| s.starts_with(c)).unwrap();
+                tokens.push(Token::Symbol(symbol));
+            }
+            '"' => {
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, '"')) => id.push('"'),
+                            Some((_, '\n')) => {}
+                            Some((_, other)) => {
+                                id.push('\\');
+                                id.push(other);
+                            }
+                            None => return Err(invalid("unterminated string")),
+                        },
+                        Some((_, '"')) => break,
+                        Some((_, other)) => id.push(other),
+                        None => return Err(invalid("unterminated string")),
+                    }
+                }
+                tokens.push(Token::Id(id));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut end = i + c.len_utf8();
+                while let Some((j, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '.') {
+                    end = j + c.len_utf8();
+                }
+                tokens.push(Token::Id(input[i..end].to_string()));
+            }
+            other => return Err(invalid(&format!("unexpected character `{}`", other))),
+        }
+        at_line_start = false;
+    }
+    Ok(tokens)
+}
+
+fn attribute_list<I: Iterator<Item = Token>>(tokens: &mut std::iter::Peekable<I>) -> io::Result<Vec<(String, String)>> {
+    let mut attributes = Vec::new();
+    while tokens.peek() == Some(&Token::Symbol("[")) {
+        tokens.next();
+        loop {
+            match tokens.next() {
+                Some(Token::Symbol("]")) => break,
+                Some(Token::Symbol(";" | ",")) => continue,
+                Some(Token::Id(key)) => {
+                    if tokens.next() != Some(Token::Symbol("=")) {
+                        return Err(invalid(&format!("expected `=` after `{}`", key)));
+                    }
+                    attributes.push((key, expect_id(tokens.next())?));
+                }
+                _ => return Err(invalid("malformed attribute list")),
+            }
+        }
+    }
+    Ok(attributes)
+}
+
+/// Skips `:port` and `:port:compass` suffixes of a node id.
+fn skip_port<I: Iterator<Item = Token>>(tokens: &mut std::iter::Peekable<I>) -> io::Result<()> {
+    while tokens.next_if_eq(&Token::Symbol(":")).is_some() {
+        expect_id(tokens.next())?;
+    }
+    Ok(())
+}
+
+fn expect_id(token: Option<Token>) -> io::Result<String> {
+    match token {
+        Some(Token::Id(id)) => Ok(id),
+        Some(Token::Symbol(symbol)) => Err(invalid(&format!("expected an id, found `{}`", symbol))),
+        None => Err(invalid("unexpected end of input")),
+    }
+}
+
+fn quote(label: &str) -> String {
+    format!("\"{}\"", label.replace('"', "\\\""))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_dijkstra::sequential_dijkstra;
+
+    #[test]
+    fn test_read_dot() {
+        let input = r#"
+            /* Ring road */
+            strict digraph "city" {
+                rankdir = LR;
+                node [shape=circle];
+                "North Gate" -> market -> harbour [weight=4, color=blue];
+                market:e -> station [label="2"]  // label is the fallback weight
+                station -> harbour [weight=1];
+                harbour;
+                "a \"quoted\" node" -> market
+            }
+        "#;
+        let (graph, labels) = read_dot(input.as_bytes()).unwrap();
+        assert_eq!(labels.len(), 5);
+        assert_eq!(graph.num_edges(), 5);
+        let (start, goal) = (labels.id("North Gate").unwrap(), labels.id("harbour").unwrap());
+        let (cost, path) = sequential_dijkstra(&graph.adj_list, start, goal);
+        assert_eq!(cost, 7);
+        assert_eq!(labels.labels(&path), vec!["North Gate", "market", "station", "harbour"]);
+        assert_eq!(labels.id("a \"quoted\" node"), Some(4));
+    }
+
+    #[test]
+    fn test_undirected_and_errors() {
+        let (graph, _) = read_dot("graph { a -- b [weight=3] }".as_bytes()).unwrap();
+        assert_eq!(graph.adj_list, vec![vec![(1, 3)], vec![(0, 3)]]);
+        assert!(read_dot("graph { a -> b }".as_bytes()).is_err());
+        assert!(read_dot("digraph { a -> b [weight=x] }".as_bytes()).is_err());
+        assert!(read_dot("digraph { subgraph { a } }".as_bytes()).is_err());
+        assert!(read_dot("digraph { a -> b".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_edge_defaults() {
+        let input = "digraph { a -> b; edge [weight=5]; b -> c; c -> d [weight=2]; d -> e [label=7]; edge [label=3]; e -> a }";
+        let (graph, labels) = read_dot(input.as_bytes()).unwrap();
+        let weight = |u: &str, v: &str| graph.adj_list[labels.id(u).unwrap()].iter()
+            .find(|&&(t, _)| t == labels.id(v).unwrap())
+            .map(|&(_, w)| w);
+        assert_eq!(weight("a", "b"), Some(1));  // Before the defaults
+        assert_eq!(weight("b", "c"), Some(5));
+        assert_eq!(weight("c", "d"), Some(2));  // Own weight wins
+        assert_eq!(weight("d", "e"), Some(5));  // A default weight beats a label
+        assert_eq!(weight("e", "a"), Some(5));
+    }
+
+    #[test]
+    fn test_round_trip_and_path_highlight() {
+        let graph = Graph::new(vec![vec![(1, 2), (1, 9), (2, 4)], vec![(2, 1)], vec![]]);
+        let mut out = Vec::new();
+        write_dot(&graph, None, &mut out).unwrap();
+        let (read, labels) = read_dot(out.as_slice()).unwrap();
+        assert_eq!(read.adj_list, graph.adj_list);
+        assert_eq!(labels.label(2), "2");
+
+        let (_, path) = sequential_dijkstra(&graph.adj_list, 0, 2);
+        let mut out = Vec::new();
+        write_dot_with_path(&graph, None, &path, &mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert!(dot.contains("0 [color=red, style=bold];"));
+        assert!(dot.contains("0 -> 1 [weight=2, label=\"2\", color=red, penwidth=3];"));
+        assert!(dot.contains("0 -> 1 [weight=9, label=\"9\"];"));
+        assert!(dot.contains("0 -> 2 [weight=4, label=\"4\"];"));
+        assert_eq!(read_dot(dot.as_bytes()).unwrap().0.adj_list, graph.adj_list);
+    }
+}
@@ -0,0 +1,256 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use crate::graph::Graph;
+use crate::priority_queue::{with_queue, PriorityQueue, QueueKind};
+use crate::standard_dijkstra::sequential_sssp_with_queue;
+
+/// Shortest-path tree from a fixed source that is repaired after each edge
+/// update instead of being recomputed (Ramalingam–Reps). Only the nodes whose
+/// distance actually changes are touched.
+pub struct DynamicSssp {
+    graph: Graph,
+    source: usize,
+    dist: Vec<usize>,
+    prev: Vec<Option<usize>>,
+    queue: QueueKind,
+}
+
+impl DynamicSssp {
+    pub fn new(graph: Graph, source: usize) -> Self {
+        DynamicSssp::with_queue(graph, source, QueueKind::default())
+    }
+
+    /// `new`, with the initial search and every repair on the priority
+    /// queue `queue`.
+    pub fn with_queue(graph: Graph, source: usize, queue: QueueKind) -> Self {
+        let (dist, prev) = sequential_sssp_with_queue(&graph.adj_list, queue, source);
+        DynamicSssp { graph, source, dist, prev, queue }
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    pub fn dist(&self) -> &[usize] {
+        &self.dist
+    }
+
+    pub fn prev(&self) -> &[Option<usize>] {
+        &self.prev
+    }
+
+    /// Same result shape as `sequential_dijkstra(graph, source, goal)`.
+    pub fn path_to(&self, goal: usize) -> (usize, Vec<usize>) {
+        if self.dist[goal] == usize::MAX {
+            return (usize::MAX, Vec::new());
+        }
+        let mut path = Vec::new();
+        let mut current = Some(goal);
+        while let Some(node) = current {
+            path.push(node);
+            current = self.prev[node];
+        }
+        path.reverse();
+        (self.dist[goal], path)
+    }
+
+    pub fn insert_edge(&mut self, u: usize, v: usize, weight: usize) {
+        self.graph.insert_edge(u, v, weight);
+        self.decrease(u, v, weight);
+    }
+
+    pub fn delete_edge(&mut self, u: usize, v: usize) -> Option<usize> {
+        let weight = self.graph.delete_edge(u, v)?;
+        self.increase(u, v);
+        Some(weight)
+    }
+
+    pub fn set_weight(&mut self, u: usize, v: usize, weight: usize) -> Option<usize> {
+        let old = self.graph.set_weight(u, v, weight)?;
+        match weight.cmp(&old) {
+            Ordering::Less => self.decrease(u, v, weight),
+            Ordering::Greater => self.increase(u, v),
+            Ordering::Equal => {}
+        }
+        Some(old)
+    }
+
+    fn decrease(&mut self, u: usize, v: usize, weight: usize) {
+        let next_cost = self.dist[u].saturating_add(weight);
+        if next_cost >= self.dist[v] {
+            return;
+        }
+        self.dist[v] = next_cost;
+        self.prev[v] = Some(u);
+        with_queue!(self.queue, propagate::<Q>(self, &[v]));
+    }
+
+    fn increase(&mut self, u: usize, v: usize) {
+        if self.prev[v] != Some(u) {
+            return;
+        }
+
+        // Every node in the subtree below `v` may have lost its shortest path.
+        let mut affected = vec![false; self.graph.num_nodes()];
+        let mut subtree = vec![v];
+        let mut queue = VecDeque::from([v]);
+        affected[v] = true;
+        while let Some(node) = queue.pop_front() {
+            for &(child, _) in &self.graph.adj_list[node] {
+                if !affected[child] && self.prev[child] == Some(node) {
+                    affected[child] = true;
+                    subtree.push(child);
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        for &node in &subtree {
+            self.dist[node] = usize::MAX;
+            self.prev[node] = None;
+        }
+
+        // Seed each affected node with its best edge from an unaffected node.
+        let mut seeds = Vec::new();
+        for &node in &subtree {
+            for &(parent, weight) in &self.graph.rev_adj_list[node] {
+                if affected[parent] || self.dist[parent] == usize::MAX {
+                    continue;
+                }
+                let next_cost = self.dist[parent].saturating_add(weight);
+                if next_cost < self.dist[node] {
+                    self.dist[node] = next_cost;
+                    self.prev[node] = Some(parent);
+                }
+            }
+            if self.dist[node] != usize::MAX {
+                seeds.push(node);
+            }
+        }
+        with_queue!(self.queue, propagate::<Q>(self, &seeds));
+    }
+}
+
+/// Dijkstra from `seeds`, whose distances are already set, through every
+/// node they improve.
+fn propagate<Q: PriorityQueue>(sssp: &mut DynamicSssp, seeds: &[usize]) {
+    let mut heap = Q::new(sssp.graph.num_nodes());
+    for &node in seeds {
+        heap.push_or_decrease(node, sssp.dist[node]);
+    }
+    while let Some((cost, position)) = heap.pop() {
+        if cost > sssp.dist[position] {
+            continue;
+        }
+
+        for &(neighbor, weight) in &sssp.graph.adj_list[position] {
+            let next_cost = cost.saturating_add(weight);
+            if next_cost < sssp.dist[neighbor] {
+                sssp.dist[neighbor] = next_cost;
+                heap.push_or_decrease(neighbor, next_cost);
+                sssp.prev[neighbor] = Some(position);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{self, Weights};
+    use crate::standard_dijkstra::{sequential_dijkstra, sequential_sssp};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn generate_random_graph(nodes: usize, edges: usize) -> Vec<Vec<(usize, usize)>> {
+        generators::gnm(nodes, edges, Weights::Uniform(1, 99), 42).adj_list
+    }
+
+    fn assert_matches_recompute(sssp: &DynamicSssp) {
+        let (dist, _) = sequential_sssp(&sssp.graph().adj_list, sssp.source());
+        assert_eq!(sssp.dist(), dist.as_slice());
+        for (node, &parent) in sssp.prev().iter().enumerate() {
+            if let Some(parent) = parent {
+                let weight = sssp.graph().adj_list[parent].iter()
+                    .filter(|&&(t, _)| t == node)
+                    .map(|&(_, w)| w)
+                    .min()
+                    .unwrap();
+                assert_eq!(sssp.dist()[node], sssp.dist()[parent] + weight);  // Tree edges are tight
+            }
+        }
+    }
+
+    #[test]
+    fn test_simple_updates() {
+        let graph = Graph::new(vec![
+            vec![(1, 2), (2, 4)],  // Node 0
+            vec![(2, 1)],          // Node 1
+            vec![],                // Node 2
+        ]);
+        let mut sssp = DynamicSssp::new(graph, 0);
+        assert_eq!(sssp.path_to(2), (3, vec![0, 1, 2]));
+
+        sssp.set_weight(0, 2, 1);
+        assert_eq!(sssp.path_to(2), (1, vec![0, 2]));
+
+        assert_eq!(sssp.delete_edge(0, 2), Some(1));
+        assert_eq!(sssp.path_to(2), (3, vec![0, 1, 2]));
+
+        sssp.delete_edge(0, 1);
+        assert_eq!(sssp.path_to(2), (usize::MAX, vec![]));
+
+        sssp.insert_edge(0, 1, 5);
+        assert_eq!(sssp.path_to(2), (6, vec![0, 1, 2]));
+        assert_eq!(sssp.delete_edge(2, 0), None);
+    }
+
+    #[test]
+    fn test_random_updates_match_recompute() {
+        let adj_list = generate_random_graph(500, 2500);
+        let mut sssp = DynamicSssp::new(Graph::new(adj_list), 0);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for step in 0..300 {
+            let u = rng.gen_range(0..500);
+            let v = rng.gen_range(0..500);
+            match step % 3 {
+                0 => sssp.insert_edge(u, v, rng.gen_range(1..100)),
+                1 => {
+                    if let Some(&(v, _)) = sssp.graph().adj_list[u].first() {
+                        sssp.delete_edge(u, v);
+                    }
+                }
+                _ => {
+                    if let Some(&(v, _)) = sssp.graph().adj_list[u].first() {
+                        sssp.set_weight(u, v, rng.gen_range(1..100));
+                    }
+                }
+            }
+            assert_matches_recompute(&sssp);
+        }
+
+        let (cost, _) = sequential_dijkstra(&sssp.graph().adj_list, 0, 499);
+        assert_eq!(sssp.path_to(499).0, cost);
+    }
+
+    #[test]
+    fn test_every_queue() {
+        for queue in QueueKind::ALL {
+            let mut sssp = DynamicSssp::with_queue(Graph::new(generate_random_graph(300, 1500)), 0, queue);
+            let mut rng = StdRng::seed_from_u64(11);
+            for _ in 0..50 {
+                let u = rng.gen_range(0..300);
+                sssp.insert_edge(u, rng.gen_range(0..300), rng.gen_range(1..100));
+                if let Some(&(v, _)) = sssp.graph().adj_list[u].first() {
+                    sssp.delete_edge(u, v);
+                }
+                assert_matches_recompute(&sssp);
+            }
+        }
+    }
+}
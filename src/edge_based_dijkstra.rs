@@ -0,0 +1,242 @@
+use crate::graph::{Graph, Neighbors};
+use crate::overlay::{effective_weight, is_banned, Overlay};
+use crate::priority_queue::{with_queue, LazyBinaryHeap, PriorityQueue, QueueKind};
+use crate::turn_costs::{Turn, TurnCostTable};
+
+/// Dijkstra over incoming-edge states, so that the turn taken at every node
+/// can be checked against `graph.turn_costs`. Returns the node path like the
+/// node-based variants. Builds a `TurnGraph` on every call; keep one for
+/// repeated queries.
+pub fn edge_based_dijkstra(graph: &Graph, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    TurnGraph::new(graph, &graph.turn_costs).shortest_path(start, goal)
+}
+
+/// `edge_based_dijkstra` with the bans and weight overrides of `overlay`.
+pub fn edge_based_dijkstra_with_overlay(graph: &Graph, overlay: &Overlay, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    TurnGraph::new(graph, &graph.turn_costs).shortest_path_with_overlay(overlay, start, goal)
+}
+
+/// `edge_based_dijkstra` on the priority queue `queue`, keyed by edge id.
+pub fn edge_based_dijkstra_with_queue(graph: &Graph, queue: QueueKind, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    TurnGraph::new(graph, &graph.turn_costs).shortest_path_with_queue(queue, start, goal)
+}
+
+/// A graph with turn costs and the edge ids the edge-based search works
+/// on: edge `i` of node `u` gets the id `offsets[u] + i`. Building it takes a
+/// pass over every edge, so keep it across queries.
+pub struct TurnGraph<'a, G: ?Sized> {
+    graph: &'a G,
+    turn_costs: &'a TurnCostTable,
+    offsets: Vec<usize>,
+    tail: Vec<usize>,
+    head: Vec<usize>,
+}
+
+impl<'a, G: Neighbors + ?Sized> TurnGraph<'a, G> {
+    pub fn new(graph: &'a G, turn_costs: &'a TurnCostTable) -> Self {
+        let mut offsets = Vec::with_capacity(graph.num_nodes() + 1);
+        offsets.push(0);
+        for node in 0..graph.num_nodes() {
+            offsets.push(offsets.last().unwrap() + graph.degree(node));
+        }
+        let num_edges = *offsets.last().unwrap();
+
+        // Tail and head node of every edge id.
+        let mut tail = Vec::with_capacity(num_edges);
+        let mut head = Vec::with_capacity(num_edges);
+        for node in 0..graph.num_nodes() {
+            for (neighbor, _) in graph.neighbors(node) {
+                tail.push(node);
+                head.push(neighbor);
+            }
+        }
+        TurnGraph { graph, turn_costs, offsets, tail, head }
+    }
+
+    pub fn graph(&self) -> &'a G {
+        self.graph
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.head.len()
+    }
+
+    pub fn shortest_path(&self, start: usize, goal: usize) -> (usize, Vec<usize>) {
+        search::<_, LazyBinaryHeap>(self, None, start, goal)
+    }
+
+    /// `shortest_path` with the bans and weight overrides of `overlay`.
+    pub fn shortest_path_with_overlay(&self, overlay: &Overlay, start: usize, goal: usize) -> (usize, Vec<usize>) {
+        search::<_, LazyBinaryHeap>(self, Some(overlay), start, goal)
+    }
+
+    /// `shortest_path` on the priority queue `queue`, keyed by edge id.
+    pub fn shortest_path_with_queue(&self, queue: QueueKind, start: usize, goal: usize) -> (usize, Vec<usize>) {
+        with_queue!(queue, search(self, None, start, goal))
+    }
+}
+
+fn search<G: Neighbors + ?Sized, Q: PriorityQueue>(turn_graph: &TurnGraph<'_, G>, overlay: Option<&Overlay>, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    if is_banned(overlay, start) || is_banned(overlay, goal) {
+        return (usize::MAX, Vec::new());
+    }
+    if start == goal {
+        return (0, vec![start]);
+    }
+
+    let TurnGraph { graph, turn_costs, offsets, tail, head } = turn_graph;
+    let num_edges = turn_graph.num_edges();
+    let mut dist = vec![usize::MAX; num_edges];
+    let mut prev = vec![None; num_edges];
+    let mut heap = Q::new(num_edges);
+
+    for (i, (neighbor, weight)) in graph.neighbors(start).enumerate() {
+        let Some(weight) = effective_weight(overlay, start, i, neighbor, weight) else {
+            continue;
+        };
+        let edge = offsets[start] + i;
+        if weight < dist[edge] {
+            dist[edge] = weight;
+            heap.push_or_decrease(edge, weight);
+        }
+    }
+
+    while let Some((cost, edge)) = heap.pop() {
+        if cost > dist[edge] {
+            continue;
+        }
+
+        let via = head[edge];
+        if via == goal {
+            let path = reconstruct_path(edge, &prev, tail, head);
+            return (cost, path);
+        }
+
+        for (i, (to, weight)) in graph.neighbors(via).enumerate() {
+            let Some(weight) = effective_weight(overlay, via, i, to, weight) else {
+                continue;
+            };
+            let turn_cost = match turn_costs.get(tail[edge], via, to) {
+                Turn::Forbidden => continue,
+                Turn::Cost(turn_cost) => turn_cost,
+            };
+            let next = offsets[via] + i;
+            let next_cost = cost.saturating_add(turn_cost).saturating_add(weight);
+            if next_cost < dist[next] {
+                dist[next] = next_cost;
+                heap.push_or_decrease(next, next_cost);
+                prev[next] = Some(edge);
+            }
+        }
+    }
+
+    (usize::MAX, Vec::new())
+}
+
+fn reconstruct_path(last_edge: usize, prev: &[Option<usize>], tail: &[usize], head: &[usize]) -> Vec<usize> {
+    let mut path = vec![head[last_edge]];
+    let mut current = last_edge;
+    while let Some(edge) = prev[current] {
+        path.push(head[edge]);
+        current = edge;
+    }
+    path.push(tail[current]);
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UndirectedGraph;
+    use crate::sequential_dijkstra;
+
+    fn grid_graph() -> Vec<Vec<(usize, usize)>> {
+        // 0 - 1 - 2
+        // |   |   |
+        // 3 - 4 - 5
+        vec![
+            vec![(1, 1), (3, 1)],          // Node 0
+            vec![(0, 1), (2, 1), (4, 1)],  // Node 1
+            vec![(1, 1), (5, 1)],          // Node 2
+            vec![(0, 1), (4, 1)],          // Node 3
+            vec![(1, 1), (3, 1), (5, 1)],  // Node 4
+            vec![(2, 1), (4, 1)],          // Node 5
+        ]
+    }
+
+    #[test]
+    fn test_matches_sequential_without_turn_costs() {
+        let adj_list = grid_graph();
+        let graph = Graph::new(adj_list.clone());
+        let (cost, path) = edge_based_dijkstra(&graph, 0, 5);
+        assert_eq!(cost, sequential_dijkstra(&adj_list, 0, 5).0);  // Shortest path cost: 3
+        assert_eq!(path.len(), 4);
+    }
+
+    #[test]
+    fn test_forbidden_turns() {
+        let mut turn_costs = TurnCostTable::new();
+        turn_costs.forbid(0, 1, 2);
+        turn_costs.forbid(0, 1, 4);
+        turn_costs.forbid(3, 4, 5);
+        turn_costs.forbid(4, 1, 4);  // No U-turn at node 1
+        let graph = Graph::new(grid_graph()).with_turn_costs(turn_costs);
+        let (cost, path) = edge_based_dijkstra(&graph, 0, 5);
+        assert_eq!(cost, 5);  // Only detour: 0 -> 3 -> 4 -> 1 -> 2 -> 5
+        assert_eq!(path, vec![0, 3, 4, 1, 2, 5]);
+    }
+
+    #[test]
+    fn test_turn_penalties() {
+        let mut turn_costs = TurnCostTable::new();
+        turn_costs.set_cost(0, 1, 2, 10);
+        turn_costs.set_cost(0, 1, 4, 10);
+        turn_costs.set_cost(0, 3, 4, 1);
+        let graph = Graph::new(grid_graph()).with_turn_costs(turn_costs);
+        let (cost, path) = edge_based_dijkstra(&graph, 0, 5);
+        assert_eq!(cost, 4);  // 3 edges plus the cheaper penalty at node 3
+        assert_eq!(path, vec![0, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_all_turns_forbidden() {
+        let mut turn_costs = TurnCostTable::new();
+        turn_costs.forbid(0, 1, 2);
+        let graph = Graph::new(vec![vec![(1, 1)], vec![(2, 1)], vec![]]).with_turn_costs(turn_costs);
+        let (cost, path) = edge_based_dijkstra(&graph, 0, 2);
+        assert_eq!(cost, usize::MAX);
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn test_every_queue() {
+        let mut turn_costs = TurnCostTable::new();
+        turn_costs.forbid(0, 1, 2);
+        turn_costs.set_cost(0, 3, 4, 1);
+        let graph = Graph::new(grid_graph()).with_turn_costs(turn_costs);
+        let expected = edge_based_dijkstra(&graph, 0, 5);
+        for queue in QueueKind::ALL {
+            assert_eq!(edge_based_dijkstra_with_queue(&graph, queue, 0, 5).0, expected.0);
+        }
+    }
+
+    #[test]
+    fn test_turn_graph_on_other_adjacencies() {
+        let mut turn_costs = TurnCostTable::new();
+        turn_costs.forbid(0, 1, 2);
+        turn_costs.set_cost(3, 4, 5, 2);
+        let graph = Graph::new(grid_graph()).with_turn_costs(turn_costs.clone());
+        let turn_graph = TurnGraph::new(&graph.adj_list, &turn_costs);
+        assert_eq!(turn_graph.num_edges(), 14);
+        for goal in 0..6 {
+            assert_eq!(turn_graph.shortest_path(0, goal), edge_based_dijkstra(&graph, 0, goal));
+        }
+
+        let undirected = UndirectedGraph::from_edges(3, vec![(0, 1, 1), (1, 2, 1)]);
+        let mut no_u_turn = TurnCostTable::new();
+        no_u_turn.forbid(0, 1, 2);
+        assert_eq!(TurnGraph::new(&undirected, &no_u_turn).shortest_path(0, 2), (usize::MAX, vec![]));
+        assert_eq!(TurnGraph::new(&undirected, &no_u_turn).shortest_path(2, 0), (2, vec![2, 1, 0]));
+    }
+}
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use crate::builder::GraphBuilder;
+use crate::graph::Graph;
+
+/// Bidirectional mapping between the node labels of an input file and the
+/// dense ids used by the search functions.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "Vec<String>", into = "Vec<String>"))]
+pub struct IdMap {
+    labels: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl IdMap {
+    pub fn new() -> Self {
+        IdMap::default()
+    }
+
+    /// Identity map for files whose node ids are already `0..num_nodes`.
+    pub fn identity(num_nodes: usize) -> Self {
+        let mut map = IdMap::new();
+        for id in 0..num_nodes {
+            map.get_or_insert(&id.to_string());
+        }
+        map
+    }
+
+    pub fn get_or_insert(&mut self, label: &str) -> usize {
+        if let Some(&id) = self.ids.get(label) {
+            return id;
+        }
+        let id = self.labels.len();
+        self.labels.push(label.to_string());
+        self.ids.insert(label.to_string(), id);
+        id
+    }
+
+    pub fn id(&self, label: &str) -> Option<usize> {
+        self.ids.get(label).copied()
+    }
+
+    pub fn label(&self, id: usize) -> &str {
+        &self.labels[id]
+    }
+
+    /// Translates a path returned by a search back to the original labels.
+    pub fn labels(&self, path: &[usize]) -> Vec<&str> {
+        path.iter().map(|&id| self.label(id)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+impl TryFrom<Vec<String>> for IdMap {
+    type Error = String;
+
+    /// Builds the map from labels in id order, which must be unique.
+    fn try_from(labels: Vec<String>) -> Result<Self, Self::Error> {
+        let mut map = IdMap::new();
+        for label in &labels {
+            if map.id(label).is_some() {
+                return Err(format!("duplicate label `{}`", label));
+            }
+            map.get_or_insert(label);
+        }
+        Ok(map)
+    }
+}
+
+impl From<IdMap> for Vec<String> {
+    fn from(map: IdMap) -> Self {
+        map.labels
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    /// `None` splits on any whitespace.
+    pub delimiter: Option<char>,
+    pub has_header: bool,
+    pub source: Column,
+    pub target: Column,
+    /// Weight column, or `None` to give every edge `default_weight`.
+    pub weight: Option<Column>,
+    pub default_weight: usize,
+    /// Treat node fields as numeric ids instead of labels. The ids are used
+    /// as they are, and the returned `IdMap` is left empty.
+    pub numeric_ids: bool,
+    /// Lines starting with this prefix are skipped.
+    pub comment: Option<String>,
+}
+
+impl CsvOptions {
+    /// Plain `src dst weight` lines with numeric ids, `#` comments allowed.
+    pub fn edge_list() -> Self {
+        CsvOptions {
+            delimiter: None,
+            has_header: false,
+            source: Column::Index(0),
+            target: Column::Index(1),
+            weight: Some(Column::Index(2)),
+            default_weight: 1,
+            numeric_ids: true,
+            comment: Some("#".to_string()),
+        }
+    }
+
+    /// Comma-separated `source,target,weight` with a header and string labels.
+    pub fn csv() -> Self {
+        CsvOptions {
+            delimiter: Some(','),
+            has_header: true,
+            source: Column::Name("source".to_string()),
+            target: Column::Name("target".to_string()),
+            weight: Some(Column::Name("weight".to_string())),
+            default_weight: 1,
+            numeric_ids: false,
+            comment: None,
+        }
+    }
+}
+
+/// Streams an edge file line by line, so memory is bounded by the edge list
+/// rather than the file size.
+pub fn read_edges<R: BufRead>(mut reader: R, options: &CsvOptions) -> io::Result<(Graph, IdMap)> {
+    let mut labels = IdMap::new();
+    let mut edges = Vec::new();
+    let mut max_id = None;
+    let mut columns = None;
+    let mut line = String::new();
+    let mut line_no = 0;
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_no += 1;
+        let trimmed = line.trim_end_matches(['\n', '\r']);
+        if trimmed.trim().is_empty() || options.comment.as_deref().is_some_and(|c| trimmed.starts_with(c)) {
+            continue;
+        }
+        let fields = split_fields(trimmed, options.delimiter);
+
+        if columns.is_none() {
+            let header = if options.has_header { Some(fields.as_slice()) } else { None };
+            columns = Some((
+                resolve(&options.source, header)?,
+                resolve(&options.target, header)?,
+                options.weight.as_ref().map(|column| resolve(column, header)).transpose()?,
+            ));
+            if options.has_header {
+                continue;
+            }
+        }
+        let (source, target, weight) = columns.unwrap();
+
+        let field = |column: usize| {
+            fields.get(column).map(|f| f.as_str())
+                .ok_or_else(|| invalid(line_no, &format!("missing column {}", column)))
+        };
+        let (u, v) = if options.numeric_ids {
+            let u = parse(line_no, field(source)?)?;
+            let v = parse(line_no, field(target)?)?;
+            max_id = max_id.max(Some(u.max(v)));
+            (u, v)
+        } else {
+            (labels.get_or_insert(field(source)?), labels.get_or_insert(field(target)?))
+        };
+        let w = match weight {
+            Some(column) => parse(line_no, field(column)?)?,
+            None => options.default_weight,
+        };
+        edges.push((u, v, w));
+    }
+
+    let num_nodes = if options.numeric_ids { max_id.map_or(0, |id| id + 1) } else { labels.len() };
+    let graph = GraphBuilder::new(num_nodes).edges(edges).build()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok((graph, labels))
+}
+
+pub fn read_edges_file<P: AsRef<Path>>(path: P, options: &CsvOptions) -> io::Result<(Graph, IdMap)> {
+    read_edges(BufReader::new(File::open(path)?), options)
+}
+
+/// Writes one line per edge in the layout described by `options`. Named
+/// columns become the header; `labels` replaces ids with original labels.
+pub fn write_edges<W: Write>(graph: &Graph, labels: Option<&IdMap>, options: &CsvOptions, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    let delimiter = options.delimiter.map_or(" ".to_string(), |d| d.to_string());
+
+    let mut columns = vec![(&options.source, 0), (&options.target, 1)];
+    if let Some(weight) = &options.weight {
+        columns.push((weight, 2));
+    }
+    columns.sort_by_key(|(column, order)| match column {
+        Column::Index(i) => (*i, *order),
+        Column::Name(_) => (usize::MAX, *order),
+    });
+
+    if options.has_header {
+        let names: Vec<String> = columns.iter().map(|(column, order)| match column {
+            Column::Name(name) => quote(name, options.delimiter),
+            Column::Index(_) => ["source", "target", "weight"][*order].to_string(),
+        }).collect();
+        writeln!(writer, "{}", names.join(&delimiter))?;
+    }
+
+    let name = |id: usize| match labels {
+        Some(labels) => quote(labels.label(id), options.delimiter),
+        None => id.to_string(),
+    };
+    for (u, edges) in graph.adj_list.iter().enumerate() {
+        for &(v, weight) in edges {
+            let fields: Vec<String> = columns.iter().map(|(_, order)| match order {
+                0 => name(u),
+                1 => name(v),
+                _ => weight.to_string(),
+            }).collect();
+            writeln!(writer, "{}", fields.join(&delimiter))?;
+        }
+    }
+    writer.flush()
+}
+
+pub fn write_edges_file<P: AsRef<Path>>(graph: &Graph, labels: Option<&IdMap>, options: &CsvOptions, path: P) -> io::Result<()> {
+    write_edges(graph, labels, options, File::create(path)?)
+}
+
+/// Splits a line on `delimiter` (or whitespace), honouring double-quoted
+/// fields with `""` escapes.
+pub(crate) fn split_fields(line: &str, delimiter: Option<char>) -> Vec<String> {
+    let Some(delimiter) = delimiter else {
+        return line.split_whitespace().map(|field| field.trim_matches('"').to_string()).collect();
+    };
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+fn quote(field: &str, delimiter: Option<char>) -> String {
+    let needs_quotes = match delimiter {
+        Some(d) => field.contains(d) || field.contains('"'),
+        None => field.contains(char::is_whitespace) || field.contains('"'),
+    };
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn resolve(column: &Column, header: Option<&[String]>) -> io::Result<usize> {
+    match (column, header) {
+        (Column::Index(i), _) => Ok(*i),
+        (Column::Name(name), Some(header)) => header.iter().position(|h| h == name)
+            .ok_or_else(|| invalid(1, &format!("missing column `{}`", name))),
+        (Column::Name(name), None) => Err(invalid(0, &format!("column `{}` given by name but the file has no header", name))),
+    }
+}
+
+fn parse(line_no: usize, field: &str) -> io::Result<usize> {
+    field.parse().map_err(|_| invalid(line_no, &format!("invalid number `{}`", field)))
+}
+
+fn invalid(line_no: usize, message: &str) -> io::Error {
+    let message = if line_no == 0 { message.to_string() } else { format!("line {}: {}", line_no, message) };
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_dijkstra::sequential_dijkstra;
+
+    #[test]
+    fn test_read_edge_list() {
+        let input = "# src dst weight\n0 1 2\n0 2 4\n1 2 1\n\n3 0 7\n";
+        let (graph, labels) = read_edges(input.as_bytes(), &CsvOptions::edge_list()).unwrap();
+        assert_eq!(graph.adj_list, vec![vec![(1, 2), (2, 4)], vec![(2, 1)], vec![], vec![(0, 7)]]);
+        assert!(labels.is_empty());
+        assert_eq!(sequential_dijkstra(&graph.adj_list, 0, 2), (3, vec![0, 1, 2]));
+
+        // Numeric ids get no labels, however large they are
+        let (graph, labels) = read_edges("0 1000000 1\n".as_bytes(), &CsvOptions::edge_list()).unwrap();
+        assert_eq!(graph.num_nodes(), 1_000_001);
+        assert!(labels.is_empty());
+    }
+
+    #[test]
+    fn test_read_csv_with_labels() {
+        let input = "weight;from;to\n5;\"Main St; North\";Market\n1;Market;Harbour\n9;\"Main St; North\";Harbour\n";
+        let options = CsvOptions {
+            delimiter: Some(';'),
+            source: Column::Name("from".to_string()),
+            target: Column::Name("to".to_string()),
+            ..CsvOptions::csv()
+        };
+        let (graph, labels) = read_edges(input.as_bytes(), &options).unwrap();
+        let start = labels.id("Main St; North").unwrap();
+        let goal = labels.id("Harbour").unwrap();
+        let (cost, path) = sequential_dijkstra(&graph.adj_list, start, goal);
+        assert_eq!(cost, 6);
+        assert_eq!(labels.labels(&path), vec!["Main St; North", "Market", "Harbour"]);
+    }
+
+    #[test]
+    fn test_unweighted_and_errors() {
+        let options = CsvOptions { weight: None, ..CsvOptions::edge_list() };
+        let (graph, _) = read_edges("0 1\n1 2\n".as_bytes(), &options).unwrap();
+        assert_eq!(graph.adj_list, vec![vec![(1, 1)], vec![(2, 1)], vec![]]);
+
+        let err = read_edges("0 1 2\n1 x 3\n".as_bytes(), &CsvOptions::edge_list()).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+        assert!(read_edges("a,b\n".as_bytes(), &CsvOptions::csv()).is_err());  // No `source` column
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let input = "source,target,weight\n\"A, 1\",B,3\nB,C,4\n";
+        let (graph, labels) = read_edges(input.as_bytes(), &CsvOptions::csv()).unwrap();
+        let mut out = Vec::new();
+        write_edges(&graph, Some(&labels), &CsvOptions::csv(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out.clone()).unwrap(), input);
+
+        let mut out = Vec::new();
+        write_edges(&graph, None, &CsvOptions::edge_list(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0 1 3\n1 2 4\n");
+    }
+}
@@ -0,0 +1,568 @@
+//! Seeded synthetic graph generators for tests and benchmarks.
+//!
+//! Work is split into fixed-size chunks that each get their own RNG derived
+//! from the seed, so the chunks run in parallel and the result depends only
+//! on the arguments, not on the number of threads.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use crate::builder::{GraphBuilder, ParallelEdges, SelfLoops};
+use crate::graph::Graph;
+
+const CHUNK: usize = 4096;
+
+/// The Graph500 R-MAT quadrant probabilities `(a, b, c)`; `d = 1 - a - b - c`.
+pub const RMAT_GRAPH500: (f64, f64, f64) = (0.57, 0.19, 0.19);
+
+/// How edge weights are drawn. All weights are at least 1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Weights {
+    Constant(usize),
+    /// Uniform over `min..=max`, with both raised to 1: `Uniform(0, n)` never
+    /// gives a zero weight. Panics if `min > max`.
+    Uniform(usize, usize),
+    /// Exponential with the given mean, rounded up.
+    Exponential(f64),
+    /// Euclidean length of the edge times the factor, rounded. Generators
+    /// without coordinates use length 1.
+    Euclidean(f64),
+}
+
+impl Weights {
+    pub fn sample<R: Rng>(&self, rng: &mut R, length: f64) -> usize {
+        match *self {
+            Weights::Constant(weight) => weight.max(1),
+            Weights::Uniform(min, max) => {
+                assert!(min <= max, "Weights::Uniform({}, {}): min is greater than max", min, max);
+                rng.gen_range(min.max(1)..=max.max(1))
+            }
+            Weights::Exponential(mean) => {
+                let u: f64 = rng.gen();
+                ((-mean * (1.0 - u).ln()).ceil() as usize).max(1)
+            }
+            Weights::Euclidean(factor) => ((length * factor).round() as usize).max(1),
+        }
+    }
+}
+
+/// A `rows × cols` grid with edges to the four neighbours in both directions
+/// (same weight each way). Node `r * cols + c` sits at `(c, r)`.
+pub fn grid(rows: usize, cols: usize, weights: Weights, seed: u64) -> Graph {
+    let num_nodes = rows * cols;
+    let edges = par_chunks(num_nodes, seed, |nodes, rng, edges| {
+        for u in nodes {
+            let (r, c) = (u / cols, u % cols);
+            if c + 1 < cols {
+                push_undirected(edges, u, u + 1, weights.sample(rng, 1.0));
+            }
+            if r + 1 < rows {
+                push_undirected(edges, u, u + cols, weights.sample(rng, 1.0));
+            }
+        }
+    });
+    let coordinates = (0..num_nodes).map(|u| ((u % cols) as f64, (u / cols) as f64)).collect();
+    build(num_nodes, edges).with_coordinates(coordinates)
+}
+
+/// Directed Erdős–Rényi G(n, p): every ordered pair `u != v` is an edge with
+/// probability `p`. Uses geometric skipping, so the cost is O(n + m).
+pub fn gnp(num_nodes: usize, p: f64, weights: Weights, seed: u64) -> Graph {
+    let edges = par_chunks(num_nodes, seed, |nodes, rng, edges| {
+        if p <= 0.0 || num_nodes < 2 {
+            return;
+        }
+        let log_q = (1.0 - p).ln();
+        for u in nodes {
+            // Candidate `k` stands for target `k`, skipping `u` itself.
+            let mut k = 0;
+            loop {
+                if p < 1.0 {
+                    let r: f64 = rng.gen();
+                    let skip = ((1.0 - r).ln() / log_q).floor();
+                    if skip >= (num_nodes - 1 - k) as f64 {
+                        break;
+                    }
+                    k += skip as usize;
+                }
+                if k >= num_nodes - 1 {
+                    break;
+                }
+                let v = if k < u { k } else { k + 1 };
+                edges.push((u, v, weights.sample(rng, 1.0)));
+                k += 1;
+            }
+        }
+    });
+    build(num_nodes, edges)
+}
+
+/// Directed Erdős–Rényi G(n, m): exactly `m` distinct edges without
+/// self-loops, chosen uniformly. Meant for sparse graphs.
+pub fn gnm(num_nodes: usize, num_edges: usize, weights: Weights, seed: u64) -> Graph {
+    assert!(num_edges <= num_nodes * num_nodes.saturating_sub(1), "more edges than node pairs");
+    let draw = |count: usize, seed: u64| par_chunks(count, seed, |range, rng, edges| {
+        let nodes = Uniform::from(0..num_nodes);
+        for _ in range {
+            let u = nodes.sample(rng);
+            let mut v = nodes.sample(rng);
+            while v == u {
+                v = nodes.sample(rng);
+            }
+            edges.push((u, v, weights.sample(rng, 1.0)));
+        }
+    });
+
+    // Draw, drop duplicates and top up until there are enough distinct edges.
+    let mut edges = Vec::with_capacity(num_edges);
+    let mut round = 0;
+    while edges.len() < num_edges {
+        edges.extend(draw(num_edges - edges.len(), mix(seed, round)));
+        edges.par_sort_by_key(|&(u, v, _)| (u, v));
+        edges.dedup_by_key(|&mut (u, v, _)| (u, v));
+        round += 1;
+    }
+    build(num_nodes, edges)
+}
+
+/// R-MAT (recursive matrix) graph on `2^scale` nodes: each of `num_edges`
+/// edges picks a quadrant of the adjacency matrix with probabilities
+/// `(a, b, c, 1 - a - b - c)` at every level, which yields skewed, power-law
+/// degrees. Duplicates and self-loops are dropped, so the graph may have fewer
+/// than `num_edges` edges.
+pub fn rmat(scale: u32, num_edges: usize, (a, b, c): (f64, f64, f64), weights: Weights, seed: u64) -> Graph {
+    assert!(a + b + c <= 1.0, "quadrant probabilities must sum to at most 1");
+    let num_nodes = 1usize << scale;
+    let edges = par_chunks(num_edges, seed, |range, rng, edges| {
+        for _ in range {
+            let (mut u, mut v) = (0, 0);
+            for _ in 0..scale {
+                let r: f64 = rng.gen();
+                let (down, right) = if r < a {
+                    (0, 0)
+                } else if r < a + b {
+                    (0, 1)
+                } else if r < a + b + c {
+                    (1, 0)
+                } else {
+                    (1, 1)
+                };
+                u = (u << 1) | down;
+                v = (v << 1) | right;
+            }
+            edges.push((u, v, weights.sample(rng, 1.0)));
+        }
+    });
+    GraphBuilder::new(num_nodes)
+        .parallel_edges(ParallelEdges::KeepMin)
+        .self_loops(SelfLoops::Drop)
+        .edges(edges)
+        .build()
+        .expect("generated nodes are in range")
+}
+
+/// Barabási–Albert preferential attachment: every new node links to
+/// `links` distinct existing nodes with probability proportional to their
+/// degree. Edges go both ways. Attachment is inherently sequential; only the
+/// graph construction runs in parallel.
+pub fn barabasi_albert(num_nodes: usize, links: usize, weights: Weights, seed: u64) -> Graph {
+    assert!(links >= 1 && links < num_nodes, "need 1 <= links < num_nodes");
+    let mut rng = StdRng::seed_from_u64(seed);
+    // Every node appears once per incident edge, so a uniform pick from this
+    // list is a degree-proportional pick.
+    let mut endpoints: Vec<usize> = Vec::with_capacity(2 * links * num_nodes);
+    let mut edges = Vec::with_capacity(2 * links * num_nodes);
+    let mut targets = HashSet::with_capacity(links);
+
+    for u in links..num_nodes {
+        targets.clear();
+        if endpoints.is_empty() {
+            targets.extend(0..links);
+        }
+        while targets.len() < links {
+            targets.insert(endpoints[rng.gen_range(0..endpoints.len())]);
+        }
+        let mut sorted: Vec<usize> = targets.iter().copied().collect();
+        sorted.sort_unstable();
+        for v in sorted {
+            push_undirected(&mut edges, u, v, weights.sample(&mut rng, 1.0));
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+    build(num_nodes, edges)
+}
+
+/// Random geometric graph: `num_nodes` points uniform in the unit square,
+/// joined in both directions when closer than `radius`. Coordinates are kept,
+/// so `Weights::Euclidean` gives distance-proportional weights.
+pub fn random_geometric(num_nodes: usize, radius: f64, weights: Weights, seed: u64) -> Graph {
+    let points: Vec<(f64, f64)> = par_chunks_map(num_nodes, seed, |nodes, rng| {
+        nodes.map(|_| (rng.gen::<f64>(), rng.gen::<f64>())).collect()
+    });
+
+    // Bucket the points into cells at least `radius` wide, so neighbours are
+    // within the surrounding 3 × 3 cells.
+    let cells = ((1.0 / radius).floor() as usize).clamp(1, (num_nodes as f64).sqrt() as usize + 1);
+    let cell_of = |(x, y): (f64, f64)| {
+        let cx = ((x * cells as f64) as usize).min(cells - 1);
+        let cy = ((y * cells as f64) as usize).min(cells - 1);
+        (cx, cy)
+    };
+    let mut by_cell: Vec<usize> = (0..num_nodes).collect();
+    by_cell.par_sort_by_key(|&u| {
+        let (cx, cy) = cell_of(points[u]);
+        (cy * cells + cx, u)
+    });
+    let mut cell_start = vec![0; cells * cells + 1];
+    for &u in &by_cell {
+        let (cx, cy) = cell_of(points[u]);
+        cell_start[cy * cells + cx + 1] += 1;
+    }
+    for i in 0..cells * cells {
+        cell_start[i + 1] += cell_start[i];
+    }
+
+    let edges = par_chunks(num_nodes, mix(seed, 1), |nodes, rng, edges| {
+        for u in nodes {
+            let (cx, cy) = cell_of(points[u]);
+            for ny in cy.saturating_sub(1)..=(cy + 1).min(cells - 1) {
+                for nx in cx.saturating_sub(1)..=(cx + 1).min(cells - 1) {
+                    let cell = ny * cells + nx;
+                    for &v in &by_cell[cell_start[cell]..cell_start[cell + 1]] {
+                        let length = distance(points[u], points[v]);
+                        if v > u && length < radius {
+                            push_undirected(edges, u, v, weights.sample(rng, length));
+                        }
+                    }
+                }
+            }
+        }
+    });
+    build(num_nodes, edges).with_coordinates(points)
+}
+
+/// Parameters for `road_network`. Distances are in metres.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RoadOptions {
+    pub rows: usize,
+    pub cols: usize,
+    /// Distance between neighbouring grid points.
+    pub spacing: f64,
+    /// Maximum displacement of a point, as a fraction of `spacing`.
+    pub jitter: f64,
+    /// Probability that a local street segment exists.
+    pub street_density: f64,
+    /// Probability that a grid cell gets a diagonal street.
+    pub diagonals: f64,
+    /// Every `arterial_every`-th row and column is an arterial road; highway
+    /// interchanges sit on arterial crossings.
+    pub arterial_every: usize,
+    /// Every `highway_every`-th row and column carries a highway.
+    pub highway_every: usize,
+}
+
+impl RoadOptions {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        RoadOptions {
+            rows,
+            cols,
+            spacing: 100.0,
+            jitter: 0.3,
+            street_density: 0.8,
+            diagonals: 0.15,
+            arterial_every: 8,
+            highway_every: 32,
+        }
+    }
+}
+
+const ARTERIAL_SPEEDUP: f64 = 2.0;
+/// Weights are at least the Euclidean distance divided by this.
+pub const HIGHWAY_SPEEDUP: f64 = 4.0;
+
+/// A road-like network on a perturbed grid: sparse local streets with the
+/// odd diagonal, a grid of arterial roads, and highways linking interchanges.
+/// Every road goes both ways and its weight is the travel time, the
+/// Euclidean length divided by the tier's speed-up (1, 2 or 4). Only the
+/// largest connected component is kept, so there may be fewer than
+/// `rows * cols` nodes; coordinates are in metres.
+pub fn road_network(options: &RoadOptions, seed: u64) -> Graph {
+    let RoadOptions { rows, cols, spacing, jitter, street_density, diagonals, arterial_every, highway_every } = *options;
+    assert!(arterial_every > 0 && highway_every > 0, "road spacings must be positive");
+    let num_nodes = rows * cols;
+    let points: Vec<(f64, f64)> = par_chunks_map(num_nodes, seed, |nodes, rng| {
+        nodes.map(|u| {
+            let (dx, dy) = (rng.gen_range(-jitter..=jitter), rng.gen_range(-jitter..=jitter));
+            (((u % cols) as f64 + dx) * spacing, ((u / cols) as f64 + dy) * spacing)
+        }).collect()
+    });
+
+    let road = |edges: &mut Vec<(usize, usize, usize)>, u: usize, v: usize, speedup: f64| {
+        let weight = (distance(points[u], points[v]) / speedup).round() as usize;
+        push_undirected(edges, u, v, weight.max(1));
+    };
+    let edges = par_chunks(num_nodes, mix(seed, 1), |nodes, rng, edges| {
+        for u in nodes {
+            let (r, c) = (u / cols, u % cols);
+            if c + 1 < cols {
+                if r % arterial_every == 0 {
+                    road(edges, u, u + 1, ARTERIAL_SPEEDUP);
+                } else if rng.gen_bool(street_density) {
+                    road(edges, u, u + 1, 1.0);
+                }
+            }
+            if r + 1 < rows {
+                if c % arterial_every == 0 {
+                    road(edges, u, u + cols, ARTERIAL_SPEEDUP);
+                } else if rng.gen_bool(street_density) {
+                    road(edges, u, u + cols, 1.0);
+                }
+            }
+            if r + 1 < rows && c + 1 < cols && rng.gen_bool(diagonals) {
+                if rng.gen() {
+                    road(edges, u, u + cols + 1, 1.0);
+                } else {
+                    road(edges, u + 1, u + cols, 1.0);
+                }
+            }
+            if r % highway_every == 0 && c % arterial_every == 0 && c + arterial_every < cols {
+                road(edges, u, u + arterial_every, HIGHWAY_SPEEDUP);
+            }
+            if c % highway_every == 0 && r % arterial_every == 0 && r + arterial_every < rows {
+                road(edges, u, u + arterial_every * cols, HIGHWAY_SPEEDUP);
+            }
+        }
+    });
+    largest_component(edges, points)
+}
+
+/// Keeps the largest weakly connected component, renumbering nodes in order.
+fn largest_component(edges: Vec<(usize, usize, usize)>, points: Vec<(f64, f64)>) -> Graph {
+    fn find(parent: &mut [usize], mut u: usize) -> usize {
+        while parent[u] != u {
+            parent[u] = parent[parent[u]];
+            u = parent[u];
+        }
+        u
+    }
+
+    let num_nodes = points.len();
+    let mut parent: Vec<usize> = (0..num_nodes).collect();
+    for &(u, v, _) in &edges {
+        let (a, b) = (find(&mut parent, u), find(&mut parent, v));
+        if a != b {
+            parent[a] = b;
+        }
+    }
+    let mut size = vec![0; num_nodes];
+    for u in 0..num_nodes {
+        size[find(&mut parent, u)] += 1;
+    }
+    let Some(root) = (0..num_nodes).max_by_key(|&u| size[u]) else {
+        return build(0, edges).with_coordinates(points);
+    };
+
+    let mut ids = vec![usize::MAX; num_nodes];
+    let mut kept = Vec::with_capacity(size[root]);
+    for u in 0..num_nodes {
+        if find(&mut parent, u) == root {
+            ids[u] = kept.len();
+            kept.push(points[u]);
+        }
+    }
+    let edges = edges.into_iter()
+        .filter(|&(u, _, _)| ids[u] != usize::MAX)
+        .map(|(u, v, weight)| (ids[u], ids[v], weight))
+        .collect();
+    build(kept.len(), edges).with_coordinates(kept)
+}
+
+pub(crate) fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+pub(crate) fn push_undirected(edges: &mut Vec<(usize, usize, usize)>, u: usize, v: usize, weight: usize) {
+    edges.push((u, v, weight));
+    edges.push((v, u, weight));
+}
+
+pub(crate) fn build(num_nodes: usize, edges: Vec<(usize, usize, usize)>) -> Graph {
+    GraphBuilder::new(num_nodes).edges(edges).build().expect("generated nodes are in range")
+}
+
+/// Runs `generate` over `0..count` in chunks of `CHUNK`, each with its own
+/// RNG, and concatenates the edges in chunk order.
+pub(crate) fn par_chunks<F>(count: usize, seed: u64, generate: F) -> Vec<(usize, usize, usize)>
+where
+    F: Fn(Range<usize>, &mut StdRng, &mut Vec<(usize, usize, usize)>) + Sync,
+{
+    (0..count.div_ceil(CHUNK)).into_par_iter().flat_map_iter(|chunk| {
+        let mut rng = StdRng::seed_from_u64(mix(seed, chunk as u64));
+        let mut edges = Vec::new();
+        generate(chunk * CHUNK..((chunk + 1) * CHUNK).min(count), &mut rng, &mut edges);
+        edges
+    }).collect()
+}
+
+pub(crate) fn par_chunks_map<T: Send, F>(count: usize, seed: u64, generate: F) -> Vec<T>
+where
+    F: Fn(Range<usize>, &mut StdRng) -> Vec<T> + Sync,
+{
+    (0..count.div_ceil(CHUNK)).into_par_iter().flat_map_iter(|chunk| {
+        let mut rng = StdRng::seed_from_u64(mix(seed, chunk as u64));
+        generate(chunk * CHUNK..((chunk + 1) * CHUNK).min(count), &mut rng)
+    }).collect()
+}
+
+/// SplitMix64 of `seed` combined with `stream`, for independent chunk seeds.
+pub(crate) fn mix(seed: u64, stream: u64) -> u64 {
+    let mut z = seed ^ stream.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bidirectional_dijkstra, sequential_dijkstra, sequential_sssp};
+
+    #[test]
+    fn test_grid() {
+        let graph = grid(3, 4, Weights::Constant(2), 1);
+        assert_eq!(graph.num_nodes(), 12);
+        assert_eq!(graph.num_edges(), 2 * (3 * 3 + 2 * 4));
+        assert_eq!(graph.coordinates.as_ref().unwrap()[7], (3.0, 1.0));
+        assert_eq!(sequential_dijkstra(&graph.adj_list, 0, 11).0, 2 * 5);  // Manhattan distance
+    }
+
+    #[test]
+    fn test_uniform_weights() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let weights: HashSet<usize> = (0..1000).map(|_| Weights::Uniform(0, 3).sample(&mut rng, 1.0)).collect();
+        assert_eq!(weights, HashSet::from([1, 2, 3]));  // 0 is raised to 1
+    }
+
+    #[test]
+    #[should_panic(expected = "min is greater than max")]
+    fn test_uniform_weights_reversed_bounds() {
+        Weights::Uniform(9, 2).sample(&mut StdRng::seed_from_u64(1), 1.0);
+    }
+
+    #[test]
+    fn test_seeded_and_reproducible() {
+        let a = gnm(5000, 20000, Weights::Uniform(1, 99), 7);
+        let b = gnm(5000, 20000, Weights::Uniform(1, 99), 7);
+        let c = gnm(5000, 20000, Weights::Uniform(1, 99), 8);
+        assert_eq!(a.adj_list, b.adj_list);
+        assert_ne!(a.adj_list, c.adj_list);
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let single = pool.install(|| random_geometric(5000, 0.02, Weights::Euclidean(1000.0), 3));
+        assert_eq!(single.adj_list, random_geometric(5000, 0.02, Weights::Euclidean(1000.0), 3).adj_list);
+    }
+
+    #[test]
+    fn test_gnp_and_gnm() {
+        let graph = gnp(2000, 0.01, Weights::Constant(1), 1);
+        let expected = 0.01 * 2000.0 * 1999.0;
+        assert!((graph.num_edges() as f64 - expected).abs() < 0.05 * expected);
+        assert_eq!(gnp(10, 1.0, Weights::Constant(1), 1).num_edges(), 90);
+        assert_eq!(gnp(10, 0.0, Weights::Constant(1), 1).num_edges(), 0);
+
+        let graph = gnm(100, 5000, Weights::Uniform(3, 5), 1);
+        assert_eq!(graph.num_edges(), 5000);
+        for (u, edges) in graph.adj_list.iter().enumerate() {
+            assert!(edges.windows(2).all(|pair| pair[0].0 < pair[1].0));  // Distinct targets
+            assert!(edges.iter().all(|&(v, w)| v != u && (3..=5).contains(&w)));
+        }
+    }
+
+    #[test]
+    fn test_rmat_is_skewed() {
+        let graph = rmat(12, 40000, RMAT_GRAPH500, Weights::Exponential(10.0), 1);
+        assert_eq!(graph.num_nodes(), 4096);
+        let degrees: Vec<usize> = graph.adj_list.iter().map(|edges| edges.len()).collect();
+        let average = graph.num_edges() as f64 / 4096.0;
+        assert!(degrees[0] as f64 > 20.0 * average);
+        assert!(degrees.iter().filter(|&&d| d == 0).count() > 100);
+    }
+
+    #[test]
+    fn test_barabasi_albert() {
+        let graph = barabasi_albert(1000, 3, Weights::Constant(1), 1);
+        assert_eq!(graph.num_edges(), 2 * 3 * (1000 - 3));
+        let max_degree = graph.adj_list.iter().map(|edges| edges.len()).max().unwrap();
+        assert!(max_degree > 30);  // Hubs form
+        assert_eq!(graph.adj_list, graph.rev_adj_list.iter().map(|edges| {
+            let mut edges = edges.clone();
+            edges.sort();
+            edges
+        }).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_random_geometric() {
+        let radius = 0.05;
+        let graph = random_geometric(2000, radius, Weights::Euclidean(1000.0), 1);
+        let points = graph.coordinates.as_ref().unwrap();
+        for (u, edges) in graph.adj_list.iter().enumerate() {
+            for &(v, weight) in edges {
+                let length = distance(points[u], points[v]);
+                assert!(length < radius);
+                assert_eq!(weight, ((length * 1000.0).round() as usize).max(1));
+            }
+        }
+        // Brute force count of close pairs.
+        let close = (0..2000).map(|u| (0..2000).filter(|&v| v != u && distance(points[u], points[v]) < radius).count()).sum::<usize>();
+        assert_eq!(graph.num_edges(), close);
+    }
+
+    #[test]
+    fn test_road_network_is_connected() {
+        let graph = road_network(&RoadOptions::new(60, 80), 5);
+        assert!(graph.num_nodes() > 60 * 80 * 9 / 10);
+        let (dist, _) = sequential_sssp(&graph, 0);
+        assert!(dist.iter().all(|&d| d != usize::MAX));
+        assert_eq!(graph.adj_list, road_network(&RoadOptions::new(60, 80), 5).adj_list);
+
+        let points = graph.coordinates.as_ref().unwrap();
+        for (u, edges) in graph.adj_list.iter().enumerate() {
+            for &(v, weight) in edges {
+                let lower_bound = (distance(points[u], points[v]) / HIGHWAY_SPEEDUP).round() as usize;
+                assert!(weight >= lower_bound.max(1));
+                assert!(graph.adj_list[v].contains(&(u, weight)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_road_network_hierarchy() {
+        let options = RoadOptions { jitter: 0.0, street_density: 1.0, diagonals: 0.0, ..RoadOptions::new(65, 65) };
+        let graph = road_network(&options, 1);
+        assert_eq!(graph.num_nodes(), 65 * 65);
+        // Corner to corner along the highways on row 0 and column 64.
+        assert_eq!(sequential_dijkstra(&graph, 0, 65 * 65 - 1).0, 2 * 64 * 100 / 4);
+        // Between two highways the arterials are the fastest route.
+        assert_eq!(sequential_dijkstra(&graph, 8 * 65 + 8, 8 * 65 + 24).0, 16 * 100 / 2);
+
+        let hierarchy_cost = sequential_dijkstra(&graph, 30 * 65 + 30, 60 * 65 + 60).0;
+        let streets_only = RoadOptions { arterial_every: 1000, highway_every: 1000, ..options };
+        let graph = road_network(&streets_only, 1);
+        assert_eq!(sequential_dijkstra(&graph, 30 * 65 + 30, 60 * 65 + 60).0, 60 * 100);
+        assert!(hierarchy_cost < 60 * 100 / 2);
+    }
+
+    #[test]
+    fn test_large_instances() {
+        let graph = gnm(200000, 1000000, Weights::Uniform(1, 99), 42);
+        assert_eq!(graph.num_edges(), 1000000);
+
+        let graph = random_geometric(200000, 0.004, Weights::Euclidean(10000.0), 42);
+        assert_eq!(sequential_dijkstra(&graph, 0, 199999).0, bidirectional_dijkstra(&graph, 0, 199999).0);
+    }
+}
@@ -1,19 +1,346 @@
+use std::borrow::Cow;
+use crate::turn_costs::TurnCostTable;
+
+#[derive(Clone, Debug)]
 pub struct Graph {
     pub adj_list: Vec<Vec<(usize, usize)>>,
     pub rev_adj_list: Vec<Vec<(usize, usize)>>,
+    pub turn_costs: TurnCostTable,
+    /// Optional `(x, y)` position of every node, e.g. longitude and latitude.
+    pub coordinates: Option<Vec<(f64, f64)>>,
 }
 
 impl Graph {
     pub fn new(adj_list: Vec<Vec<(usize, usize)>>) -> Self {
-        let mut rev_adj_list = vec![vec![]; adj_list.len()];
-        for (u, neighbors) in adj_list.iter().enumerate() {
-            for &(v, weight) in neighbors {
-                rev_adj_list[v].push((u, weight));
-            }
-        }
+        let rev_adj_list = reverse(&adj_list);
         Graph {
             adj_list,
             rev_adj_list,
+            turn_costs: TurnCostTable::new(),
+            coordinates: None,
+        }
+    }
+
+    pub fn with_turn_costs(mut self, turn_costs: TurnCostTable) -> Self {
+        self.turn_costs = turn_costs;
+        self
+    }
+
+    pub fn with_coordinates(mut self, coordinates: Vec<(f64, f64)>) -> Self {
+        assert_eq!(coordinates.len(), self.adj_list.len(), "one coordinate per node");
+        self.coordinates = Some(coordinates);
+        self
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.adj_list.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.adj_list.iter().map(|edges| edges.len()).sum()
+    }
+
+    /// Adds the edge `u -> v`. Parallel edges are kept.
+    pub fn insert_edge(&mut self, u: usize, v: usize, weight: usize) {
+        self.adj_list[u].push((v, weight));
+        self.rev_adj_list[v].push((u, weight));
+    }
+
+    /// Removes one `u -> v` edge and returns its weight, or `None` if there is
+    /// no such edge.
+    pub fn delete_edge(&mut self, u: usize, v: usize) -> Option<usize> {
+        let i = self.adj_list[u].iter().position(|&(t, _)| t == v)?;
+        let (_, weight) = self.adj_list[u].swap_remove(i);
+        let j = self.rev_adj_list[v].iter().position(|&(s, w)| s == u && w == weight).unwrap();
+        self.rev_adj_list[v].swap_remove(j);
+        Some(weight)
+    }
+
+    /// Changes the weight of one `u -> v` edge and returns the old weight, or
+    /// `None` if there is no such edge.
+    pub fn set_weight(&mut self, u: usize, v: usize, weight: usize) -> Option<usize> {
+        let edge = self.adj_list[u].iter_mut().find(|(t, _)| *t == v)?;
+        let old = std::mem::replace(&mut edge.1, weight);
+        let rev_edge = self.rev_adj_list[v].iter_mut().find(|&&mut (s, w)| s == u && w == old).unwrap();
+        rev_edge.1 = weight;
+        Some(old)
+    }
+}
+
+/// Only the forward adjacency is stored; `rev_adj_list` is rebuilt on load, and
+/// edges pointing outside the graph are rejected, for `Graph` and
+/// `UndirectedGraph` alike.
+#[cfg(feature = "serde")]
+mod graph_serde {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use crate::turn_costs::TurnCostTable;
+    use super::{Graph, UndirectedGraph};
+
+    #[derive(Serialize)]
+    struct GraphRef<'a> {
+        adj_list: &'a [Vec<(usize, usize)>],
+        turn_costs: &'a TurnCostTable,
+        coordinates: &'a Option<Vec<(f64, f64)>>,
+    }
+
+    #[derive(Deserialize)]
+    struct GraphData {
+        adj_list: Vec<Vec<(usize, usize)>>,
+        #[serde(default)]
+        turn_costs: TurnCostTable,
+        #[serde(default)]
+        coordinates: Option<Vec<(f64, f64)>>,
+    }
+
+    impl Serialize for Graph {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            GraphRef { adj_list: &self.adj_list, turn_costs: &self.turn_costs, coordinates: &self.coordinates }
+                .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Graph {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let data = GraphData::deserialize(deserializer)?;
+            let num_nodes = data.adj_list.len();
+            check_targets(&data.adj_list)?;
+            if data.coordinates.as_ref().is_some_and(|c| c.len() != num_nodes) {
+                return Err(de::Error::custom("expected one coordinate per node"));
+            }
+            let mut graph = Graph::new(data.adj_list).with_turn_costs(data.turn_costs);
+            graph.coordinates = data.coordinates;
+            Ok(graph)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct UndirectedGraphData {
+        adj_list: Vec<Vec<(usize, usize)>>,
+    }
+
+    impl<'de> Deserialize<'de> for UndirectedGraph {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let data = UndirectedGraphData::deserialize(deserializer)?;
+            check_targets(&data.adj_list)?;
+            Ok(UndirectedGraph { adj_list: data.adj_list })
+        }
+    }
+
+    fn check_targets<E: de::Error>(adj_list: &[Vec<(usize, usize)>]) -> Result<(), E> {
+        let num_nodes = adj_list.len();
+        match adj_list.iter().flatten().find(|&&(v, _)| v >= num_nodes) {
+            Some(&(v, _)) => Err(E::custom(format!("edge to node {} in a graph with {} nodes", v, num_nodes))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Undirected graph storing each edge once per endpoint. The reverse adjacency
+/// is the forward adjacency, so no `rev_adj_list` is kept.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UndirectedGraph {
+    pub adj_list: Vec<Vec<(usize, usize)>>,
+}
+
+impl UndirectedGraph {
+    pub fn new(num_nodes: usize) -> Self {
+        UndirectedGraph { adj_list: vec![Vec::new(); num_nodes] }
+    }
+
+    pub fn from_edges<I: IntoIterator<Item = (usize, usize, usize)>>(num_nodes: usize, edges: I) -> Self {
+        let mut graph = UndirectedGraph::new(num_nodes);
+        for (u, v, weight) in edges {
+            graph.add_edge(u, v, weight);
+        }
+        graph
+    }
+
+    pub fn add_edge(&mut self, u: usize, v: usize, weight: usize) {
+        self.adj_list[u].push((v, weight));
+        if u != v {
+            self.adj_list[v].push((u, weight));
+        }
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.adj_list.len()
+    }
+}
+
+/// Read access to the outgoing edges of every node. The searches are written
+/// against this trait so they run unchanged on nested `Vec`s, `Graph` and
+/// flat CSR storage such as a memory-mapped graph file.
+pub trait Neighbors {
+    fn num_nodes(&self) -> usize;
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_;
+
+    fn degree(&self, node: usize) -> usize {
+        self.neighbors(node).count()
+    }
+}
+
+impl Neighbors for [Vec<(usize, usize)>] {
+    fn num_nodes(&self) -> usize {
+        self.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self[node].iter().copied()
+    }
+
+    fn degree(&self, node: usize) -> usize {
+        self[node].len()
+    }
+}
+
+impl Neighbors for Vec<Vec<(usize, usize)>> {
+    fn num_nodes(&self) -> usize {
+        self.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self[node].iter().copied()
+    }
+
+    fn degree(&self, node: usize) -> usize {
+        self[node].len()
+    }
+}
+
+impl Neighbors for Graph {
+    fn num_nodes(&self) -> usize {
+        self.adj_list.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.adj_list[node].iter().copied()
+    }
+
+    fn degree(&self, node: usize) -> usize {
+        self.adj_list[node].len()
+    }
+}
+
+impl Neighbors for UndirectedGraph {
+    fn num_nodes(&self) -> usize {
+        self.adj_list.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.adj_list[node].iter().copied()
+    }
+
+    fn degree(&self, node: usize) -> usize {
+        self.adj_list[node].len()
+    }
+}
+
+/// Reverse adjacency on top of `Neighbors`, for searches that expand in both
+/// directions. By default the reverse is built on demand; `Graph` and
+/// `UndirectedGraph` hand out what they already store.
+pub trait Adjacency: Neighbors {
+    fn rev_adj_list(&self) -> Cow<'_, [Vec<(usize, usize)>]> {
+        Cow::Owned(reverse(self))
+    }
+
+    /// The reverse graph the backward half of a bidirectional search walks.
+    /// Defaults to `rev_adj_list`; graphs that store their reverse in another
+    /// layout, like `MappedGraph`, lend it here instead.
+    fn reversed(&self) -> Reversed<'_> {
+        Reversed::Lists(self.rev_adj_list())
+    }
+}
+
+/// Compressed sparse rows borrowed from flat arrays, such as the sections of
+/// a mapped graph file: the edges of `u` are `targets[offsets[u]..offsets[u + 1]]`
+/// with the matching `weights`.
+#[derive(Clone, Copy, Debug)]
+pub struct CsrSlices<'a> {
+    pub offsets: &'a [u64],
+    pub targets: &'a [u32],
+    pub weights: &'a [u64],
+}
+
+impl<'a> CsrSlices<'a> {
+    /// `neighbors`, borrowing the arrays rather than `self`.
+    pub fn edges(self, node: usize) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let edges = self.offsets[node] as usize..self.offsets[node + 1] as usize;
+        self.targets[edges.clone()].iter().zip(&self.weights[edges])
+            .map(|(&v, &weight)| (v as usize, weight as usize))
+    }
+}
+
+impl Neighbors for CsrSlices<'_> {
+    fn num_nodes(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.edges(node)
+    }
+
+    fn degree(&self, node: usize) -> usize {
+        (self.offsets[node + 1] - self.offsets[node]) as usize
+    }
+}
+
+/// Reverse adjacency as returned by `Adjacency::reversed`.
+#[derive(Clone, Debug)]
+pub enum Reversed<'a> {
+    Lists(Cow<'a, [Vec<(usize, usize)>]>),
+    Csr(CsrSlices<'a>),
+}
+
+impl Neighbors for Reversed<'_> {
+    fn num_nodes(&self) -> usize {
+        match self {
+            Reversed::Lists(lists) => lists.len(),
+            Reversed::Csr(csr) => csr.num_nodes(),
         }
     }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        // One of the two halves is always empty; chaining them gives a single
+        // iterator type for both layouts.
+        let (lists, csr) = match self {
+            Reversed::Lists(lists) => (lists[node].as_slice(), None),
+            Reversed::Csr(csr) => (&[][..], Some(csr.edges(node))),
+        };
+        lists.iter().copied().chain(csr.into_iter().flatten())
+    }
+
+    fn degree(&self, node: usize) -> usize {
+        match self {
+            Reversed::Lists(lists) => lists[node].len(),
+            Reversed::Csr(csr) => csr.degree(node),
+        }
+    }
+}
+
+/// Reverse adjacency lists of any graph.
+pub fn reverse<G: Neighbors + ?Sized>(graph: &G) -> Vec<Vec<(usize, usize)>> {
+    let mut rev_adj_list = vec![Vec::new(); graph.num_nodes()];
+    for node in 0..graph.num_nodes() {
+        for (neighbor, weight) in graph.neighbors(node) {
+            rev_adj_list[neighbor].push((node, weight));
+        }
+    }
+    rev_adj_list
+}
+
+impl Adjacency for [Vec<(usize, usize)>] {}
+
+impl Adjacency for Vec<Vec<(usize, usize)>> {}
+
+impl Adjacency for Graph {
+    fn rev_adj_list(&self) -> Cow<'_, [Vec<(usize, usize)>]> {
+        Cow::Borrowed(&self.rev_adj_list)
+    }
+}
+
+impl Adjacency for UndirectedGraph {
+    fn rev_adj_list(&self) -> Cow<'_, [Vec<(usize, usize)>]> {
+        Cow::Borrowed(&self.adj_list)
+    }
 }
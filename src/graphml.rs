@@ -0,0 +1,260 @@
+//! GraphML import and export, as used by Gephi, yEd and networkx.
+//!
+//! Edge weights live in a `<data>` element whose `<key>` has
+//! `attr.name="weight"`; node positions in keys named `x` and `y`. Undirected
+//! graphs (or edges with `directed="false"`) become two directed edges.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufWriter, Write};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use crate::edge_list::IdMap;
+use crate::graph::Graph;
+use crate::GraphBuilder;
+
+/// Reads the first `<graph>` of a GraphML document. Edges without a weight
+/// get the key's `<default>`, or 1.
+pub fn read_graphml<R: BufRead>(reader: R) -> io::Result<(Graph, IdMap)> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut parser = Parser::default();
+    loop {
+        match reader.read_event_into(&mut buf).map_err(xml_error)? {
+            Event::Start(element) => parser.start(&element)?,
+            Event::Empty(element) => {
+                parser.start(&element)?;
+                parser.end(element.name().as_ref());
+            }
+            Event::Text(text) => parser.text(text.decode().map_err(xml_error)?.trim())?,
+            Event::End(element) if element.name().as_ref() == b"graph" => break,
+            Event::End(element) => parser.end(element.name().as_ref()),
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    parser.finish()
+}
+
+#[derive(Default)]
+struct Parser {
+    labels: IdMap,
+    edges: Vec<(usize, usize, bool, Option<usize>)>,
+    positions: HashMap<usize, (Option<f64>, Option<f64>)>,
+    // Key ids of the weight attribute and of the x (true) and y (false) attributes.
+    weight_key: Option<String>,
+    position_keys: HashMap<String, bool>,
+    default_weight: Option<usize>,
+    undirected: bool,
+    // The element the next text event belongs to.
+    current_key: Option<String>,
+    in_default: bool,
+    current_node: Option<usize>,
+    current_edge: Option<(usize, usize, bool, Option<usize>)>,
+    data_key: Option<String>,
+}
+
+impl Parser {
+    fn start(&mut self, element: &BytesStart) -> io::Result<()> {
+        let attributes = attributes(element)?;
+        let get = |key: &str| attributes.get(key).ok_or_else(|| invalid(&format!("<{}> without `{}`", String::from_utf8_lossy(element.name().as_ref()), key)));
+        match element.name().as_ref() {
+            b"key" => {
+                let id = get("id")?.clone();
+                match attributes.get("attr.name").map(String::as_str) {
+                    Some("weight") if attributes.get("for").is_none_or(|f| f == "edge" || f == "all") => {
+                        self.weight_key = Some(id.clone());
+                    }
+                    Some(axis @ ("x" | "y")) => {
+                        self.position_keys.insert(id.clone(), axis == "x");
+                    }
+                    _ => {}
+                }
+                self.current_key = Some(id);
+            }
+            b"default" => self.in_default = self.current_key.is_some() && self.current_key == self.weight_key,
+            b"graph" => self.undirected = attributes.get("edgedefault").is_some_and(|d| d == "undirected"),
+            b"node" => self.current_node = Some(self.labels.get_or_insert(get("id")?)),
+            b"edge" => {
+                let source = self.labels.get_or_insert(get("source")?);
+                let target = self.labels.get_or_insert(get("target")?);
+                let directed = attributes.get("directed").map_or(!self.undirected, |d| d == "true");
+                self.current_edge = Some((source, target, directed, None));
+            }
+            b"data" => self.data_key = Some(get("key")?.clone()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str) -> io::Result<()> {
+        if self.in_default {
+            self.default_weight = Some(parse_weight(text)?);
+        } else if let Some(key) = &self.data_key {
+            if Some(key) == self.weight_key.as_ref() {
+                if let Some(edge) = &mut self.current_edge {
+                    edge.3 = Some(parse_weight(text)?);
+                }
+            } else if let (Some(&is_x), Some(node)) = (self.position_keys.get(key), self.current_node) {
+                let value = text.parse().map_err(|_| invalid(&format!("invalid coordinate `{}`", text)))?;
+                let position = self.positions.entry(node).or_default();
+                if is_x {
+                    position.0 = Some(value);
+                } else {
+                    position.1 = Some(value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn end(&mut self, name: &[u8]) {
+        match name {
+            b"key" => self.current_key = None,
+            b"default" => self.in_default = false,
+            b"node" => self.current_node = None,
+            b"data" => self.data_key = None,
+            b"edge" => self.edges.extend(self.current_edge.take()),
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> io::Result<(Graph, IdMap)> {
+        let num_nodes = self.labels.len();
+        let default_weight = self.default_weight.unwrap_or(1);
+        let edges = self.edges.into_iter().flat_map(|(u, v, directed, weight)| {
+            let weight = weight.unwrap_or(default_weight);
+            let back = (!directed && u != v).then_some((v, u, weight));
+            std::iter::once((u, v, weight)).chain(back)
+        });
+        let graph = GraphBuilder::new(num_nodes).edges(edges).build()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let coordinates: Option<Vec<(f64, f64)>> = (0..num_nodes)
+            .map(|u| match self.positions.get(&u) {
+                Some(&(Some(x), Some(y))) => Some((x, y)),
+                _ => None,
+            })
+            .collect();
+        match coordinates {
+            Some(coordinates) if num_nodes > 0 => Ok((graph.with_coordinates(coordinates), self.labels)),
+            _ => Ok((graph, self.labels)),
+        }
+    }
+}
+
+/// Writes a directed GraphML document with an integer `weight` edge key, and
+/// `x`/`y` node keys when the graph has coordinates.
+pub fn write_graphml<W: Write>(graph: &Graph, labels: Option<&IdMap>, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(writer, r#"  <key id="weight" for="edge" attr.name="weight" attr.type="long"/>"#)?;
+    if graph.coordinates.is_some() {
+        writeln!(writer, r#"  <key id="x" for="node" attr.name="x" attr.type="double"/>"#)?;
+        writeln!(writer, r#"  <key id="y" for="node" attr.name="y" attr.type="double"/>"#)?;
+    }
+    writeln!(writer, r#"  <graph id="G" edgedefault="directed">"#)?;
+
+    let name = |u: usize| match labels {
+        Some(labels) => escape(labels.label(u)),
+        None => format!("n{}", u),
+    };
+    for u in 0..graph.num_nodes() {
+        match &graph.coordinates {
+            Some(coordinates) => {
+                let (x, y) = coordinates[u];
+                writeln!(writer, r#"    <node id="{}"><data key="x">{}</data><data key="y">{}</data></node>"#, name(u), x, y)?;
+            }
+            None => writeln!(writer, r#"    <node id="{}"/>"#, name(u))?,
+        }
+    }
+    for (u, edges) in graph.adj_list.iter().enumerate() {
+        for &(v, weight) in edges {
+            writeln!(writer, r#"    <edge source="{}" target="{}"><data key="weight">{}</data></edge>"#, name(u), name(v), weight)?;
+        }
+    }
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")?;
+    writer.flush()
+}
+
+fn attributes(element: &BytesStart) -> io::Result<HashMap<String, String>> {
+    element.attributes().map(|attribute| {
+        let attribute = attribute.map_err(xml_error)?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        Ok((key, attribute.unescape_value().map_err(xml_error)?.into_owned()))
+    }).collect()
+}
+
+/// Weights may be written as floats by other tools; integral values are accepted.
+fn parse_weight(text: &str) -> io::Result<usize> {
+    text.parse().or_else(|_| match text.parse::<f64>() {
+        Ok(value) if value >= 0.0 && value.fract() == 0.0 => Ok(value as usize),
+        _ => Err(invalid(&format!("invalid weight `{}`", text))),
+    })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn xml_error<E: std::error::Error + Send + Sync + 'static>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_dijkstra::sequential_dijkstra;
+
+    #[test]
+    fn test_read_undirected_with_defaults() {
+        let input = r#"<?xml version="1.0"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="edge" attr.name="weight" attr.type="double"><default>5</default></key>
+  <key id="d1" for="node" attr.name="label" attr.type="string"/>
+  <graph id="G" edgedefault="undirected">
+    <node id="a"><data key="d1">Start</data></node>
+    <node id="b"/>
+    <node id="c"/>
+    <edge source="a" target="b"><data key="d0">2.0</data></edge>
+    <edge source="b" target="c"/>
+    <edge source="a" target="c" directed="true"><data key="d0">9</data></edge>
+  </graph>
+</graphml>"#;
+        let (graph, labels) = read_graphml(input.as_bytes()).unwrap();
+        let (a, c) = (labels.id("a").unwrap(), labels.id("c").unwrap());
+        assert_eq!(graph.num_edges(), 5);
+        assert_eq!(sequential_dijkstra(&graph.adj_list, a, c), (7, vec![0, 1, 2]));
+        assert_eq!(labels.labels(&sequential_dijkstra(&graph.adj_list, c, a).1), vec!["c", "b", "a"]);
+        assert!(graph.coordinates.is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let graph = Graph::new(vec![vec![(1, 3), (2, 8)], vec![(2, 4)], vec![]])
+            .with_coordinates(vec![(0.0, 0.0), (1.5, 2.0), (3.0, -1.0)]);
+        let mut labels = IdMap::new();
+        for label in ["Harbour & Co", "Market", "Station"] {
+            labels.get_or_insert(label);
+        }
+
+        let mut out = Vec::new();
+        write_graphml(&graph, Some(&labels), &mut out).unwrap();
+        let (read, read_labels) = read_graphml(out.as_slice()).unwrap();
+        assert_eq!(read.adj_list, graph.adj_list);
+        assert_eq!(read.coordinates, graph.coordinates);
+        assert_eq!(read_labels.label(0), "Harbour & Co");
+
+        let mut out = Vec::new();
+        write_graphml(&Graph::new(graph.adj_list.clone()), None, &mut out).unwrap();
+        let (read, read_labels) = read_graphml(out.as_slice()).unwrap();
+        assert_eq!(read.adj_list, graph.adj_list);
+        assert_eq!(read_labels.label(2), "n2");
+    }
+}
@@ -0,0 +1,707 @@
+//! Benchmark harness: scenarios from a config file, repeated timed runs of
+//! each algorithm and CSV / JSON / Markdown reports.
+//!
+//! A config file is a list of `key = value` lines. Lines before the first
+//! `[name]` header are defaults for every scenario; each header starts a
+//! scenario that may override them:
+//!
+//! ```text
+//! runs = 5
+//! warmup = 1
+//! queries = 20
+//! seed = 42
+//! algorithms = sequential, bidirectional, parallel, parallel-bidirectional, dial
+//! queue = binary-heap
+//!
+//! [road-town]
+//! graph = road 100 100
+//!
+//! [dimacs-ny]
+//! graph = file USA-road-d.NY.gr
+//! queries = 100
+//! ```
+//!
+//! Graphs are `road <rows> <cols>`, `grid <rows> <cols>`, `gnm <nodes> <edges>`,
+//! `gnp <nodes> <p>`, `rmat <scale> <edges>`, `ba <nodes> <links>`,
+//! `geometric <nodes> <radius>`, `complete <nodes>` or `file <path>`
+//! (`.gr`, `.csr` or a whitespace edge list). `queue` names the priority queue
+//! the searches run on (see `QueueKind`); repeat a scenario with another queue
+//! to compare them.
+
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::algorithm::Algorithm;
+use crate::binary::MappedGraph;
+use crate::dimacs::read_gr;
+use crate::edge_list::{read_edges_file, CsvOptions};
+use crate::generators::{self, RoadOptions, Weights, RMAT_GRAPH500};
+use crate::graph::{Adjacency, Graph};
+use crate::parallel_config::ParallelConfig;
+use crate::priority_queue::QueueKind;
+use crate::search_stats::SearchStats;
+use crate::selection::GraphProfile;
+use crate::{dial_dijkstra_with_max_weight, sequential_dijkstra};
+use crate::verify::verify_path;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphSpec {
+    Road { rows: usize, cols: usize },
+    Grid { rows: usize, cols: usize },
+    Gnm { nodes: usize, edges: usize },
+    Gnp { nodes: usize, p: f64 },
+    Rmat { scale: u32, edges: usize },
+    BarabasiAlbert { nodes: usize, links: usize },
+    Geometric { nodes: usize, radius: f64 },
+    /// Every ordered pair joined with weight `(u + v) % 10 + 1`.
+    Complete { nodes: usize },
+    File(PathBuf),
+}
+
+impl GraphSpec {
+    /// Generated graphs use random weights in `1..=99` (Euclidean for road
+    /// and geometric graphs).
+    pub fn build(&self, seed: u64) -> io::Result<Graph> {
+        let weights = Weights::Uniform(1, 99);
+        Ok(match *self {
+            GraphSpec::Road { rows, cols } => generators::road_network(&RoadOptions::new(rows, cols), seed),
+            GraphSpec::Grid { rows, cols } => generators::grid(rows, cols, weights, seed),
+            GraphSpec::Gnm { nodes, edges } => generators::gnm(nodes, edges, weights, seed),
+            GraphSpec::Gnp { nodes, p } => generators::gnp(nodes, p, weights, seed),
+            GraphSpec::Rmat { scale, edges } => generators::rmat(scale, edges, RMAT_GRAPH500, weights, seed),
+            GraphSpec::BarabasiAlbert { nodes, links } => generators::barabasi_albert(nodes, links, weights, seed),
+            GraphSpec::Geometric { nodes, radius } => generators::random_geometric(nodes, radius, Weights::Euclidean(10_000.0), seed),
+            GraphSpec::Complete { nodes } => Graph::new((0..nodes).map(|i| {
+                (0..nodes).filter(|&j| j != i).map(|j| (j, (i + j) % 10 + 1)).collect()
+            }).collect()),
+            GraphSpec::File(ref path) => match path.extension().and_then(|ext| ext.to_str()) {
+                Some("gr") => read_gr(BufReader::new(File::open(path)?))?,
+                Some("csr") | Some("bin") => MappedGraph::open(path)?.to_graph(),
+                _ => read_edges_file(path, &CsvOptions::edge_list())?.0,
+            },
+        })
+    }
+    /// A `.csr` / `.bin` file mapped rather than copied by `build`, so the
+    /// runs search it in place; `None` for every other graph.
+    pub fn map(&self) -> io::Result<Option<MappedGraph>> {
+        match self {
+            GraphSpec::File(path) if matches!(path.extension().and_then(|ext| ext.to_str()), Some("csr") | Some("bin")) => {
+                MappedGraph::open(path).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The same kind of graph with about `factor` times the nodes and edges,
+    /// for weak scaling. Files cannot be scaled.
+    pub fn scaled(&self, factor: usize) -> Option<GraphSpec> {
+        let root = (factor as f64).sqrt();
+        Some(match *self {
+            GraphSpec::Road { rows, cols } => GraphSpec::Road { rows: rows * factor, cols },
+            GraphSpec::Grid { rows, cols } => GraphSpec::Grid { rows: rows * factor, cols },
+            GraphSpec::Gnm { nodes, edges } => GraphSpec::Gnm { nodes: nodes * factor, edges: edges * factor },
+            GraphSpec::Gnp { nodes, p } => GraphSpec::Gnp { nodes: nodes * factor, p: p / factor as f64 },
+            GraphSpec::Rmat { scale, edges } => GraphSpec::Rmat { scale: scale + factor.ilog2(), edges: edges * factor },
+            GraphSpec::BarabasiAlbert { nodes, links } => GraphSpec::BarabasiAlbert { nodes: nodes * factor, links },
+            GraphSpec::Geometric { nodes, radius } => GraphSpec::Geometric { nodes: nodes * factor, radius: radius / root },
+            // Edges grow with the square of the node count.
+            GraphSpec::Complete { nodes } => GraphSpec::Complete { nodes: (nodes as f64 * root).round() as usize },
+            GraphSpec::File(_) => return None,
+        })
+    }
+}
+
+impl FromStr for GraphSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let arity = |n: usize| {
+            if parts.len() == n + 1 { Ok(()) } else { Err(format!("`{}` takes {} argument(s)", parts[0], n)) }
+        };
+        let number = |i: usize| parts[i].parse::<usize>().map_err(|_| format!("invalid number `{}`", parts[i]));
+        let float = |i: usize| parts[i].parse::<f64>().map_err(|_| format!("invalid number `{}`", parts[i]));
+        match parts.first().copied() {
+            Some("road") => arity(2).and_then(|_| Ok(GraphSpec::Road { rows: number(1)?, cols: number(2)? })),
+            Some("grid") => arity(2).and_then(|_| Ok(GraphSpec::Grid { rows: number(1)?, cols: number(2)? })),
+            Some("gnm") => arity(2).and_then(|_| Ok(GraphSpec::Gnm { nodes: number(1)?, edges: number(2)? })),
+            Some("gnp") => arity(2).and_then(|_| Ok(GraphSpec::Gnp { nodes: number(1)?, p: float(2)? })),
+            Some("rmat") => arity(2).and_then(|_| Ok(GraphSpec::Rmat { scale: number(1)? as u32, edges: number(2)? })),
+            Some("ba") => arity(2).and_then(|_| Ok(GraphSpec::BarabasiAlbert { nodes: number(1)?, links: number(2)? })),
+            Some("geometric") => arity(2).and_then(|_| Ok(GraphSpec::Geometric { nodes: number(1)?, radius: float(2)? })),
+            Some("complete") => arity(1).and_then(|_| Ok(GraphSpec::Complete { nodes: number(1)? })),
+            Some("file") => arity(1).map(|_| GraphSpec::File(PathBuf::from(parts[1]))),
+            Some(other) => Err(format!("unknown graph kind `{}`", other)),
+            None => Err("empty graph".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    pub graph: GraphSpec,
+    /// Random start/goal pairs, answered once per run.
+    pub queries: usize,
+    pub runs: usize,
+    pub warmup: usize,
+    pub algorithms: Vec<Algorithm>,
+    pub queue: QueueKind,
+    pub seed: u64,
+}
+
+pub fn parse_config(text: &str) -> Result<Vec<Scenario>, String> {
+    let mut defaults = Scenario {
+        name: String::new(),
+        graph: GraphSpec::Complete { nodes: 0 },
+        queries: 10,
+        runs: 5,
+        warmup: 1,
+        algorithms: Algorithm::ALL.to_vec(),
+        queue: QueueKind::default(),
+        seed: 42,
+    };
+    // (scenario, line of its header, whether it set a graph)
+    let mut scenarios: Vec<(Scenario, usize, bool)> = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            let scenario = Scenario { name: name.trim().to_string(), ..defaults.clone() };
+            scenarios.push((scenario, line_no, false));
+            continue;
+        }
+        let (key, value) = line.split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or_else(|| format!("line {}: expected `key = value`", line_no))?;
+        let error = |message: String| format!("line {}: {}", line_no, message);
+        let number = || value.parse::<usize>().map_err(|_| error(format!("invalid number `{}`", value)));
+
+        let (target, has_graph) = match scenarios.last_mut() {
+            Some((scenario, _, has_graph)) => (scenario, Some(has_graph)),
+            None => (&mut defaults, None),
+        };
+        match key {
+            "graph" => match has_graph {
+                Some(has_graph) => {
+                    target.graph = value.parse().map_err(error)?;
+                    *has_graph = true;
+                }
+                None => return Err(error("`graph` belongs in a scenario".to_string())),
+            },
+            "queries" => target.queries = number()?,
+            "runs" => target.runs = number()?.max(1),
+            "warmup" => target.warmup = number()?,
+            "seed" => target.seed = number()? as u64,
+            "algorithms" => {
+                target.algorithms = value.split(',')
+                    .map(|name| name.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(error)?;
+            }
+            "queue" => target.queue = value.parse().map_err(error)?,
+            other => return Err(error(format!("unknown key `{}`", other))),
+        }
+    }
+
+    scenarios.into_iter().map(|(scenario, line_no, has_graph)| {
+        if has_graph { Ok(scenario) } else { Err(format!("line {}: scenario `{}` has no graph", line_no, scenario.name)) }
+    }).collect()
+}
+
+pub fn load_config<P: AsRef<Path>>(path: P) -> io::Result<Vec<Scenario>> {
+    parse_config(&std::fs::read_to_string(path)?)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// One algorithm on one scenario. Times are per query: each run's total
+/// divided by the number of queries.
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    pub scenario: String,
+    pub algorithm: Algorithm,
+    pub nodes: usize,
+    pub edges: usize,
+    pub queries: usize,
+    pub runs: usize,
+    pub median: Duration,
+    pub p95: Duration,
+    pub min: Duration,
+    /// Average settled nodes per query.
+    pub settled: f64,
+    /// Median time of `sequential` over this algorithm's, if the scenario runs both.
+    pub speedup: Option<f64>,
+    /// Queries whose cost differs from `sequential_dijkstra` or whose path is invalid.
+    pub errors: usize,
+}
+
+pub fn random_queries(num_nodes: usize, count: usize, seed: u64) -> Vec<(usize, usize)> {
+    if num_nodes == 0 {
+        return Vec::new();
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count).map(|_| (rng.gen_range(0..num_nodes), rng.gen_range(0..num_nodes))).collect()
+}
+
+pub fn run_scenario(scenario: &Scenario) -> io::Result<Vec<Measurement>> {
+    Ok(match scenario.graph.map()? {
+        Some(mapped) => measure_scenario(scenario, &mapped),
+        None => measure_scenario(scenario, &scenario.graph.build(scenario.seed)?),
+    })
+}
+
+fn measure_scenario<G: Adjacency + Sync + ?Sized>(scenario: &Scenario, graph: &G) -> Vec<Measurement> {
+    let queries = random_queries(graph.num_nodes(), scenario.queries, scenario.seed);
+    let expected: Vec<usize> = queries.iter().map(|&(start, goal)| sequential_dijkstra(graph, start, goal).0).collect();
+    // Found once, so that Dial's algorithm is timed without its scan over every edge
+    let max_weight = GraphProfile::of(graph).weight_range.map_or(0, |(_, max)| max);
+    let run = |algorithm: Algorithm, graph: &G, start, goal| match algorithm {
+        Algorithm::Dial => dial_dijkstra_with_max_weight(graph, max_weight, start, goal),
+        algorithm => algorithm.run_with_queue(graph, scenario.queue, start, goal),
+    };
+    measure(&scenario.name, graph, &queries, &expected, &scenario.algorithms, run, scenario.warmup, scenario.runs)
+}
+
+/// `run` answers one query with one algorithm in the timed runs.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn measure<G, R>(name: &str, graph: &G, queries: &[(usize, usize)], expected: &[usize], algorithms: &[Algorithm], run: R, warmup: usize, runs: usize) -> Vec<Measurement>
+where
+    G: Adjacency + Sync + ?Sized,
+    R: Fn(Algorithm, &G, usize, usize) -> (usize, Vec<usize>),
+{
+    let edges = (0..graph.num_nodes()).map(|u| graph.degree(u)).sum();
+    let mut measurements: Vec<Measurement> = algorithms.iter().map(|&algorithm| {
+        // One counted pass for the settled nodes and the correctness check.
+        let stats = SearchStats::new();
+        let errors = queries.iter().zip(expected).filter(|&(&(start, goal), &expected)| {
+            let (cost, path) = algorithm.run_with_stats(graph, &stats, start, goal);
+            cost != expected || verify_path(graph, start, goal, cost, &path).is_err()
+        }).count();
+
+        for _ in 0..warmup {
+            run_queries(graph, queries, algorithm, &run);
+        }
+        let mut times: Vec<Duration> = (0..runs).map(|_| {
+            let start_time = Instant::now();
+            run_queries(graph, queries, algorithm, &run);
+            start_time.elapsed() / queries.len().max(1) as u32
+        }).collect();
+        times.sort();
+
+        Measurement {
+            scenario: name.to_string(),
+            algorithm,
+            nodes: graph.num_nodes(),
+            edges,
+            queries: queries.len(),
+            runs,
+            median: times[times.len() / 2],
+            p95: times[(times.len() * 95).div_ceil(100).max(1) - 1],
+            min: times[0],
+            settled: stats.settled() as f64 / queries.len().max(1) as f64,
+            speedup: None,
+            errors,
+        }
+    }).collect();
+
+    if let Some(sequential) = measurements.iter().find(|m| m.algorithm == Algorithm::Sequential).map(|m| m.median) {
+        for measurement in &mut measurements {
+            measurement.speedup = Some(sequential.as_secs_f64() / measurement.median.as_secs_f64().max(1e-12));
+        }
+    }
+    measurements
+}
+
+fn run_queries<G: ?Sized, R: Fn(Algorithm, &G, usize, usize) -> (usize, Vec<usize>)>(graph: &G, queries: &[(usize, usize)], algorithm: Algorithm, run: &R) {
+    for &(start, goal) in queries {
+        std::hint::black_box(run(algorithm, graph, start, goal));
+    }
+}
+
+const COLUMNS: [&str; 12] = ["scenario", "algorithm", "nodes", "edges", "queries", "runs", "median_ms", "p95_ms", "min_ms", "settled", "speedup", "errors"];
+
+fn millis(duration: Duration) -> String {
+    format!("{:.4}", duration.as_secs_f64() * 1000.0)
+}
+
+fn speedup(measurement: &Measurement) -> String {
+    measurement.speedup.map_or(String::new(), |speedup| format!("{:.2}", speedup))
+}
+
+/// Quotes `field` if it holds a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) { format!("\"{}\"", field.replace('"', "\"\"")) } else { field.to_string() }
+}
+
+pub fn write_csv<W: Write>(measurements: &[Measurement], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "{}", COLUMNS.join(","))?;
+    for m in measurements {
+        writeln!(writer, "{},{},{},{},{},{},{},{},{},{:.1},{},{}",
+            csv_field(&m.scenario), m.algorithm, m.nodes, m.edges, m.queries, m.runs,
+            millis(m.median), millis(m.p95), millis(m.min), m.settled, speedup(m), m.errors)?;
+    }
+    Ok(())
+}
+
+pub fn write_json<W: Write>(measurements: &[Measurement], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "[")?;
+    for (i, m) in measurements.iter().enumerate() {
+        let separator = if i + 1 < measurements.len() { "," } else { "" };
+        writeln!(writer, "  {{\"scenario\": {}, \"algorithm\": \"{}\", \"nodes\": {}, \"edges\": {}, \"queries\": {}, \"runs\": {}, \"median_ms\": {}, \"p95_ms\": {}, \"min_ms\": {}, \"settled\": {:.1}, \"speedup\": {}, \"errors\": {}}}{}",
+            json_string(&m.scenario), m.algorithm, m.nodes, m.edges, m.queries, m.runs,
+            millis(m.median), millis(m.p95), millis(m.min), m.settled,
+            m.speedup.map_or("null".to_string(), |speedup| format!("{:.2}", speedup)), m.errors, separator)?;
+    }
+    writeln!(writer, "]")
+}
+
+pub fn write_markdown<W: Write>(measurements: &[Measurement], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "| Scenario | Algorithm | Nodes | Edges | Median (ms) | p95 (ms) | Min (ms) | Settled | Speedup | Errors |")?;
+    writeln!(writer, "|---|---|---:|---:|---:|---:|---:|---:|---:|---:|")?;
+    for m in measurements {
+        writeln!(writer, "| {} | {} | {} | {} | {} | {} | {} | {:.0} | {} | {} |",
+            m.scenario.replace('|', "\\|"), m.algorithm, m.nodes, m.edges,
+            millis(m.median), millis(m.p95), millis(m.min), m.settled, speedup(m), m.errors)?;
+    }
+    Ok(())
+}
+
+/// Writes `results.csv`, `results.json` and `results.md` into `dir`.
+pub fn write_reports<P: AsRef<Path>>(measurements: &[Measurement], dir: P) -> io::Result<()> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    write_csv(measurements, io::BufWriter::new(File::create(dir.join("results.csv"))?))?;
+    write_json(measurements, io::BufWriter::new(File::create(dir.join("results.json"))?))?;
+    write_markdown(measurements, io::BufWriter::new(File::create(dir.join("results.md"))?))
+}
+
+/// One parallel algorithm at one thread count. Strong scaling keeps the
+/// scenario's graph; weak scaling grows it with the thread count (see
+/// `GraphSpec::scaled`). Efficiencies are relative to one thread: `T1 / (p * Tp)`
+/// for strong and `T1 / Tp` for weak scaling.
+#[derive(Clone, Debug)]
+pub struct ScalingPoint {
+    pub scenario: String,
+    pub algorithm: Algorithm,
+    pub threads: usize,
+    pub strong: Duration,
+    pub strong_speedup: f64,
+    pub strong_efficiency: f64,
+    /// `None` when the graph cannot be scaled.
+    pub weak_nodes: Option<usize>,
+    pub weak: Option<Duration>,
+    pub weak_efficiency: Option<f64>,
+}
+
+/// Runs the parallel search on dedicated pools of `1..=max_threads` threads,
+/// starting one task per thread. The parallel bidirectional search is left
+/// out whatever the scenario lists: it always runs two tasks, one per
+/// direction, so it has no curve to show.
+pub fn run_scaling(scenario: &Scenario, max_threads: usize) -> io::Result<Vec<ScalingPoint>> {
+    let algorithms = [Algorithm::Parallel];
+    match scenario.graph.map()? {
+        Some(mapped) => scaling_points(scenario, &algorithms, &mapped, max_threads),
+        None => scaling_points(scenario, &algorithms, &scenario.graph.build(scenario.seed)?, max_threads),
+    }
+}
+
+/// The median time per query of each of `algorithms` on a pool of `threads`.
+fn scaling_medians<G: Adjacency + Sync + ?Sized>(scenario: &Scenario, algorithms: &[Algorithm], graph: &G, threads: usize) -> io::Result<Vec<Duration>> {
+    let queries = random_queries(graph.num_nodes(), scenario.queries, scenario.seed);
+    let expected: Vec<usize> = queries.iter().map(|&(start, goal)| sequential_dijkstra(graph, start, goal).0).collect();
+    let config = ParallelConfig::new().dedicated_pool(threads).map_err(io::Error::other)?;
+    let run = |algorithm: Algorithm, graph: &G, start, goal| algorithm.run_with_config(graph, &config, start, goal);
+    Ok(config.install(|| measure(&scenario.name, graph, &queries, &expected, algorithms, run, scenario.warmup, scenario.runs))
+        .into_iter().map(|m| m.median).collect())
+}
+
+fn scaling_points<G: Adjacency + Sync + ?Sized>(scenario: &Scenario, algorithms: &[Algorithm], graph: &G, max_threads: usize) -> io::Result<Vec<ScalingPoint>> {
+    let mut points = Vec::new();
+    let (mut strong_base, mut weak_base) = (Vec::new(), Vec::new());
+    for threads in 1..=max_threads.max(1) {
+        let strong = scaling_medians(scenario, algorithms, graph, threads)?;
+        let weak = match scenario.graph.scaled(threads) {
+            Some(spec) => {
+                let weak_graph = spec.build(scenario.seed)?;
+                Some((weak_graph.num_nodes(), scaling_medians(scenario, algorithms, &weak_graph, threads)?))
+            }
+            None => None,
+        };
+        if threads == 1 {
+            strong_base = strong.clone();
+            weak_base = weak.as_ref().map_or(Vec::new(), |(_, times)| times.clone());
+        }
+
+        for (i, &algorithm) in algorithms.iter().enumerate() {
+            let ratio = |base: Duration, time: Duration| base.as_secs_f64() / time.as_secs_f64().max(1e-12);
+            let strong_speedup = ratio(strong_base[i], strong[i]);
+            points.push(ScalingPoint {
+                scenario: scenario.name.clone(),
+                algorithm,
+                threads,
+                strong: strong[i],
+                strong_speedup,
+                strong_efficiency: strong_speedup / threads as f64,
+                weak_nodes: weak.as_ref().map(|(nodes, _)| *nodes),
+                weak: weak.as_ref().map(|(_, times)| times[i]),
+                weak_efficiency: weak.as_ref().map(|(_, times)| ratio(weak_base[i], times[i])),
+            });
+        }
+    }
+    Ok(points)
+}
+
+const SCALING_COLUMNS: [&str; 9] = ["scenario", "algorithm", "threads", "strong_ms", "strong_speedup", "strong_efficiency", "weak_nodes", "weak_ms", "weak_efficiency"];
+
+fn optional<T>(value: Option<T>, format: impl Fn(T) -> String, missing: &str) -> String {
+    value.map_or(missing.to_string(), format)
+}
+
+pub fn write_scaling_csv<W: Write>(points: &[ScalingPoint], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "{}", SCALING_COLUMNS.join(","))?;
+    for p in points {
+        writeln!(writer, "{},{},{},{},{:.3},{:.3},{},{},{}",
+            csv_field(&p.scenario), p.algorithm, p.threads, millis(p.strong), p.strong_speedup, p.strong_efficiency,
+            optional(p.weak_nodes, |n| n.to_string(), ""), optional(p.weak, millis, ""),
+            optional(p.weak_efficiency, |e| format!("{:.3}", e), ""))?;
+    }
+    Ok(())
+}
+
+pub fn write_scaling_json<W: Write>(points: &[ScalingPoint], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "[")?;
+    for (i, p) in points.iter().enumerate() {
+        let separator = if i + 1 < points.len() { "," } else { "" };
+        writeln!(writer, "  {{\"scenario\": {}, \"algorithm\": \"{}\", \"threads\": {}, \"strong_ms\": {}, \"strong_speedup\": {:.3}, \"strong_efficiency\": {:.3}, \"weak_nodes\": {}, \"weak_ms\": {}, \"weak_efficiency\": {}}}{}",
+            json_string(&p.scenario), p.algorithm, p.threads, millis(p.strong), p.strong_speedup, p.strong_efficiency,
+            optional(p.weak_nodes, |n| n.to_string(), "null"), optional(p.weak, millis, "null"),
+            optional(p.weak_efficiency, |e| format!("{:.3}", e), "null"), separator)?;
+    }
+    writeln!(writer, "]")
+}
+
+/// One table per scenario and algorithm, with a bar per thread count showing
+/// the strong-scaling efficiency.
+pub fn write_scaling_markdown<W: Write>(points: &[ScalingPoint], mut writer: W) -> io::Result<()> {
+    let mut groups: Vec<(&str, Algorithm)> = Vec::new();
+    for p in points {
+        if !groups.contains(&(p.scenario.as_str(), p.algorithm)) {
+            groups.push((p.scenario.as_str(), p.algorithm));
+        }
+    }
+    for (index, &(scenario, algorithm)) in groups.iter().enumerate() {
+        if index > 0 {
+            writeln!(writer)?;
+        }
+        writeln!(writer, "### {} / {}", scenario, algorithm)?;
+        writeln!(writer)?;
+        writeln!(writer, "| Threads | Strong (ms) | Speedup | Strong efficiency | | Weak nodes | Weak (ms) | Weak efficiency |")?;
+        writeln!(writer, "|---:|---:|---:|---:|---|---:|---:|---:|")?;
+        for p in points.iter().filter(|p| p.scenario == scenario && p.algorithm == algorithm) {
+            let bar = "#".repeat((p.strong_efficiency * 20.0).round().clamp(0.0, 40.0) as usize);
+            writeln!(writer, "| {} | {} | {:.2} | {:.2} | `{}` | {} | {} | {} |",
+                p.threads, millis(p.strong), p.strong_speedup, p.strong_efficiency, bar,
+                optional(p.weak_nodes, |n| n.to_string(), "-"), optional(p.weak, millis, "-"),
+                optional(p.weak_efficiency, |e| format!("{:.2}", e), "-"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes `scaling.csv`, `scaling.json` and `scaling.md` into `dir`.
+pub fn write_scaling_reports<P: AsRef<Path>>(points: &[ScalingPoint], dir: P) -> io::Result<()> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    write_scaling_csv(points, io::BufWriter::new(File::create(dir.join("scaling.csv"))?))?;
+    write_scaling_json(points, io::BufWriter::new(File::create(dir.join("scaling.json"))?))?;
+    write_scaling_markdown(points, io::BufWriter::new(File::create(dir.join("scaling.md"))?))
+}
+
+/// Quotes `text` as a JSON string; shared with the `dijkstra` binary's JSON output.
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = "\
+# defaults
+runs = 3
+algorithms = sequential, bidirectional
+
+[road]
+graph = road 20 30   # small town
+queries = 4
+
+[dense]
+graph = complete 50
+runs = 0
+algorithms = parallel
+queue = radix
+";
+        let scenarios = parse_config(config).unwrap();
+        assert_eq!(scenarios.len(), 2);
+        assert_eq!(scenarios[0].graph, GraphSpec::Road { rows: 20, cols: 30 });
+        assert_eq!((scenarios[0].queries, scenarios[0].runs, scenarios[0].warmup), (4, 3, 1));
+        assert_eq!(scenarios[0].algorithms, vec![Algorithm::Sequential, Algorithm::Bidirectional]);
+        assert_eq!((scenarios[1].queries, scenarios[1].runs), (10, 1));
+        assert_eq!(scenarios[1].algorithms, vec![Algorithm::Parallel]);
+        assert_eq!((scenarios[0].queue, scenarios[1].queue), (QueueKind::BinaryHeap, QueueKind::Radix));
+
+        let errors = [
+            ("[a]\nqueries = 3\n", "line 1: scenario `a` has no graph"),
+            ("graph = road 2 2\n", "line 1: `graph` belongs in a scenario"),
+            ("[a]\ngraph = road 2\n", "line 2: `road` takes 2 argument(s)"),
+            ("[a]\ngraph = road 2 2\nalgorithms = dfs\n", "line 3: unknown algorithm `dfs`"),
+            ("[a]\nspeed = 3\n", "line 2: unknown key `speed`"),
+            ("[a]\nqueue = fibonacci\n", "line 2: unknown queue `fibonacci`"),
+        ];
+        for (config, message) in errors {
+            let err = parse_config(config).unwrap_err();
+            assert!(err.starts_with(message), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_run_scenario() {
+        let scenario = Scenario {
+            name: "road".to_string(),
+            graph: GraphSpec::Road { rows: 30, cols: 30 },
+            queries: 5,
+            runs: 4,
+            warmup: 1,
+            algorithms: vec![Algorithm::Sequential, Algorithm::Bidirectional, Algorithm::Parallel],
+            queue: QueueKind::Pairing,
+            seed: 3,
+        };
+        let measurements = run_scenario(&scenario).unwrap();
+        assert_eq!(measurements.len(), 3);
+        for m in &measurements {
+            assert_eq!(m.errors, 0, "{}", m.algorithm);
+            assert!(m.min <= m.median && m.median <= m.p95);
+            assert!(m.settled > 0.0);
+        }
+        assert_eq!(measurements[0].speedup, Some(1.0));
+        // Bidirectional search settles fewer nodes on road networks.
+        assert!(measurements[1].settled < measurements[0].settled);
+
+        // Binary files are searched through the mapping.
+        let path = std::env::temp_dir().join(format!("harness_{}_road.csr", std::process::id()));
+        let graph = scenario.graph.build(scenario.seed).unwrap();
+        crate::binary::write_binary_file(&graph, &path).unwrap();
+        let mapped = Scenario { graph: GraphSpec::File(path), ..scenario };
+        assert!(mapped.graph.map().unwrap().is_some());
+        for m in run_scenario(&mapped).unwrap() {
+            assert_eq!((m.errors, m.nodes, m.edges), (0, graph.num_nodes(), graph.num_edges()), "{}", m.algorithm);
+        }
+    }
+
+    #[test]
+    fn test_reports() {
+        let m = Measurement {
+            scenario: "a, b".to_string(),
+            algorithm: Algorithm::Bidirectional,
+            nodes: 10,
+            edges: 20,
+            queries: 2,
+            runs: 3,
+            median: Duration::from_micros(1500),
+            p95: Duration::from_micros(2000),
+            min: Duration::from_micros(1000),
+            settled: 4.5,
+            speedup: None,
+            errors: 0,
+        };
+
+        let mut csv = Vec::new();
+        write_csv(std::slice::from_ref(&m), &mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), format!("{}\n\"a, b\",bidirectional,10,20,2,3,1.5000,2.0000,1.0000,4.5,,0\n", COLUMNS.join(",")));
+
+        let mut json = Vec::new();
+        write_json(std::slice::from_ref(&m), &mut json).unwrap();
+        assert!(String::from_utf8(json).unwrap().contains("{\"scenario\": \"a, b\", \"algorithm\": \"bidirectional\", \"nodes\": 10, \"edges\": 20, \"queries\": 2, \"runs\": 3, \"median_ms\": 1.5000, \"p95_ms\": 2.0000, \"min_ms\": 1.0000, \"settled\": 4.5, \"speedup\": null, \"errors\": 0}\n"));
+
+        let mut markdown = Vec::new();
+        write_markdown(&[m], &mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        assert_eq!(markdown.lines().nth(2).unwrap(), "| a, b | bidirectional | 10 | 20 | 1.5000 | 2.0000 | 1.0000 | 4 |  | 0 |");
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("North \"Gate\"\\"), "\"North \\\"Gate\\\"\\\\\"");
+        assert_eq!(json_string("a\tb\nc\u{1}"), "\"a\\tb\\nc\\u0001\"");
+    }
+
+    #[test]
+    fn test_scaled_specs() {
+        assert_eq!(GraphSpec::Road { rows: 10, cols: 20 }.scaled(3), Some(GraphSpec::Road { rows: 30, cols: 20 }));
+        assert_eq!(GraphSpec::Rmat { scale: 10, edges: 100 }.scaled(4), Some(GraphSpec::Rmat { scale: 12, edges: 400 }));
+        assert_eq!(GraphSpec::Complete { nodes: 100 }.scaled(4), Some(GraphSpec::Complete { nodes: 200 }));
+        assert_eq!(GraphSpec::File(PathBuf::from("a.gr")).scaled(2), None);
+        let gnp = GraphSpec::Gnp { nodes: 1000, p: 0.01 }.scaled(2).unwrap();
+        assert_eq!(gnp, GraphSpec::Gnp { nodes: 2000, p: 0.005 });
+    }
+
+    #[test]
+    fn test_run_scaling() {
+        let scenario = Scenario {
+            name: "grid".to_string(),
+            graph: GraphSpec::Grid { rows: 20, cols: 20 },
+            queries: 3,
+            runs: 2,
+            warmup: 0,
+            algorithms: vec![Algorithm::Sequential, Algorithm::Parallel, Algorithm::ParallelBidirectional],
+            queue: QueueKind::default(),
+            seed: 1,
+        };
+        let points = run_scaling(&scenario, 2).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[0].threads, points[1].threads), (1, 2));
+        assert!(points.iter().all(|p| p.algorithm == Algorithm::Parallel));
+        assert_eq!((points[0].strong_speedup, points[0].strong_efficiency, points[0].weak_efficiency), (1.0, 1.0, Some(1.0)));
+        assert_eq!(points[1].weak_nodes, Some(800));
+        assert!((points[1].strong_efficiency - points[1].strong_speedup / 2.0).abs() < 1e-9);
+
+        let mut markdown = Vec::new();
+        write_scaling_markdown(&points, &mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        assert!(markdown.starts_with("### grid / parallel\n\n| Threads |"));
+        assert!(markdown.contains("| 1 | "));
+        assert!(markdown.contains(" | 1.00 | 1.00 | `####################` | 400 | "));
+
+        let mut points = points;
+        points[0].scenario = "a, \"b\"".to_string();
+        let mut csv = Vec::new();
+        write_scaling_csv(&points, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(1).unwrap().starts_with("\"a, \"\"b\"\"\",parallel,1,"), "{}", csv);
+    }
+}
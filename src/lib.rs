@@ -1,11 +1,56 @@
 pub mod graph;
+pub mod builder;
+pub mod timetable;
+pub mod turn_costs;
+pub mod overlay;
+pub mod search_stats;
+pub mod priority_queue;
+pub mod parallel_config;
 pub mod standard_dijkstra;
 pub mod bidirectional_dijkstra;
 pub mod parallel_dijkstra;
 pub mod parallel_bi_dijkstra;
+pub mod dial;
+pub mod edge_based_dijkstra;
+pub mod csa;
+pub mod raptor;
+pub mod dynamic_sssp;
+pub mod algorithm;
+pub mod selection;
+pub mod verify;
+pub mod query;
+pub mod dimacs;
+pub mod edge_list;
+pub mod binary;
+pub mod osm;
+pub mod graphml;
+pub mod dot;
+pub mod matrix_market;
+pub mod petgraph_interop;
+pub mod generators;
+pub mod harness;
 
-pub use graph::Graph;
-pub use standard_dijkstra::sequential_dijkstra;
-pub use bidirectional_dijkstra::bidirectional_dijkstra;
-pub use parallel_dijkstra::parallel_dijkstra;
-pub use parallel_bi_dijkstra::parallel_bidirectional_dijkstra;
+pub use graph::{Adjacency, Graph, Neighbors, UndirectedGraph};
+pub use builder::{BuildError, GraphBuilder, ParallelEdges, SelfLoops};
+pub use turn_costs::{Turn, TurnCostTable};
+pub use overlay::Overlay;
+pub use search_stats::SearchStats;
+pub use priority_queue::{PriorityQueue, QueueKind};
+pub use parallel_config::ParallelConfig;
+pub use standard_dijkstra::{sequential_dijkstra, sequential_dijkstra_with_overlay, sequential_dijkstra_with_stats, sequential_dijkstra_with_queue, sequential_sssp, sequential_sssp_with_overlay, sequential_sssp_with_queue};
+pub use bidirectional_dijkstra::{bidirectional_dijkstra, bidirectional_dijkstra_with_overlay, bidirectional_dijkstra_with_stats, bidirectional_dijkstra_with_queue};
+pub use parallel_dijkstra::{parallel_dijkstra, parallel_dijkstra_with_overlay, parallel_dijkstra_with_stats, parallel_dijkstra_with_config, parallel_dijkstra_with_queue};
+pub use parallel_bi_dijkstra::{parallel_bidirectional_dijkstra, parallel_bidirectional_dijkstra_with_overlay, parallel_bidirectional_dijkstra_with_stats, parallel_bidirectional_dijkstra_with_config, parallel_bidirectional_dijkstra_with_queue};
+pub use dial::{dial_dijkstra, dial_dijkstra_with_max_weight, dial_dijkstra_with_overlay, dial_dijkstra_with_stats};
+pub use edge_based_dijkstra::{edge_based_dijkstra, edge_based_dijkstra_with_overlay, edge_based_dijkstra_with_queue, TurnGraph};
+pub use timetable::Timetable;
+pub use csa::csa_earliest_arrival;
+pub use raptor::raptor_earliest_arrival;
+pub use dynamic_sssp::DynamicSssp;
+pub use algorithm::Algorithm;
+pub use selection::{select_algorithm, shortest_path, shortest_path_with_selection, shortest_path_with_stats, GraphProfile, Selection};
+pub use query::{PathResult, Query};
+pub use verify::{verify_distances, verify_path, verify_tree, VerifyError};
+pub use edge_list::IdMap;
+pub use binary::MappedGraph;
+pub use petgraph_interop::PetgraphAdapter;
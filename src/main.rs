@@ -1,164 +1,80 @@
-// FOR ADDITIONAL BENCHMARK TEST
-
-use my_dijkstra_crate::{sequential_dijkstra, bidirectional_dijkstra, parallel_bidirectional_dijkstra,parallel_dijkstra};
-use std::time::Instant;
-
-
-fn is_valid_path(graph: &Vec<Vec<(usize, usize)>>, path: &Vec<usize>, expected_cost: usize) -> bool {
-    if path.is_empty() {
-        return false;
-    }
-
-    let mut total_cost = 0;
-    for window in path.windows(2) {
-        if let Some(&(next, weight)) = graph[window[0]].iter().find(|&&(next, _)| next == window[1]) {
-            total_cost += weight;
-        } else {
-            return false;
+// Benchmark harness: runs the scenarios of a config file (default
+// `benchmarks.conf`) and writes CSV, JSON and Markdown reports. With
+// `--scaling` it instead measures how the parallel search scales with the
+// number of threads.
+//
+//   cargo run --release --bin benchmark -- [CONFIG] [--out DIR]
+//   cargo run --release --bin benchmark -- --scaling [CONFIG] [--max-threads N] [--out DIR]
+
+use my_dijkstra_crate::harness::{load_config, parse_config, run_scaling, run_scenario, write_markdown, write_reports, write_scaling_markdown, write_scaling_reports, Scenario};
+use std::io;
+use std::process::ExitCode;
+
+const DEFAULT_CONFIG: &str = include_str!("../benchmarks.conf");
+const DEFAULT_OUT: &str = "target/benchmark-results";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
         }
     }
-    total_cost == expected_cost
 }
 
-fn main() {
-    // Define the number of nodes for the large graphs
-    let num_nodes = 100;
-    let num_large_nodes = 1000;  // Define the number of nodes for the super large graph
-    let num_huge_nodes = 5000;  // Define the number of nodes for the super huge graph
-
-    // Initialize a very large and dense graph with proper indices
-    let dense_graph = (0..num_nodes).map(|i| {
-        (0..num_nodes).filter(|&j| j != i).map(|j| (j, (i + j) % 10 + 1)).collect::<Vec<_>>()
-    }).collect::<Vec<_>>();
-
-    // Initialize a super large and dense graph
-    let super_large_dense_graph = (0..num_large_nodes).map(|i| {
-        (0..num_large_nodes).filter(|&j| j != i).map(|j| (j, (i + j) % 10 + 1)).collect::<Vec<_>>()
-    }).collect::<Vec<_>>();
-
-    // Initialize a super huge and dense graph
-    let super_huge_dense_graph = (0..num_huge_nodes).map(|i| {
-        (0..num_huge_nodes).filter(|&j| j != i).map(|j| (j, (i + j) % 10 + 1)).collect::<Vec<_>>()
-    }).collect::<Vec<_>>();
-
-    // Initialize a mega huge and dense graph
-    let mega_huge_dense_graph = (0..10000).map(|i| {
-        (0..10000).filter(|&j| j != i).map(|j| (j, (i + j) % 10 + 1)).collect::<Vec<_>>()
-    }).collect::<Vec<_>>();
-
-    // Define multiple test cases
-    let test_cases = vec![
-        (
-            "Simple Path",
-            vec![
-                vec![(1, 2)],  // Node 0 connections
-                vec![(2, 2)],  // Node 1 connections
-                vec![(3, 2)],  // Node 2 connections
-                vec![]         // Node 3 has no outgoing edges
-            ],
-            0,
-            3,
-        ),
-        (
-            "Graph with Cycles",
-            vec![
-                vec![(1, 2), (2, 1)],  // Node 0 connections
-                vec![(3, 1)],          // Node 1 connections
-                vec![(1, 2), (3, 2)],  // Node 2 connections
-                vec![]                 // Node 3 has no outgoing edges
-            ],
-            0,
-            3,
-        ),
-        (
-            "Medium-Sized Dense Graph with Close Start and Goal",
-            (0..500).map(|i| {
-                (0..500).filter(|&j| j != i).map(|j| (j, (i + j) % 10 + 1)).collect::<Vec<_>>()
-            }).collect::<Vec<_>>(),
-            0,
-            10,
-        ),
-        (
-            "Very Complex Graph",
-            vec![
-                vec![(1, 5), (2, 1), (3, 10)],     // Node 0
-                vec![(0, 5), (2, 3), (4, 1)],      // Node 1
-                vec![(0, 1), (1, 3), (3, 4), (4, 8)],  // Node 2
-                vec![(0, 10), (2, 4), (4, 2)],     // Node 3
-                vec![(1, 1), (2, 8), (3, 2), (5, 6)],  // Node 4
-                vec![(4, 6)],                      // Node 5
-            ],
-            0,
-            5,
-        ),
-        (
-            "Large Graph with Multiple Paths",
-            vec![
-                vec![(1, 1), (5, 3)],               // Node 0 connections
-                vec![(2, 2), (4, 3)],               // Node 1 connections
-                vec![(3, 4)],                       // Node 2 connections
-                vec![(4, 1), (6, 1)],               // Node 3 connections
-                vec![(7, 5)],                       // Node 4 connections
-                vec![(6, 2)],                       // Node 5 connections
-                vec![(7, 1)],                       // Node 6 connections
-                vec![]                              // Node 7 has no outgoing edges
-            ],
-            0,
-            7,
-        ),
-        (
-            "Very Large and Dense Graph",
-            dense_graph,
-            0,
-            99,
-        ),
-        (
-            "Super Large and Dense Graph",
-            super_large_dense_graph,
-            0,
-            700,
-        ),
-        
-    ];
-
-    // Run each test case
-    for (name, adj_list, start, goal) in test_cases {
-        println!("Test Case: {}", name);
-
-        // Benchmark sequential Dijkstra
-        let start_time = Instant::now();
-        let (sequential_cost, sequential_path) = sequential_dijkstra(&adj_list, start, goal);
-        let sequential_duration = start_time.elapsed();
-        println!("Sequential Dijkstra: cost = {:?}, path = {:?}, duration = {:?}", sequential_cost, sequential_path, sequential_duration);
-
-        // Benchmark bidirectional Dijkstra
-        let start_time = Instant::now();
-        let (bidirectional_cost, bidirectional_path) = bidirectional_dijkstra(&adj_list, start, goal);
-        let bidirectional_duration = start_time.elapsed();
-        println!("Bidirectional Dijkstra: cost = {:?}, path = {:?}, duration = {:?}", bidirectional_cost, bidirectional_path, bidirectional_duration);
-       
-        // Benchmark parallel Dijkstra
-        let start_time = Instant::now();
-        let (bidirectional_cost, bidirectional_path) = parallel_dijkstra(&adj_list, start, goal);
-        let bidirectional_duration = start_time.elapsed();
-        println!("Parallel Dijkstra: cost = {:?}, path = {:?}, duration = {:?}", bidirectional_cost, bidirectional_path, bidirectional_duration);
-        
-        // Benchmark parallel bidirectional Dijkstra
-        let start_time = Instant::now();
-        let (bidirectional_cost, bidirectional_path) = parallel_bidirectional_dijkstra(&adj_list, start, goal);
-        let bidirectional_duration = start_time.elapsed();
-        println!("Parallel Bidirectional Dijkstra: cost = {:?}, path = {:?}, duration = {:?}", bidirectional_cost, bidirectional_path, bidirectional_duration);
+fn run() -> Result<(), String> {
+    let mut config = None;
+    let mut out = DEFAULT_OUT.to_string();
+    let mut scaling = false;
+    let mut max_threads = num_cpus::get();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out = args.next().ok_or("--out needs a directory")?,
+            "--scaling" => scaling = true,
+            "--max-threads" => {
+                let value = args.next().ok_or("--max-threads needs a number")?;
+                max_threads = value.parse().map_err(|_| format!("invalid thread count `{}`", value))?;
+            }
+            "-h" | "--help" => {
+                println!("Usage: benchmark [CONFIG] [--out DIR]\n       benchmark --scaling [CONFIG] [--max-threads N] [--out DIR]\n\nRuns the scenarios in CONFIG (default: the bundled benchmarks.conf)\nand writes results.csv, results.json and results.md to DIR (default: {}).\nWith --scaling, runs the parallel search under 1..=N threads (default:\nthe number of CPUs) and writes scaling.csv, scaling.json and scaling.md.", DEFAULT_OUT);
+                return Ok(());
+            }
+            _ if config.is_none() => config = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
 
+    let scenarios: Vec<Scenario> = match &config {
+        Some(path) => load_config(path).map_err(|err| format!("{}: {}", path, err))?,
+        None => parse_config(DEFAULT_CONFIG)?,
+    };
 
-        // Ensure both paths are valid
-        if sequential_cost != usize::MAX {
-            assert!(is_valid_path(&adj_list, &sequential_path, sequential_cost), "Sequential path is not valid for {}", name);
-            assert!(is_valid_path(&adj_list, &bidirectional_path, bidirectional_cost), "Bidirectional path is not valid for {}", name);
-        } else {
-            assert!(sequential_path.is_empty(), "Sequential path should be empty for {}", name);
-            assert!(bidirectional_path.is_empty(), "Bidirectional path should be empty for {}", name);
+    if scaling {
+        let mut points = Vec::new();
+        for scenario in &scenarios {
+            eprintln!("Scaling {} on 1..={} threads...", scenario.name, max_threads);
+            points.extend(run_scaling(scenario, max_threads).map_err(|err| format!("{}: {}", scenario.name, err))?);
         }
+        write_scaling_markdown(&points, io::stdout().lock()).map_err(|err| err.to_string())?;
+        write_scaling_reports(&points, &out).map_err(|err| format!("{}: {}", out, err))?;
+        eprintln!("Reports written to {}", out);
+        return Ok(());
+    }
 
-        println!();  // Add a blank line between test cases
+    let mut measurements = Vec::new();
+    for scenario in &scenarios {
+        eprintln!("Running {} ({} queries x {} runs)...", scenario.name, scenario.queries, scenario.runs);
+        let results = run_scenario(scenario).map_err(|err| format!("{}: {}", scenario.name, err))?;
+        for result in results.iter().filter(|result| result.errors > 0) {
+            eprintln!("warning: {} gave {} wrong answers on {}", result.algorithm, result.errors, scenario.name);
+        }
+        measurements.extend(results);
     }
+
+    write_markdown(&measurements, io::stdout().lock()).map_err(|err| err.to_string())?;
+    write_reports(&measurements, &out).map_err(|err| format!("{}: {}", out, err))?;
+    eprintln!("Reports written to {}", out);
+    Ok(())
 }
@@ -0,0 +1,156 @@
+//! Matrix Market coordinate format, as used by the SuiteSparse collection.
+//!
+//! Entry `(i, j, value)` is the edge `i - 1 -> j - 1`. `pattern` matrices get
+//! weight 1, `real` values are rounded to the nearest integer, and
+//! `symmetric` matrices store each off-diagonal edge once for both directions.
+
+use std::io::{self, BufRead, BufWriter, Write};
+use crate::graph::Graph;
+use crate::GraphBuilder;
+
+pub fn read_matrix_market<R: BufRead>(reader: R) -> io::Result<Graph> {
+    let mut lines = reader.lines().enumerate();
+    let banner = match lines.next() {
+        Some((_, line)) => line?,
+        None => return Err(invalid(1, "empty file")),
+    };
+    let banner: Vec<String> = banner.split_whitespace().map(|s| s.to_ascii_lowercase()).collect();
+    if banner.len() != 5 || banner[0] != "%%matrixmarket" || banner[1] != "matrix" {
+        return Err(invalid(1, "expected `%%MatrixMarket matrix coordinate <field> <symmetry>`"));
+    }
+    if banner[2] != "coordinate" {
+        return Err(invalid(1, "only the coordinate format is supported"));
+    }
+    let field = banner[3].as_str();
+    if !matches!(field, "real" | "integer" | "pattern") {
+        return Err(invalid(1, &format!("unsupported field `{}`", field)));
+    }
+    let symmetric = match banner[4].as_str() {
+        "general" => false,
+        "symmetric" => true,
+        other => return Err(invalid(1, &format!("unsupported symmetry `{}`", other))),
+    };
+
+    let mut size = None;
+    let mut edges = Vec::new();
+    for (index, line) in lines {
+        let line_no = index + 1;
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some((rows, max_edges)) = size else {
+            if parts.len() != 3 {
+                return Err(invalid(line_no, "expected `rows columns entries`"));
+            }
+            let (rows, columns, entries) = (parse(line_no, parts[0])?, parse(line_no, parts[1])?, parse(line_no, parts[2])?);
+            if rows != columns {
+                return Err(invalid(line_no, "adjacency matrices must be square"));
+            }
+            // A symmetric entry off the diagonal stands for two edges
+            let max_edges = entries.checked_mul(if symmetric { 2 } else { 1 })
+                .ok_or_else(|| invalid(line_no, "entry count too large"))?;
+            size = Some((rows, max_edges));
+            edges.reserve(max_edges.min(1 << 20));
+            continue;
+        };
+
+        let expected = if field == "pattern" { 2 } else { 3 };
+        if parts.len() != expected {
+            return Err(invalid(line_no, &format!("expected {} values", expected)));
+        }
+        let (i, j) = (parse(line_no, parts[0])?, parse(line_no, parts[1])?);
+        if i == 0 || j == 0 || i > rows || j > rows {
+            return Err(invalid(line_no, "entry out of range"));
+        }
+        let weight = match field {
+            "pattern" => 1,
+            "integer" => parse(line_no, parts[2])?,
+            _ => match parts[2].parse::<f64>() {
+                Ok(value) if value >= 0.0 && value.is_finite() => value.round() as usize,
+                _ => return Err(invalid(line_no, &format!("invalid weight `{}`", parts[2]))),
+            },
+        };
+        edges.push((i - 1, j - 1, weight));
+        if symmetric && i != j {
+            edges.push((j - 1, i - 1, weight));
+        }
+        if edges.len() > max_edges {
+            return Err(invalid(line_no, "more entries than declared"));
+        }
+    }
+
+    let (rows, _) = size.ok_or_else(|| invalid(0, "missing size line"))?;
+    GraphBuilder::new(rows).edges(edges).build()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Writes `graph` as a general integer coordinate matrix.
+pub fn write_matrix_market<W: Write>(graph: &Graph, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "%%MatrixMarket matrix coordinate integer general")?;
+    writeln!(writer, "{} {} {}", graph.num_nodes(), graph.num_nodes(), graph.num_edges())?;
+    for (u, edges) in graph.adj_list.iter().enumerate() {
+        for &(v, weight) in edges {
+            writeln!(writer, "{} {} {}", u + 1, v + 1, weight)?;
+        }
+    }
+    writer.flush()
+}
+
+fn parse(line_no: usize, value: &str) -> io::Result<usize> {
+    value.parse().map_err(|_| invalid(line_no, &format!("invalid number `{}`", value)))
+}
+
+fn invalid(line_no: usize, message: &str) -> io::Error {
+    let message = if line_no == 0 { message.to_string() } else { format!("line {}: {}", line_no, message) };
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_dijkstra::sequential_dijkstra;
+
+    #[test]
+    fn test_read_symmetric_real() {
+        let input = "%%MatrixMarket matrix coordinate real symmetric\n% comment\n3 3 3\n2 1 1.6\n3 2 0.4\n3 3 5\n";
+        let graph = read_matrix_market(input.as_bytes()).unwrap();
+        assert_eq!(graph.adj_list, vec![vec![(1, 2)], vec![(0, 2), (2, 0)], vec![(1, 0), (2, 5)]]);
+        assert_eq!(sequential_dijkstra(&graph.adj_list, 0, 2), (2, vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn test_pattern_and_errors() {
+        let graph = read_matrix_market("%%MatrixMarket matrix coordinate pattern general\n2 2 1\n1 2\n".as_bytes()).unwrap();
+        assert_eq!(graph.adj_list, vec![vec![(1, 1)], vec![]]);
+
+        let cases = [
+            "%%MatrixMarket matrix array real general\n2 2\n1\n2\n3\n4\n",
+            "%%MatrixMarket matrix coordinate complex general\n2 2 1\n1 2 1 0\n",
+            "%%MatrixMarket matrix coordinate integer general\n2 3 1\n1 2 1\n",
+            "%%MatrixMarket matrix coordinate integer general\n2 2 1\n1 3 1\n",
+            "%%MatrixMarket matrix coordinate real general\n2 2 1\n1 2 -1.5\n",
+            "%%MatrixMarket matrix coordinate integer general\n2 2 1\n1 2 1\n2 1 1\n",
+            "%%MatrixMarket matrix coordinate integer symmetric\n2 2 18446744073709551615\n1 2 1\n",
+        ];
+        for input in cases {
+            assert!(read_matrix_market(input.as_bytes()).is_err(), "{}", input);
+        }
+
+        // A huge declared count is not reserved up front
+        let graph = read_matrix_market("%%MatrixMarket matrix coordinate integer general\n2 2 18446744073709551615\n1 2 4\n".as_bytes()).unwrap();
+        assert_eq!(graph.adj_list, vec![vec![(1, 4)], vec![]]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let graph = Graph::new(vec![vec![(1, 7), (1, 3)], vec![(0, 2)], vec![(2, 1)]]);
+        let mut out = Vec::new();
+        write_matrix_market(&graph, &mut out).unwrap();
+        assert!(String::from_utf8(out.clone()).unwrap().starts_with("%%MatrixMarket matrix coordinate integer general\n3 3 4\n"));
+        assert_eq!(read_matrix_market(out.as_slice()).unwrap().adj_list, graph.adj_list);
+    }
+}
@@ -0,0 +1,644 @@
+//! OpenStreetMap import from `.osm` XML and `.osm.pbf` extracts.
+//!
+//! Only ways with an accepted `highway` tag become edges. Consecutive way
+//! nodes are joined by an edge weighted with their great-circle distance in
+//! metres, in both directions unless the way is one-way. Only nodes used by
+//! an accepted way end up in the graph, so its ids are dense.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use flate2::read::ZlibDecoder;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use crate::builder::{GraphBuilder, ParallelEdges, SelfLoops};
+use crate::graph::Graph;
+
+/// Mean earth radius in metres.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Highway values a car can drive on.
+pub const CAR_HIGHWAYS: [&str; 16] = [
+    "motorway", "motorway_link", "trunk", "trunk_link", "primary", "primary_link",
+    "secondary", "secondary_link", "tertiary", "tertiary_link", "unclassified",
+    "residential", "living_street", "service", "road", "track",
+];
+
+#[derive(Clone, Debug)]
+pub struct OsmOptions {
+    /// Accepted `highway` values; empty accepts every way with a `highway` tag.
+    pub highways: Vec<String>,
+    /// Whether `oneway`, roundabouts and motorways restrict the direction.
+    pub respect_oneway: bool,
+}
+
+impl Default for OsmOptions {
+    fn default() -> Self {
+        OsmOptions {
+            highways: CAR_HIGHWAYS.iter().map(|h| h.to_string()).collect(),
+            respect_oneway: true,
+        }
+    }
+}
+
+/// A routable graph built from OSM data. Coordinates are `(lon, lat)`, and
+/// `osm_ids[u]` is the OSM node id of node `u`.
+#[derive(Clone, Debug)]
+pub struct OsmGraph {
+    pub graph: Graph,
+    pub osm_ids: Vec<i64>,
+    /// Inverse of `osm_ids`, built at load time.
+    ids: HashMap<i64, usize>,
+}
+
+impl OsmGraph {
+    pub fn node(&self, osm_id: i64) -> Option<usize> {
+        self.ids.get(&osm_id).copied()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Direction {
+    Both,
+    Forward,
+    Backward,
+}
+
+/// Raw nodes and highway ways collected by either parser.
+#[derive(Default)]
+struct OsmData {
+    nodes: HashMap<i64, (f64, f64)>,
+    ways: Vec<(Vec<i64>, Direction)>,
+}
+
+impl OsmData {
+    fn add_way(&mut self, refs: Vec<i64>, tags: &HashMap<String, String>, options: &OsmOptions) {
+        let Some(highway) = tags.get("highway") else {
+            return;
+        };
+        if !options.highways.is_empty() && !options.highways.iter().any(|h| h == highway) {
+            return;
+        }
+        let direction = if !options.respect_oneway {
+            Direction::Both
+        } else {
+            match tags.get("oneway").map(String::as_str) {
+                Some("yes" | "true" | "1") => Direction::Forward,
+                Some("-1" | "reverse") => Direction::Backward,
+                Some("no" | "false" | "0") => Direction::Both,
+                _ if highway == "motorway" || tags.get("junction").is_some_and(|j| j == "roundabout") => Direction::Forward,
+                _ => Direction::Both,
+            }
+        };
+        self.ways.push((refs, direction));
+    }
+
+    fn into_graph(self) -> io::Result<OsmGraph> {
+        let mut ids = HashMap::new();
+        let mut osm_ids = Vec::new();
+        let mut coordinates = Vec::new();
+        let mut edges = Vec::new();
+        for (refs, direction) in &self.ways {
+            // Segments touching nodes missing from a clipped extract are skipped.
+            for pair in refs.windows(2) {
+                let (Some(&a), Some(&b)) = (self.nodes.get(&pair[0]), self.nodes.get(&pair[1])) else {
+                    continue;
+                };
+                let mut dense = |osm_id: i64, coordinate: (f64, f64)| *ids.entry(osm_id).or_insert_with(|| {
+                    osm_ids.push(osm_id);
+                    coordinates.push(coordinate);
+                    osm_ids.len() - 1
+                });
+                let (u, v) = (dense(pair[0], a), dense(pair[1], b));
+                let length = haversine_distance(a, b).round() as usize;
+                if *direction != Direction::Backward {
+                    edges.push((u, v, length));
+                }
+                if *direction != Direction::Forward {
+                    edges.push((v, u, length));
+                }
+            }
+        }
+
+        let graph = GraphBuilder::new(osm_ids.len())
+            .parallel_edges(ParallelEdges::KeepMin)
+            .self_loops(SelfLoops::Drop)
+            .edges(edges)
+            .build()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(OsmGraph { graph: graph.with_coordinates(coordinates), osm_ids, ids })
+    }
+}
+
+/// Great-circle distance in metres between two `(lon, lat)` points.
+pub fn haversine_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lon1, lat1) = (a.0.to_radians(), a.1.to_radians());
+    let (lon2, lat2) = (b.0.to_radians(), b.1.to_radians());
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * h.sqrt().asin()
+}
+
+/// Loads `.osm.pbf` files as PBF and anything else as XML.
+pub fn load_osm<P: AsRef<Path>>(path: P, options: &OsmOptions) -> io::Result<OsmGraph> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    if path.to_string_lossy().ends_with(".pbf") {
+        read_osm_pbf(BufReader::new(file), options)
+    } else {
+        read_osm_xml(BufReader::new(file), options)
+    }
+}
+
+pub fn read_osm_xml<R: BufRead>(reader: R, options: &OsmOptions) -> io::Result<OsmGraph> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut data = OsmData::default();
+    // The way currently being read, with its node refs and tags.
+    let mut way: Option<(Vec<i64>, HashMap<String, String>)> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf).map_err(xml_error)? {
+            // A self-closing `<way/>` has no end tag and no nodes, so it is ignored.
+            Event::Empty(element) if element.name().as_ref() == b"way" => {}
+            Event::Start(element) | Event::Empty(element) => {
+                let attributes = attributes(&element)?;
+                let get = |key: &str| attributes.get(key).ok_or_else(|| invalid(&format!("<{}> without `{}`", String::from_utf8_lossy(element.name().as_ref()), key)));
+                match element.name().as_ref() {
+                    b"node" => {
+                        let id = parse(get("id")?)?;
+                        data.nodes.insert(id, (parse(get("lon")?)?, parse(get("lat")?)?));
+                    }
+                    b"way" => way = Some((Vec::new(), HashMap::new())),
+                    b"nd" => if let Some((refs, _)) = &mut way {
+                        refs.push(parse(get("ref")?)?);
+                    },
+                    b"tag" => if let Some((_, tags)) = &mut way {
+                        tags.insert(get("k")?.clone(), get("v")?.clone());
+                    },
+                    _ => {}
+                }
+            }
+            Event::End(element) if element.name().as_ref() == b"way" => {
+                if let Some((refs, tags)) = way.take() {
+                    data.add_way(refs, &tags, options);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    data.into_graph()
+}
+
+fn attributes(element: &BytesStart) -> io::Result<HashMap<String, String>> {
+    element.attributes().map(|attribute| {
+        let attribute = attribute.map_err(xml_error)?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        Ok((key, attribute.unescape_value().map_err(xml_error)?.into_owned()))
+    }).collect()
+}
+
+/// Size limits from the PBF spec, checked before anything is allocated.
+const MAX_BLOB_HEADER_SIZE: usize = 64 * 1024;
+const MAX_BLOB_SIZE: usize = 32 * 1024 * 1024;
+
+/// Reads the PBF blob sequence. Only uncompressed and zlib blobs are supported,
+/// which is what common tools such as osmium and osmosis write by default.
+pub fn read_osm_pbf<R: Read>(mut reader: R, options: &OsmOptions) -> io::Result<OsmGraph> {
+    let mut data = OsmData::default();
+    loop {
+        let mut len = [0; 4];
+        match reader.read_exact(&mut len) {
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            result => result?,
+        }
+        let header_size = u32::from_be_bytes(len) as usize;
+        if header_size >= MAX_BLOB_HEADER_SIZE {
+            return Err(invalid(&format!("PBF blob header of {} bytes exceeds the 64 KiB limit", header_size)));
+        }
+        let mut header = vec![0; header_size];
+        reader.read_exact(&mut header)?;
+        let (blob_type, data_size) = blob_header(&header)?;
+        if data_size > MAX_BLOB_SIZE {
+            return Err(invalid(&format!("PBF blob of {} bytes exceeds the 32 MiB limit", data_size)));
+        }
+        let mut blob = vec![0; data_size];
+        reader.read_exact(&mut blob)?;
+        let block = decode_blob(&blob)?;
+        match blob_type.as_str() {
+            "OSMHeader" => check_header_block(&block)?,
+            "OSMData" => read_primitive_block(&block, &mut data, options)?,
+            _ => {}
+        }
+    }
+    data.into_graph()
+}
+
+fn blob_header(bytes: &[u8]) -> io::Result<(String, usize)> {
+    let mut blob_type = String::new();
+    let mut data_size = 0;
+    for field in Proto::new(bytes) {
+        match field? {
+            (1, Value::Bytes(b)) => blob_type = String::from_utf8_lossy(b).into_owned(),
+            (3, Value::Varint(size)) => data_size = usize::try_from(size).unwrap_or(usize::MAX),
+            _ => {}
+        }
+    }
+    Ok((blob_type, data_size))
+}
+
+fn decode_blob(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    for field in Proto::new(bytes) {
+        match field? {
+            (1, Value::Bytes(raw)) => return Ok(raw.to_vec()),
+            (3, Value::Bytes(zlib)) => {
+                let mut block = Vec::new();
+                ZlibDecoder::new(zlib).take(MAX_BLOB_SIZE as u64 + 1).read_to_end(&mut block)?;
+                if block.len() > MAX_BLOB_SIZE {
+                    return Err(invalid("decompressed PBF blob exceeds the 32 MiB limit"));
+                }
+                return Ok(block);
+            }
+            (4 | 6 | 7, _) => return Err(invalid("only raw and zlib PBF blobs are supported")),
+            _ => {}
+        }
+    }
+    Err(invalid("empty PBF blob"))
+}
+
+fn check_header_block(bytes: &[u8]) -> io::Result<()> {
+    for field in Proto::new(bytes) {
+        if let (4, Value::Bytes(feature)) = field? {
+            let feature = String::from_utf8_lossy(feature);
+            if feature != "OsmSchema-V0.6" && feature != "DenseNodes" {
+                return Err(invalid(&format!("unsupported PBF feature `{}`", feature)));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_primitive_block(bytes: &[u8], data: &mut OsmData, options: &OsmOptions) -> io::Result<()> {
+    let mut strings = Vec::new();
+    let mut groups = Vec::new();
+    let (mut granularity, mut lat_offset, mut lon_offset) = (100i64, 0i64, 0i64);
+    for field in Proto::new(bytes) {
+        match field? {
+            (1, Value::Bytes(table)) => for entry in Proto::new(table) {
+                if let (1, Value::Bytes(s)) = entry? {
+                    strings.push(String::from_utf8_lossy(s).into_owned());
+                }
+            },
+            (2, Value::Bytes(group)) => groups.push(group),
+            (17, Value::Varint(g)) => granularity = g as i64,
+            (19, Value::Varint(offset)) => lat_offset = offset as i64,
+            (20, Value::Varint(offset)) => lon_offset = offset as i64,
+            _ => {}
+        }
+    }
+    let degrees = |offset: i64, value: i64| 1e-9 * (offset + granularity * value) as f64;
+    let string = |index: u64| strings.get(index as usize).cloned().ok_or_else(|| invalid("string table index out of range"));
+
+    for group in groups {
+        for field in Proto::new(group) {
+            match field? {
+                (1, Value::Bytes(node)) => {
+                    let (mut id, mut lat, mut lon) = (0, 0, 0);
+                    for field in Proto::new(node) {
+                        match field? {
+                            (1, Value::Varint(v)) => id = zigzag(v),
+                            (8, Value::Varint(v)) => lat = zigzag(v),
+                            (9, Value::Varint(v)) => lon = zigzag(v),
+                            _ => {}
+                        }
+                    }
+                    data.nodes.insert(id, (degrees(lon_offset, lon), degrees(lat_offset, lat)));
+                }
+                (2, Value::Bytes(dense)) => {
+                    let (mut ids, mut lats, mut lons) = (Vec::new(), Vec::new(), Vec::new());
+                    for field in Proto::new(dense) {
+                        match field? {
+                            (1, value) => ids = packed(value)?,
+                            (8, value) => lats = packed(value)?,
+                            (9, value) => lons = packed(value)?,
+                            _ => {}
+                        }
+                    }
+                    if ids.len() != lats.len() || ids.len() != lons.len() {
+                        return Err(invalid("dense nodes with mismatched id and coordinate counts"));
+                    }
+                    let (mut id, mut lat, mut lon) = (0, 0, 0);
+                    for i in 0..ids.len() {
+                        id += zigzag(ids[i]);
+                        lat += zigzag(lats[i]);
+                        lon += zigzag(lons[i]);
+                        data.nodes.insert(id, (degrees(lon_offset, lon), degrees(lat_offset, lat)));
+                    }
+                }
+                (3, Value::Bytes(way)) => {
+                    let (mut keys, mut values, mut deltas) = (Vec::new(), Vec::new(), Vec::new());
+                    for field in Proto::new(way) {
+                        match field? {
+                            (2, value) => keys = packed(value)?,
+                            (3, value) => values = packed(value)?,
+                            (8, value) => deltas = packed(value)?,
+                            _ => {}
+                        }
+                    }
+                    let tags = keys.iter().zip(&values)
+                        .map(|(&k, &v)| Ok((string(k)?, string(v)?)))
+                        .collect::<io::Result<HashMap<_, _>>>()?;
+                    let refs = deltas.iter()
+                        .scan(0i64, |id, &delta| {
+                            *id += zigzag(delta);
+                            Some(*id)
+                        })
+                        .collect();
+                    data.add_way(refs, &tags, options);
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One protobuf field value; fixed-width fields are skipped by the reader.
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Minimal protobuf wire-format reader, yielding `(field number, value)`.
+struct Proto<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Proto<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Proto { bytes, pos: 0 }
+    }
+
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.bytes.get(self.pos).ok_or_else(|| invalid("truncated protobuf varint"))?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("protobuf varint too long"))
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).ok_or_else(|| invalid("protobuf field length overflows"))?;
+        let bytes = self.bytes.get(self.pos..end).ok_or_else(|| invalid("truncated protobuf field"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn field(&mut self) -> io::Result<(u32, Value<'a>)> {
+        let key = self.varint()?;
+        let value = match key & 7 {
+            0 => Value::Varint(self.varint()?),
+            1 => self.take(8).map(|_| Value::Fixed)?,
+            2 => {
+                let len = self.varint()? as usize;
+                Value::Bytes(self.take(len)?)
+            }
+            5 => self.take(4).map(|_| Value::Fixed)?,
+            wire_type => return Err(invalid(&format!("unsupported protobuf wire type {}", wire_type))),
+        };
+        Ok(((key >> 3) as u32, value))
+    }
+}
+
+impl<'a> Iterator for Proto<'a> {
+    type Item = io::Result<(u32, Value<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.bytes.len() {
+            return None;
+        }
+        let field = self.field();
+        if field.is_err() {
+            self.pos = self.bytes.len();
+        }
+        Some(field)
+    }
+}
+
+/// A repeated varint field, packed or not.
+fn packed(value: Value) -> io::Result<Vec<u64>> {
+    match value {
+        Value::Varint(v) => Ok(vec![v]),
+        Value::Bytes(bytes) => {
+            let mut proto = Proto::new(bytes);
+            let mut values = Vec::new();
+            while proto.pos < bytes.len() {
+                values.push(proto.varint()?);
+            }
+            Ok(values)
+        }
+        Value::Fixed => Err(invalid("expected a packed varint field")),
+    }
+}
+
+fn zigzag(v: u64) -> i64 {
+    (v >> 1) as i64 ^ -((v & 1) as i64)
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> io::Result<T> {
+    value.parse().map_err(|_| invalid(&format!("invalid number `{}`", value)))
+}
+
+fn xml_error<E: std::error::Error + Send + Sync + 'static>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bidirectional_dijkstra, sequential_dijkstra};
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    // A small T junction: a two-way residential street 1-2-3, a one-way
+    // street 2-4, a footway 3-5 that cars must ignore and a reversed one-way 5-1.
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osm version="0.6" generator="test">
+  <node id="1" lat="52.5000" lon="13.4000"/>
+  <node id="2" lat="52.5000" lon="13.4010"/>
+  <node id="3" lat="52.5000" lon="13.4020"/>
+  <node id="4" lat="52.5010" lon="13.4010"/>
+  <node id="5" lat="52.5010" lon="13.4020"/>
+  <way id="10">
+    <nd ref="1"/><nd ref="2"/><nd ref="3"/>
+    <tag k="highway" v="residential"/>
+    <tag k="name" v="Main &amp; Co Street"/>
+  </way>
+  <way id="11">
+    <nd ref="2"/><nd ref="4"/>
+    <tag k="highway" v="residential"/>
+    <tag k="oneway" v="yes"/>
+  </way>
+  <way id="12">
+    <nd ref="3"/><nd ref="5"/>
+    <tag k="highway" v="footway"/>
+  </way>
+  <way id="13">
+    <nd ref="4"/><nd ref="99"/>
+    <tag k="highway" v="service"/>
+  </way>
+</osm>"#;
+
+    #[test]
+    fn test_haversine_distance() {
+        // One degree of longitude at the equator.
+        assert!((haversine_distance((0.0, 0.0), (1.0, 0.0)) - 111_195.0).abs() < 1.0);
+        assert_eq!(haversine_distance((13.4, 52.5), (13.4, 52.5)), 0.0);
+    }
+
+    #[test]
+    fn test_read_xml() {
+        let osm = read_osm_xml(XML.as_bytes(), &OsmOptions::default()).unwrap();
+        assert_eq!(osm.osm_ids, vec![1, 2, 3, 4]);  // 5 is only on the footway
+        let coordinates = osm.graph.coordinates.as_ref().unwrap();
+        assert_eq!(coordinates[3], (13.401, 52.501));
+
+        let (one, three, four) = (osm.node(1).unwrap(), osm.node(3).unwrap(), osm.node(4).unwrap());
+        assert_eq!(osm.node(5), None);
+        let (cost, path) = sequential_dijkstra(&osm.graph.adj_list, one, four);
+        assert_eq!(path, vec![0, 1, 3]);
+        assert!((175..=185).contains(&cost), "cost {}", cost);  // ~68 m east, ~111 m north
+        assert_eq!(sequential_dijkstra(&osm.graph.adj_list, four, one).0, usize::MAX);  // One-way
+        assert_eq!(bidirectional_dijkstra(&osm.graph, three, one).1, vec![2, 1, 0]);
+
+        let ignore_oneway = OsmOptions { respect_oneway: false, ..OsmOptions::default() };
+        let osm = read_osm_xml(XML.as_bytes(), &ignore_oneway).unwrap();
+        assert_ne!(sequential_dijkstra(&osm.graph.adj_list, 3, 0).0, usize::MAX);
+
+        let any_highway = OsmOptions { highways: Vec::new(), ..OsmOptions::default() };
+        assert_eq!(read_osm_xml(XML.as_bytes(), &any_highway).unwrap().osm_ids.len(), 5);
+    }
+
+    fn varint(out: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            out.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        out.push(v as u8);
+    }
+
+    fn field(out: &mut Vec<u8>, number: u64, bytes: &[u8]) {
+        varint(out, number << 3 | 2);
+        varint(out, bytes.len() as u64);
+        out.extend_from_slice(bytes);
+    }
+
+    fn packed_sint(values: &[i64]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for &v in values {
+            varint(&mut out, ((v << 1) ^ (v >> 63)) as u64);
+        }
+        out
+    }
+
+    fn blob(out: &mut Vec<u8>, blob_type: &str, block: &[u8], compress: bool) {
+        let mut blob = Vec::new();
+        if compress {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(block).unwrap();
+            field(&mut blob, 3, &encoder.finish().unwrap());
+        } else {
+            field(&mut blob, 1, block);
+        }
+        let mut header = Vec::new();
+        field(&mut header, 1, blob_type.as_bytes());
+        varint(&mut header, 3 << 3);
+        varint(&mut header, blob.len() as u64);
+        out.extend_from_slice(&(header.len() as u32).to_be_bytes());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(&blob);
+    }
+
+    #[test]
+    fn test_read_pbf() {
+        let mut header_block = Vec::new();
+        field(&mut header_block, 4, b"OsmSchema-V0.6");
+        field(&mut header_block, 4, b"DenseNodes");
+
+        // Nodes 1, 2, 3 along a line, in units of 100 nanodegrees, delta coded.
+        let mut dense = Vec::new();
+        field(&mut dense, 1, &packed_sint(&[1, 1, 1]));
+        field(&mut dense, 8, &packed_sint(&[525_000_000, 0, 10_000]));
+        field(&mut dense, 9, &packed_sint(&[134_000_000, 10_000, 0]));
+        let mut way = Vec::new();
+        varint(&mut way, 1 << 3);
+        varint(&mut way, 10);
+        field(&mut way, 2, &[1, 3]);  // Keys "highway", "oneway"
+        field(&mut way, 3, &[2, 4]);  // Values "primary", "yes"
+        field(&mut way, 8, &packed_sint(&[1, 1, 1]));
+        let mut group = Vec::new();
+        field(&mut group, 2, &dense);
+        field(&mut group, 3, &way);
+        let mut strings = Vec::new();
+        for s in ["", "highway", "primary", "oneway", "yes"] {
+            field(&mut strings, 1, s.as_bytes());
+        }
+        let mut block = Vec::new();
+        field(&mut block, 1, &strings);
+        field(&mut block, 2, &group);
+
+        for compress in [false, true] {
+            let mut file = Vec::new();
+            blob(&mut file, "OSMHeader", &header_block, compress);
+            blob(&mut file, "OSMData", &block, compress);
+            let osm = read_osm_pbf(file.as_slice(), &OsmOptions::default()).unwrap();
+            assert_eq!(osm.osm_ids, vec![1, 2, 3]);
+            let (lon, lat) = osm.graph.coordinates.as_ref().unwrap()[1];
+            assert!((lon - 13.401).abs() < 1e-9 && (lat - 52.5).abs() < 1e-9);
+            assert_eq!(osm.graph.num_edges(), 2);
+            assert_ne!(sequential_dijkstra(&osm.graph.adj_list, 0, 2).0, usize::MAX);
+            assert_eq!(sequential_dijkstra(&osm.graph.adj_list, 2, 0).0, usize::MAX);
+        }
+
+        let mut unsupported = Vec::new();
+        field(&mut unsupported, 4, b"HistoricalInformation");
+        let mut file = Vec::new();
+        blob(&mut file, "OSMHeader", &unsupported, false);
+        assert!(read_osm_pbf(file.as_slice(), &OsmOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_oversized_pbf() {
+        let options = OsmOptions::default();
+        let huge_header = u32::MAX.to_be_bytes();
+        assert!(read_osm_pbf(huge_header.as_slice(), &options).unwrap_err().to_string().contains("64 KiB"));
+
+        let mut header = Vec::new();
+        field(&mut header, 1, b"OSMData");
+        varint(&mut header, 3 << 3);
+        varint(&mut header, 1 << 40);
+        let mut file = (header.len() as u32).to_be_bytes().to_vec();
+        file.extend_from_slice(&header);
+        assert!(read_osm_pbf(file.as_slice(), &options).unwrap_err().to_string().contains("32 MiB"));
+
+        // A length-delimited field claiming u64::MAX bytes
+        let mut overflowing = Vec::new();
+        varint(&mut overflowing, (1 << 3) | 2);
+        varint(&mut overflowing, u64::MAX);
+        assert_eq!(blob_header(&overflowing).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use crate::graph::Neighbors;
+
+#[derive(Clone, Debug, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        BitSet { words: vec![0; len.div_ceil(64)] }
+    }
+
+    pub fn insert(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    pub fn remove(&mut self, i: usize) {
+        self.words[i / 64] &= !(1 << (i % 64));
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words.get(i / 64).is_some_and(|word| word & (1 << (i % 64)) != 0)
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+/// Per-query modifications of a graph that is shared unchanged between
+/// queries: banned nodes, banned edges and edge weight overrides. Edge `i` of
+/// node `u` is addressed by the id `offsets[u] + i`.
+#[derive(Clone, Debug)]
+pub struct Overlay {
+    offsets: Vec<usize>,
+    banned_nodes: BitSet,
+    banned_edges: BitSet,
+    weight_overrides: HashMap<usize, usize>,
+}
+
+impl Overlay {
+    pub fn new<G: Neighbors + ?Sized>(graph: &G) -> Self {
+        let mut offsets = Vec::with_capacity(graph.num_nodes() + 1);
+        offsets.push(0);
+        for node in 0..graph.num_nodes() {
+            offsets.push(offsets.last().unwrap() + graph.degree(node));
+        }
+        let num_edges = *offsets.last().unwrap();
+        Overlay {
+            offsets,
+            banned_nodes: BitSet::new(graph.num_nodes()),
+            banned_edges: BitSet::new(num_edges),
+            weight_overrides: HashMap::new(),
+        }
+    }
+
+    pub fn edge_id(&self, u: usize, index: usize) -> usize {
+        self.offsets[u] + index
+    }
+
+    pub fn ban_node(&mut self, node: usize) {
+        self.banned_nodes.insert(node);
+    }
+
+    pub fn unban_node(&mut self, node: usize) {
+        self.banned_nodes.remove(node);
+    }
+
+    pub fn ban_edge_id(&mut self, edge: usize) {
+        self.banned_edges.insert(edge);
+    }
+
+    /// Bans every `u -> v` edge of `graph`.
+    pub fn ban_edge<G: Neighbors + ?Sized>(&mut self, graph: &G, u: usize, v: usize) {
+        for (i, _) in graph.neighbors(u).enumerate().filter(|&(_, (t, _))| t == v) {
+            self.banned_edges.insert(self.offsets[u] + i);
+        }
+    }
+
+    /// Replaces the weight of every `u -> v` edge of `graph`.
+    pub fn override_weight<G: Neighbors + ?Sized>(&mut self, graph: &G, u: usize, v: usize, weight: usize) {
+        for (i, _) in graph.neighbors(u).enumerate().filter(|&(_, (t, _))| t == v) {
+            self.weight_overrides.insert(self.offsets[u] + i, weight);
+        }
+    }
+
+    pub fn is_node_banned(&self, node: usize) -> bool {
+        self.banned_nodes.contains(node)
+    }
+
+    pub fn is_edge_banned(&self, edge: usize) -> bool {
+        self.banned_edges.contains(edge)
+    }
+
+    /// Weight of edge `index` of `u` (pointing to `v`) under this overlay, or
+    /// `None` if the edge or one of its endpoints is banned.
+    pub fn edge_weight(&self, u: usize, index: usize, v: usize, weight: usize) -> Option<usize> {
+        let edge = self.offsets[u] + index;
+        if self.banned_edges.contains(edge) || self.banned_nodes.contains(u) || self.banned_nodes.contains(v) {
+            return None;
+        }
+        Some(self.weight_overrides.get(&edge).copied().unwrap_or(weight))
+    }
+
+    /// Reverse adjacency with this overlay already applied, for the backward
+    /// half of bidirectional searches.
+    pub fn reverse_adj_list<G: Neighbors + ?Sized>(&self, graph: &G) -> Vec<Vec<(usize, usize)>> {
+        let mut rev_adj_list = vec![Vec::new(); graph.num_nodes()];
+        for node in 0..graph.num_nodes() {
+            for (i, (neighbor, weight)) in graph.neighbors(node).enumerate() {
+                if let Some(weight) = self.edge_weight(node, i, neighbor, weight) {
+                    rev_adj_list[neighbor].push((node, weight));
+                }
+            }
+        }
+        rev_adj_list
+    }
+}
+
+/// `Overlay::edge_weight` for an optional overlay; the plain searches pass `None`.
+#[inline]
+pub(crate) fn effective_weight(overlay: Option<&Overlay>, u: usize, index: usize, v: usize, weight: usize) -> Option<usize> {
+    match overlay {
+        Some(overlay) => overlay.edge_weight(u, index, v, weight),
+        None => Some(weight),
+    }
+}
+
+/// Whether a query endpoint is banned, making the query unreachable.
+pub(crate) fn is_banned(overlay: Option<&Overlay>, node: usize) -> bool {
+    overlay.is_some_and(|overlay| overlay.is_node_banned(node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitset() {
+        let mut set = BitSet::new(130);
+        set.insert(0);
+        set.insert(64);
+        set.insert(129);
+        assert!(set.contains(64) && set.contains(129));
+        assert!(!set.contains(1) && !set.contains(1000));
+        set.remove(64);
+        assert_eq!(set.count(), 2);
+    }
+
+    #[test]
+    fn test_edge_weight() {
+        let graph = vec![
+            vec![(1, 2), (2, 4), (1, 7)],  // Node 0, with a parallel edge to 1
+            vec![(2, 1)],                  // Node 1
+            vec![],                        // Node 2
+        ];
+        let mut overlay = Overlay::new(&graph);
+        overlay.ban_edge(&graph, 0, 1);
+        overlay.override_weight(&graph, 0, 2, 9);
+        assert_eq!(overlay.edge_weight(0, 0, 1, 2), None);
+        assert_eq!(overlay.edge_weight(0, 2, 1, 7), None);
+        assert_eq!(overlay.edge_weight(0, 1, 2, 4), Some(9));
+        assert_eq!(overlay.edge_weight(1, 0, 2, 1), Some(1));
+        overlay.ban_node(2);
+        assert_eq!(overlay.edge_weight(1, 0, 2, 1), None);
+        assert_eq!(overlay.reverse_adj_list(&graph), vec![vec![], vec![], vec![]]);
+    }
+
+    #[test]
+    fn test_queries_share_graph_across_threads() {
+        use crate::{
+            bidirectional_dijkstra_with_overlay, parallel_bidirectional_dijkstra_with_overlay,
+            parallel_dijkstra_with_overlay, sequential_dijkstra_with_overlay, ParallelConfig,
+        };
+        let graph = vec![
+            vec![(1, 5), (2, 1), (3, 10)],     // Node 0
+            vec![(0, 5), (2, 3), (4, 1)],      // Node 1
+            vec![(0, 1), (1, 3), (3, 4), (4, 8)],  // Node 2
+            vec![(0, 10), (2, 4), (4, 2)],     // Node 3
+            vec![(1, 1), (2, 8), (3, 2), (5, 6)],  // Node 4
+            vec![(4, 6)],                      // Node 5
+        ];
+        type Search = fn(&[Vec<(usize, usize)>], &Overlay, usize, usize) -> (usize, Vec<usize>);
+        let searches: [Search; 4] = [
+            sequential_dijkstra_with_overlay,
+            bidirectional_dijkstra_with_overlay,
+            |graph, overlay, start, goal| parallel_dijkstra_with_overlay(graph, overlay, &ParallelConfig::default(), start, goal),
+            |graph, overlay, start, goal| parallel_bidirectional_dijkstra_with_overlay(graph, overlay, &ParallelConfig::default(), start, goal),
+        ];
+
+        let unchanged = Overlay::new(&graph);
+        let mut closed_node = Overlay::new(&graph);
+        closed_node.ban_node(1);  // Forces 0 -> 2 -> 3 -> 4 -> 5
+        let mut slow_edge = Overlay::new(&graph);
+        slow_edge.override_weight(&graph, 2, 1, 10);  // Forces 0 -> 1 -> 4 -> 5
+        let mut cut = Overlay::new(&graph);
+        cut.ban_edge(&graph, 4, 5);
+
+        let cases = [(&unchanged, 11), (&closed_node, 13), (&slow_edge, 12), (&cut, usize::MAX)];
+        std::thread::scope(|scope| {
+            for (overlay, expected) in cases {
+                for search in searches {
+                    let graph = &graph;
+                    scope.spawn(move || assert_eq!(search(graph, overlay, 0, 5).0, expected));
+                }
+            }
+        });
+    }
+}
@@ -1,52 +1,68 @@
-use rayon::prelude::*;
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
 use std::sync::{Arc, Mutex};
-use rand::{distributions::{Distribution, Uniform}, SeedableRng, rngs::StdRng, Rng};
+use std::borrow::Cow;
+use crate::graph::{Adjacency, Neighbors, Reversed};
+use crate::parallel_config::ParallelConfig;
+use crate::overlay::{effective_weight, is_banned, Overlay};
+use crate::priority_queue::{with_queue, LazyBinaryHeap, PriorityQueue, QueueKind};
+use crate::search_stats::{record_settled, SearchStats};
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: usize,
+pub fn parallel_bidirectional_dijkstra<G: Adjacency + Sync + ?Sized>(graph: &G, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, None, &ParallelConfig::default(), start, goal)
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
+/// `parallel_bidirectional_dijkstra_with_config` with the bans and weight
+/// overrides of `overlay`.
+pub fn parallel_bidirectional_dijkstra_with_overlay<G: Adjacency + Sync + ?Sized>(graph: &G, overlay: &Overlay, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, Some(overlay), None, config, start, goal)
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+/// `parallel_bidirectional_dijkstra_with_config` that counts its work in `stats`.
+pub fn parallel_bidirectional_dijkstra_with_stats<G: Adjacency + Sync + ?Sized>(graph: &G, stats: &SearchStats, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, Some(stats), config, start, goal)
 }
 
-fn reverse_adj_list(adj_list: &[Vec<(usize, usize)>]) -> Vec<Vec<(usize, usize)>> {
-    let mut rev_adj_list = vec![Vec::new(); adj_list.len()];
-    for (node, edges) in adj_list.iter().enumerate() {
-        for &(neighbor, weight) in edges {
-            rev_adj_list[neighbor].push((node, weight));
-        }
+/// `parallel_bidirectional_dijkstra` on the thread pool of `config`, falling
+/// back to `bidirectional_dijkstra` on graphs below its sequential threshold.
+/// It always runs one task per direction, whatever `num_threads` says.
+pub fn parallel_bidirectional_dijkstra_with_config<G: Adjacency + Sync + ?Sized>(graph: &G, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, None, config, start, goal)
+}
+
+/// `parallel_bidirectional_dijkstra_with_config` with both directions on the
+/// priority queue `queue`.
+pub fn parallel_bidirectional_dijkstra_with_queue<G: Adjacency + Sync + ?Sized>(graph: &G, queue: QueueKind, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    with_queue!(queue, search(graph, None, None, config, start, goal))
+}
+
+fn search<G: Adjacency + Sync + ?Sized, Q: PriorityQueue + Send>(graph: &G, overlay: Option<&Overlay>, stats: Option<&SearchStats>, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    if config.is_sequential(graph.num_nodes()) {
+        return crate::bidirectional_dijkstra::search::<_, Q>(graph, overlay, stats, start, goal);
     }
-    rev_adj_list
+    config.install(|| race::<_, Q>(graph, overlay, stats, start, goal))
 }
 
-pub fn parallel_bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usize, goal: usize) -> (usize, Vec<usize>) {
+/// Forward and backward searches as two tasks on the current rayon pool.
+fn race<G: Adjacency + Sync + ?Sized, Q: PriorityQueue + Send>(graph: &G, overlay: Option<&Overlay>, stats: Option<&SearchStats>, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    if is_banned(overlay, start) || is_banned(overlay, goal) {
+        return (usize::MAX, Vec::new());
+    }
     if start == goal {
         return (0, vec![start]);
     }
 
-    let rev_graph = reverse_adj_list(graph);
-    let graph = Arc::new(graph.to_vec());
-    let rev_graph = Arc::new(rev_graph);
-    let dist_fwd = Arc::new(Mutex::new(vec![usize::MAX; graph.len()]));
-    let dist_bwd = Arc::new(Mutex::new(vec![usize::MAX; graph.len()]));
-    let heap_fwd = Arc::new(Mutex::new(BinaryHeap::new()));
-    let heap_bwd = Arc::new(Mutex::new(BinaryHeap::new()));
-    let prev_fwd = Arc::new(Mutex::new(vec![None; graph.len()]));
-    let prev_bwd = Arc::new(Mutex::new(vec![None; graph.len()]));
+    // An undirected graph, `Graph` or `MappedGraph` lends its stored reverse
+    // adjacency; the overlay needs its own copy with bans and overrides applied.
+    let rev_graph = match overlay {
+        Some(overlay) => Reversed::Lists(Cow::Owned(overlay.reverse_adj_list(graph))),
+        None => graph.reversed(),
+    };
+    let rev_graph = &rev_graph;
+    let dist_fwd = Arc::new(Mutex::new(vec![usize::MAX; graph.num_nodes()]));
+    let dist_bwd = Arc::new(Mutex::new(vec![usize::MAX; graph.num_nodes()]));
+    let heap_fwd = Arc::new(Mutex::new(Q::new(graph.num_nodes())));
+    let heap_bwd = Arc::new(Mutex::new(Q::new(graph.num_nodes())));
+    let prev_fwd = Arc::new(Mutex::new(vec![None; graph.num_nodes()]));
+    let prev_bwd = Arc::new(Mutex::new(vec![None; graph.num_nodes()]));
     let estimate = Arc::new(Mutex::new(usize::MAX));
     let join_node = Arc::new(Mutex::new(None));
 
@@ -58,8 +74,8 @@ pub fn parallel_bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usi
         
         dist_fwd[start] = 0;
         dist_bwd[goal] = 0;
-        heap_fwd.push(State { cost: 0, position: start });
-        heap_bwd.push(State { cost: 0, position: goal });
+        heap_fwd.push_or_decrease(start, 0);
+        heap_bwd.push_or_decrease(goal, 0);
     }
 
     let forward_done = Arc::new(Mutex::new(false));
@@ -71,14 +87,15 @@ pub fn parallel_bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usi
         let dist_fwd_fwd = Arc::clone(&dist_fwd);
         let dist_bwd_fwd = Arc::clone(&dist_bwd);
         let heap_fwd_fwd = Arc::clone(&heap_fwd);
+        let heap_bwd_fwd = Arc::clone(&heap_bwd);
         let prev_fwd_fwd = Arc::clone(&prev_fwd);
         let estimate_fwd = Arc::clone(&estimate);
         let join_node_fwd = Arc::clone(&join_node);
-        let graph_fwd = Arc::clone(&graph);
+        let graph_fwd = graph;
 
         s.spawn(move |_| {
             while !*forward_done_fwd.lock().unwrap() {
-                if let Some(State { cost, position }) = {
+                if let Some((cost, position)) = {
                     let mut heap_fwd = heap_fwd_fwd.lock().unwrap();
                     heap_fwd.pop()
                 } {
@@ -88,7 +105,19 @@ pub fn parallel_bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usi
                             continue;
                         }
                     }
-                    for &(neighbor, weight) in &graph_fwd[position] {
+                    // No path through an unsettled node can beat the estimate
+                    // once the two frontiers add up to it.
+                    let other_min = heap_bwd_fwd.lock().unwrap().peek().map_or(0, |(key, _)| key);
+                    if cost.saturating_add(other_min) >= *estimate_fwd.lock().unwrap() {
+                        *forward_done_fwd.lock().unwrap() = true;
+                        *backward_done_fwd.lock().unwrap() = true;
+                        continue;
+                    }
+                    record_settled(stats);
+                    for (i, (neighbor, weight)) in graph_fwd.neighbors(position).enumerate() {
+                        let Some(weight) = effective_weight(overlay, position, i, neighbor, weight) else {
+                            continue;
+                        };
                         let next_cost = cost.saturating_add(weight);
                         let mut should_continue = false;
                         {
@@ -99,27 +128,15 @@ pub fn parallel_bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usi
                             }
                         }
                         if should_continue {
-                            let mut heap_fwd = heap_fwd_fwd.lock().unwrap();
-                            heap_fwd.push(State { cost: next_cost, position: neighbor });
-                            let mut prev_fwd = prev_fwd_fwd.lock().unwrap();
-                            prev_fwd[neighbor] = Some(position);
-                        }
-                    }
-                    {
-                        let dist_bwd = dist_bwd_fwd.lock().unwrap();
-                        if let Some(&rev_cost) = dist_bwd.get(position) {
-                            if rev_cost != usize::MAX {
-                                let total_cost = {
-                                    let dist_fwd = dist_fwd_fwd.lock().unwrap();
-                                    dist_fwd[position].saturating_add(rev_cost)
-                                };
+                            heap_fwd_fwd.lock().unwrap().push_or_decrease(neighbor, next_cost);
+                            prev_fwd_fwd.lock().unwrap()[neighbor] = Some(position);
+                            // Meeting the other side on an edge may improve the estimate
+                            let other_cost = dist_bwd_fwd.lock().unwrap()[neighbor];
+                            if other_cost != usize::MAX {
                                 let mut estimate = estimate_fwd.lock().unwrap();
-                                if total_cost < *estimate {
-                                    *estimate = total_cost;
-                                    let mut join_node = join_node_fwd.lock().unwrap();
-                                    *join_node = Some(position);
-                                    *forward_done_fwd.lock().unwrap() = true;
-                                    *backward_done_fwd.lock().unwrap() = true;
+                                if next_cost.saturating_add(other_cost) < *estimate {
+                                    *estimate = next_cost.saturating_add(other_cost);
+                                    *join_node_fwd.lock().unwrap() = Some(neighbor);
                                 }
                             }
                         }
@@ -135,14 +152,15 @@ pub fn parallel_bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usi
         let dist_fwd_bwd = Arc::clone(&dist_fwd);
         let dist_bwd_bwd = Arc::clone(&dist_bwd);
         let heap_bwd_bwd = Arc::clone(&heap_bwd);
+        let heap_fwd_bwd = Arc::clone(&heap_fwd);
         let prev_bwd_bwd = Arc::clone(&prev_bwd);
         let estimate_bwd = Arc::clone(&estimate);
         let join_node_bwd = Arc::clone(&join_node);
-        let rev_graph_bwd = Arc::clone(&rev_graph);
+        let rev_graph_bwd = rev_graph;
 
         s.spawn(move |_| {
             while !*backward_done_bwd.lock().unwrap() {
-                if let Some(State { cost, position }) = {
+                if let Some((cost, position)) = {
                     let mut heap_bwd = heap_bwd_bwd.lock().unwrap();
                     heap_bwd.pop()
                 } {
@@ -152,7 +170,16 @@ pub fn parallel_bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usi
                             continue;
                         }
                     }
-                    for &(neighbor, weight) in &rev_graph_bwd[position] {
+                    // No path through an unsettled node can beat the estimate
+                    // once the two frontiers add up to it.
+                    let other_min = heap_fwd_bwd.lock().unwrap().peek().map_or(0, |(key, _)| key);
+                    if cost.saturating_add(other_min) >= *estimate_bwd.lock().unwrap() {
+                        *forward_done_bwd.lock().unwrap() = true;
+                        *backward_done_bwd.lock().unwrap() = true;
+                        continue;
+                    }
+                    record_settled(stats);
+                    for (neighbor, weight) in rev_graph_bwd.neighbors(position) {
                         let next_cost = cost.saturating_add(weight);
                         let mut should_continue = false;
                         {
@@ -163,27 +190,15 @@ pub fn parallel_bidirectional_dijkstra(graph: &[Vec<(usize, usize)>], start: usi
                             }
                         }
                         if should_continue {
-                            let mut heap_bwd = heap_bwd_bwd.lock().unwrap();
-                            heap_bwd.push(State { cost: next_cost, position: neighbor });
-                            let mut prev_bwd = prev_bwd_bwd.lock().unwrap();
-                            prev_bwd[neighbor] = Some(position);
-                        }
-                    }
-                    {
-                        let dist_fwd = dist_fwd_bwd.lock().unwrap();
-                        if let Some(&fwd_cost) = dist_fwd.get(position) {
-                            if fwd_cost != usize::MAX {
-                                let total_cost = {
-                                    let dist_bwd = dist_bwd_bwd.lock().unwrap();
-                                    dist_bwd[position].saturating_add(fwd_cost)
-                                };
+                            heap_bwd_bwd.lock().unwrap().push_or_decrease(neighbor, next_cost);
+                            prev_bwd_bwd.lock().unwrap()[neighbor] = Some(position);
+                            // Meeting the other side on an edge may improve the estimate
+                            let other_cost = dist_fwd_bwd.lock().unwrap()[neighbor];
+                            if other_cost != usize::MAX {
                                 let mut estimate = estimate_bwd.lock().unwrap();
-                                if total_cost < *estimate {
-                                    *estimate = total_cost;
-                                    let mut join_node = join_node_bwd.lock().unwrap();
-                                    *join_node = Some(position);
-                                    *forward_done_bwd.lock().unwrap() = true;
-                                    *backward_done_bwd.lock().unwrap() = true;
+                                if next_cost.saturating_add(other_cost) < *estimate {
+                                    *estimate = next_cost.saturating_add(other_cost);
+                                    *join_node_bwd.lock().unwrap() = Some(neighbor);
                                 }
                             }
                         }
@@ -227,9 +242,8 @@ fn reconstruct_path(goal: usize, prev: &[Option<usize>]) -> Vec<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::distributions::{Distribution, Uniform};
-    use rand::rngs::StdRng;
-    use rand::SeedableRng;
+    use crate::generators::{self, Weights};
+    use crate::graph::{Graph, UndirectedGraph};
     use std::time::Instant;
 
     #[test]
@@ -299,21 +313,7 @@ mod tests {
     }
 
     fn generate_random_graph(nodes: usize, edges: usize) -> Vec<Vec<(usize, usize)>> {
-        let mut graph = vec![Vec::new(); nodes];
-        let mut rng = StdRng::seed_from_u64(42); // Seed for reproducibility
-        let range = Uniform::from(0..nodes);
-        let weight_range = Uniform::from(1..100); // Random weights between 1 and 100
-
-        for _ in 0..edges {
-            let u = range.sample(&mut rng);
-            let v = range.sample(&mut rng);
-            if u != v {
-                let weight = weight_range.sample(&mut rng);
-                graph[u].push((v, weight));
-            }
-        }
-
-        graph
+        generators::gnm(nodes, edges, Weights::Uniform(1, 99), 42).adj_list
     }
 
     #[test]
@@ -355,4 +355,60 @@ mod tests {
         println!("Par-Bi-Test Large Very Complex Graph - Time elapsed: {:?}", duration);
         println!("Cost: {}, Path length: {}", cost, path.len());
     }
+
+    #[test]
+    fn test_undirected_graph() {
+        let graph = UndirectedGraph::from_edges(6, vec![
+            (0, 1, 7), (0, 2, 9), (0, 5, 14),
+            (1, 2, 10), (1, 3, 15),
+            (2, 3, 11), (2, 5, 2),
+            (3, 4, 6), (4, 5, 9),
+        ]);
+        let start_time = Instant::now();
+        let (cost, path) = parallel_bidirectional_dijkstra(&graph, 0, 4);
+        let duration = start_time.elapsed();
+        println!("Par-Bi-Test Undirected Graph - Time elapsed: {:?}", duration);
+        assert_eq!(cost, 20);  // Shortest path cost: 20
+        assert_eq!(path, vec![0, 2, 5, 4]);  // Shortest path: 0 -> 2 -> 5 -> 4
+        assert_eq!(parallel_bidirectional_dijkstra(&graph, 4, 0), (20, vec![4, 5, 2, 0]));
+    }
+
+    #[test]
+    fn test_graph_reuses_reverse_adjacency() {
+        let graph = Graph::new(vec![
+            vec![(1, 5), (2, 1), (3, 10)],     // Node 0
+            vec![(0, 5), (2, 3), (4, 1)],      // Node 1
+            vec![(0, 1), (1, 3), (3, 4), (4, 8)],  // Node 2
+            vec![(0, 10), (2, 4), (4, 2)],     // Node 3
+            vec![(1, 1), (2, 8), (3, 2), (5, 6)],  // Node 4
+            vec![(4, 6)],                      // Node 5
+        ]);
+        assert!(matches!(graph.rev_adj_list(), Cow::Borrowed(_)));
+        assert_eq!(parallel_bidirectional_dijkstra(&graph, 0, 5), (11, vec![0, 2, 1, 4, 5]));
+
+        let undirected = UndirectedGraph::from_edges(3, vec![(0, 1, 4), (1, 2, 5)]);
+        assert!(matches!(undirected.rev_adj_list(), Cow::Borrowed(_)));
+        assert_eq!(parallel_bidirectional_dijkstra(&undirected, 2, 0), (9, vec![2, 1, 0]));
+    }
+
+    #[test]
+    fn test_two_threads_find_the_shortest_path() {
+        // Two real threads, so the directions actually race each other
+        let config = ParallelConfig::new().dedicated_pool(2).unwrap().sequential_threshold(0);
+        let grid = generators::grid(40, 40, Weights::Uniform(1, 9), 3);
+        let gnm = generators::gnm(2_000, 8_000, Weights::Uniform(1, 99), 7);
+        for (graph, goals) in [(&grid, [1599, 820, 39]), (&gnm, [1999, 1000, 17])] {
+            for goal in goals {
+                let expected = crate::standard_dijkstra::sequential_dijkstra(graph, 0, goal).0;
+                for _ in 0..20 {
+                    let (cost, path) = parallel_bidirectional_dijkstra_with_config(graph, &config, 0, goal);
+                    assert_eq!(cost, expected);
+                    let walked: usize = path.windows(2)
+                        .map(|pair| graph.neighbors(pair[0]).find(|&(node, _)| node == pair[1]).unwrap().1)
+                        .sum();
+                    assert_eq!(walked, expected);
+                }
+            }
+        }
+    }
 }
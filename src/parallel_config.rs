@@ -0,0 +1,152 @@
+use std::sync::Arc;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+/// How the parallel searches use threads: how many tasks to start, which rayon
+/// pool to run them on, and below what size to skip the parallel machinery.
+///
+/// The default reproduces the fixed behaviour of the plain entry points: four
+/// tasks on the global rayon pool, with no sequential fallback.
+#[derive(Clone, Debug)]
+pub struct ParallelConfig {
+    num_threads: usize,
+    pool: Option<Arc<ThreadPool>>,
+    grain_size: usize,
+    sequential_threshold: usize,
+}
+
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        ParallelConfig {
+            num_threads: 4,
+            pool: None,
+            grain_size: 1,
+            sequential_threshold: 0,
+        }
+    }
+}
+
+impl ParallelConfig {
+    pub fn new() -> Self {
+        ParallelConfig::default()
+    }
+
+    /// Number of tasks `parallel_dijkstra` starts. Clamped to at least one.
+    /// `parallel_bidirectional_dijkstra` ignores it and always runs two
+    /// tasks, one per direction.
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = num_threads.max(1);
+        self
+    }
+
+    /// Runs the searches on `pool` instead of the global rayon pool, keeping
+    /// them apart from the rest of the application's rayon work.
+    pub fn thread_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Builds a dedicated pool of `num_threads` threads and starts that many
+    /// tasks on it.
+    pub fn dedicated_pool(self, num_threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|i| format!("dijkstra-{}", i))
+            .build()?;
+        Ok(self.num_threads(num_threads).thread_pool(Arc::new(pool)))
+    }
+
+    /// Minimum number of nodes per task; smaller graphs start fewer tasks.
+    pub fn grain_size(mut self, nodes: usize) -> Self {
+        self.grain_size = nodes.max(1);
+        self
+    }
+
+    /// Graphs with fewer nodes than this are searched sequentially, where
+    /// spawning tasks would cost more than it saves.
+    pub fn sequential_threshold(mut self, nodes: usize) -> Self {
+        self.sequential_threshold = nodes;
+        self
+    }
+
+    pub fn pool(&self) -> Option<&Arc<ThreadPool>> {
+        self.pool.as_ref()
+    }
+
+    /// Runs `f` on the configured pool, or on the current one if there is none.
+    pub fn install<R: Send, F: FnOnce() -> R + Send>(&self, f: F) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(f),
+            None => f(),
+        }
+    }
+
+    pub(crate) fn is_sequential(&self, num_nodes: usize) -> bool {
+        num_nodes < self.sequential_threshold
+    }
+
+    pub(crate) fn tasks(&self, num_nodes: usize) -> usize {
+        self.num_threads.min((num_nodes / self.grain_size).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{grid, Weights};
+    use crate::{parallel_bidirectional_dijkstra_with_config, parallel_dijkstra_with_config, sequential_dijkstra};
+    use crate::{parallel_bidirectional_dijkstra_with_overlay, parallel_bidirectional_dijkstra_with_queue, parallel_dijkstra_with_overlay, parallel_dijkstra_with_queue};
+    use crate::{parallel_dijkstra_with_stats, sequential_dijkstra_with_overlay, sequential_dijkstra_with_stats, Overlay, QueueKind, SearchStats};
+
+    #[test]
+    fn test_tasks_and_fallback() {
+        let config = ParallelConfig::new();
+        assert_eq!(config.tasks(1_000), 4);
+        assert!(!config.is_sequential(0));
+
+        let config = ParallelConfig::new().num_threads(8).grain_size(100).sequential_threshold(50);
+        assert_eq!(config.tasks(10_000), 8);
+        assert_eq!(config.tasks(250), 2);
+        assert_eq!(config.tasks(60), 1);
+        assert!(config.is_sequential(49));
+        assert!(!config.is_sequential(50));
+    }
+
+    #[test]
+    fn test_dedicated_pool() {
+        let graph = grid(40, 40, Weights::Uniform(1, 9), 3);
+        let expected = sequential_dijkstra(&graph, 0, 1599);
+        let config = ParallelConfig::new().dedicated_pool(3).unwrap();
+        assert_eq!(config.pool().unwrap().current_num_threads(), 3);
+        assert_eq!(config.install(rayon::current_num_threads), 3);
+        assert_eq!(parallel_dijkstra_with_config(&graph, &config, 0, 1599), expected);
+
+        // Below the threshold the bidirectional search runs sequentially
+        let config = config.sequential_threshold(graph.num_nodes() + 1);
+        assert_eq!(parallel_bidirectional_dijkstra_with_config(&graph, &config, 0, 1599).0, expected.0);
+    }
+
+    #[test]
+    fn test_config_reaches_every_entry_point() {
+        let graph = grid(40, 40, Weights::Uniform(1, 9), 3);
+        let stats = SearchStats::new();
+        sequential_dijkstra_with_stats(&graph, &stats, 0, 1599);
+        let sequential = stats.settled();
+
+        // One task settles exactly what the sequential search does
+        let single = ParallelConfig::new().num_threads(1);
+        stats.reset();
+        parallel_dijkstra_with_stats(&graph, &stats, &single, 0, 1599);
+        assert_eq!(stats.settled(), sequential);
+
+        let pool = ParallelConfig::new().dedicated_pool(2).unwrap();
+        let mut overlay = Overlay::new(&graph);
+        overlay.ban_node(1);
+        let expected = sequential_dijkstra_with_overlay(&graph, &overlay, 0, 1599).0;
+        assert_eq!(parallel_dijkstra_with_overlay(&graph, &overlay, &pool, 0, 1599).0, expected);
+        assert_eq!(parallel_bidirectional_dijkstra_with_overlay(&graph, &overlay, &pool, 0, 1599).0, expected);
+        for queue in QueueKind::ALL {
+            assert_eq!(parallel_dijkstra_with_queue(&graph, queue, &single, 0, 1599).0, sequential_dijkstra(&graph, 0, 1599).0);
+            assert_eq!(parallel_bidirectional_dijkstra_with_queue(&graph, queue, &pool, 0, 1599).0, sequential_dijkstra(&graph, 0, 1599).0);
+        }
+    }
+}
@@ -1,50 +1,63 @@
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
 use rayon::prelude::*;
 use std::sync::{Arc, Mutex};
-use rand::{distributions::{Distribution, Uniform}, SeedableRng, rngs::StdRng, Rng};
+use crate::graph::Neighbors;
+use crate::parallel_config::ParallelConfig;
+use crate::priority_queue::{with_queue, LazyBinaryHeap, PriorityQueue, QueueKind};
+use crate::overlay::{effective_weight, is_banned, Overlay};
+use crate::search_stats::{record_settled, SearchStats};
 
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: usize,
+pub fn parallel_dijkstra<G: Neighbors + Sync + ?Sized>(graph: &G, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, None, &ParallelConfig::default(), start, goal)
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
+/// `parallel_dijkstra_with_config` with the bans and weight overrides of `overlay`.
+pub fn parallel_dijkstra_with_overlay<G: Neighbors + Sync + ?Sized>(graph: &G, overlay: &Overlay, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, Some(overlay), None, config, start, goal)
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+/// `parallel_dijkstra_with_config` that counts its work in `stats`.
+pub fn parallel_dijkstra_with_stats<G: Neighbors + Sync + ?Sized>(graph: &G, stats: &SearchStats, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, Some(stats), config, start, goal)
+}
+
+/// `parallel_dijkstra` with the task count, thread pool and sequential
+/// fallback of `config`.
+pub fn parallel_dijkstra_with_config<G: Neighbors + Sync + ?Sized>(graph: &G, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, None, config, start, goal)
 }
 
-pub fn parallel_dijkstra(graph: &[Vec<(usize, usize)>], start: usize, goal: usize) -> (usize, Vec<usize>) {
+/// `parallel_dijkstra_with_config` with every task on the priority queue `queue`.
+pub fn parallel_dijkstra_with_queue<G: Neighbors + Sync + ?Sized>(graph: &G, queue: QueueKind, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    with_queue!(queue, search(graph, None, None, config, start, goal))
+}
+
+fn search<G: Neighbors + Sync + ?Sized, Q: PriorityQueue>(graph: &G, overlay: Option<&Overlay>, stats: Option<&SearchStats>, config: &ParallelConfig, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    if is_banned(overlay, start) || is_banned(overlay, goal) {
+        return (usize::MAX, Vec::new());
+    }
     if start == goal {
         return (0, vec![start]);
     }
 
-    let num_threads = 4;
-    let graph = Arc::new(graph.to_vec());
+    if config.is_sequential(graph.num_nodes()) {
+        return crate::standard_dijkstra::search::<_, Q>(graph, overlay, stats, start, goal);
+    }
+
+    let num_threads = config.tasks(graph.num_nodes());
     let results = Arc::new(Mutex::new(Vec::new()));
 
-    (0..num_threads).into_par_iter().for_each(|i| {
-        let graph = Arc::clone(&graph);
+    config.install(|| (0..num_threads).into_par_iter().for_each(|_| {
         let results = Arc::clone(&results);
 
-        let mut dist = vec![usize::MAX; graph.len()];
-        let mut heap = BinaryHeap::new();
-        let mut prev = vec![None; graph.len()];
+        let mut dist = vec![usize::MAX; graph.num_nodes()];
+        let mut heap = Q::new(graph.num_nodes());
+        let mut prev = vec![None; graph.num_nodes()];
 
         dist[start] = 0;
-        heap.push(State { cost: 0, position: start });
+        heap.push_or_decrease(start, 0);
 
-        while let Some(State { cost, position }) = heap.pop() {
+        while let Some((cost, position)) = heap.pop() {
             if position == goal {
                 let path = reconstruct_path(goal, &prev);
                 let mut results = results.lock().unwrap();
@@ -55,17 +68,21 @@ pub fn parallel_dijkstra(graph: &[Vec<(usize, usize)>], start: usize, goal: usiz
             if cost > dist[position] {
                 continue;
             }
+            record_settled(stats);
 
-            for &(neighbor, weight) in &graph[position] {
+            for (i, (neighbor, weight)) in graph.neighbors(position).enumerate() {
+                let Some(weight) = effective_weight(overlay, position, i, neighbor, weight) else {
+                    continue;
+                };
                 let next_cost = cost.saturating_add(weight);
                 if next_cost < dist[neighbor] {
                     dist[neighbor] = next_cost;
-                    heap.push(State { cost: next_cost, position: neighbor });
+                    heap.push_or_decrease(neighbor, next_cost);
                     prev[neighbor] = Some(position);
                 }
             }
         }
-    });
+    }));
 
     let results = results.lock().unwrap();
     if results.is_empty() {
@@ -86,30 +103,10 @@ fn reconstruct_path(goal: usize, prev: &[Option<usize>]) -> Vec<usize> {
     path.reverse();
     path
 }
-fn generate_large_graph(nodes: usize, edges_per_node: usize, max_weight: usize, seed: u64) -> Vec<Vec<(usize, usize)>> {
-    let mut rng = StdRng::seed_from_u64(seed);
-    let weight_dist = Uniform::from(1..=max_weight);
-
-    let mut graph = vec![Vec::new(); nodes];
-    for node in 0..nodes {
-        // Each node will have a random number of edges up to `edges_per_node`
-        let num_edges = rng.gen_range(1..=edges_per_node);
-        for _ in 0..num_edges {
-            let target = rng.gen_range(0..nodes);
-            let weight = weight_dist.sample(&mut rng);
-            // Ensure no self-loops or duplicate edges
-            if target != node && !graph[node].iter().any(|&(t, _)| t == target) {
-                graph[node].push((target, weight));
-            }
-        }
-    }
-
-    graph
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generators::{self, Weights};
     use std::time::Instant;
 
     #[test]
@@ -137,7 +134,7 @@ mod tests {
         ];
         let start_time = Instant::now();
 
-        let (cost, path) = parallel_dijkstra(&graph, 0, (3));
+        let (cost, path) = parallel_dijkstra(&graph, 0, 3);
         let duration = start_time.elapsed();
         println!("Test Larger Graph - Time elapsed: {:?}", duration);
 
@@ -156,7 +153,7 @@ mod tests {
         ];
         let start_time = Instant::now();
 
-        let (cost, path) = parallel_dijkstra(&graph, 0, (4));
+        let (cost, path) = parallel_dijkstra(&graph, 0, 4);
         let duration = start_time.elapsed();
         println!("Test Complex Graph - Time elapsed: {:?}", duration);
 
@@ -176,7 +173,7 @@ mod tests {
         ];
         let start_time = Instant::now();
 
-        let (cost, path) = parallel_dijkstra(&graph, 0, (5));
+        let (cost, path) = parallel_dijkstra(&graph, 0, 5);
         let duration = start_time.elapsed();
         println!("Test Very Complex Graph - Time elapsed: {:?}", duration);
 
@@ -191,10 +188,10 @@ mod tests {
         let max_weight = 100; // Maximum weight of 100
         let seed = 42; // Fixed seed for reproducibility
 
-        let graph = generate_large_graph(nodes, edges_per_node, max_weight, seed);
+        let graph = generators::gnm(nodes, nodes * edges_per_node / 2, Weights::Uniform(1, max_weight), seed).adj_list;
 
         let start = 0;
-        let goal = Some(nodes - nodes/3); // Assuming we want to find path from node 0 to the last node
+        let _goal = Some(nodes - nodes/3); // Assuming we want to find path from node 0 to the last node
         let start_time = std::time::Instant::now();
         let (cost, path) = parallel_dijkstra(&graph, start, nodes - 1);
         let duration = start_time.elapsed();
@@ -202,21 +199,7 @@ mod tests {
         println!("Test Huge Graph - Time elapsed: {:?}, Cost: {}, Path Length: {}", duration, cost, path.len());
     }
     fn generate_random_graph(nodes: usize, edges: usize) -> Vec<Vec<(usize, usize)>> {
-        let mut graph = vec![Vec::new(); nodes];
-        let mut rng = StdRng::seed_from_u64(42); // Seed for reproducibility
-        let range = Uniform::from(0..nodes);
-        let weight_range = Uniform::from(1..100); // Random weights between 1 and 100
-
-        for _ in 0..edges {
-            let u = range.sample(&mut rng);
-            let v = range.sample(&mut rng);
-            if u != v {
-                let weight = weight_range.sample(&mut rng);
-                graph[u].push((v, weight));
-            }
-        }
-
-        graph
+        generators::gnm(nodes, edges, Weights::Uniform(1, 99), 42).adj_list
     }
 
     #[test]
@@ -272,23 +255,29 @@ mod tests {
 
 
 
-}
-
-/*
- successes:
-
- ---- parallel_dijkstra::tests::test_simple_graph stdout ----
-Test Simple Graph - Time elapsed: 678.583µs
-
----- parallel_dijkstra::tests::test_larger_graph stdout ----
-Test Larger Graph - Time elapsed: 674.708µs
-
----- parallel_dijkstra::tests::test_disconnected_graph stdout ----
-Test Disconnected Graph - Time elapsed: 632.208µs
-
----- parallel_dijkstra::tests::test_complex_graph stdout ----
-Test Complex Graph - Time elapsed: 696.791µs
 
----- parallel_dijkstra::tests::test_very_complex_graph stdout ----
-Test Very Complex Graph - Time elapsed: 762.833µs
- */
+    #[test]
+    fn test_config_tasks_and_fallback() {
+        let graph = generators::grid(30, 30, Weights::Uniform(1, 9), 5);
+        let expected = crate::sequential_dijkstra(&graph, 0, 899);
+        let stats = SearchStats::new();
+        crate::sequential_dijkstra_with_stats(&graph, &stats, 0, 899);
+        let once = stats.settled();
+
+        // Every task runs the whole search, so the settled count scales with them
+        let config = ParallelConfig::new().num_threads(3);
+        stats.reset();
+        assert_eq!(search::<_, LazyBinaryHeap>(&graph, None, Some(&stats), &config, 0, 899), expected);
+        assert_eq!(stats.settled(), 3 * once);
+
+        let config = config.grain_size(450);
+        stats.reset();
+        assert_eq!(search::<_, LazyBinaryHeap>(&graph, None, Some(&stats), &config, 0, 899), expected);
+        assert_eq!(stats.settled(), 2 * once);
+
+        let config = config.sequential_threshold(1_000);
+        stats.reset();
+        assert_eq!(search::<_, LazyBinaryHeap>(&graph, None, Some(&stats), &config, 0, 899), expected);
+        assert_eq!(stats.settled(), once);
+    }
+}
@@ -0,0 +1,176 @@
+//! Conversions to and from `petgraph`, and a zero-copy adapter that lets the
+//! searches run on any petgraph graph through its visit traits.
+
+use petgraph::csr::Csr;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::{EdgeRef, IntoEdges, NodeIndexable};
+use crate::graph::{Adjacency, Graph, Neighbors};
+
+/// Views a petgraph graph as `Neighbors`, so `sequential_dijkstra`,
+/// `bidirectional_dijkstra` and the parallel variants run on it in place.
+/// Node `u` is `graph.from_index(u)`; `weight` maps an edge to its cost.
+///
+/// `graph` is a reference such as `&DiGraph<N, E>`, `&Csr<N, E>` or
+/// `&StableGraph<N, E>`. For undirected petgraph graphs every edge can be
+/// walked in both directions.
+#[derive(Copy, Clone, Debug)]
+pub struct PetgraphAdapter<G, F> {
+    graph: G,
+    weight: F,
+}
+
+impl<G, F> PetgraphAdapter<G, F>
+where
+    G: IntoEdges + NodeIndexable,
+    F: Fn(G::EdgeRef) -> usize,
+{
+    pub fn new(graph: G, weight: F) -> Self {
+        PetgraphAdapter { graph, weight }
+    }
+}
+
+impl<G, F> Neighbors for PetgraphAdapter<G, F>
+where
+    G: IntoEdges + NodeIndexable,
+    F: Fn(G::EdgeRef) -> usize,
+{
+    fn num_nodes(&self) -> usize {
+        self.graph.node_bound()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let graph = self.graph;
+        graph.edges(graph.from_index(node))
+            .map(move |edge| (graph.to_index(edge.target()), (self.weight)(edge)))
+    }
+}
+
+impl<G, F> Adjacency for PetgraphAdapter<G, F>
+where
+    G: IntoEdges + NodeIndexable,
+    F: Fn(G::EdgeRef) -> usize,
+{
+}
+
+/// Copies any petgraph graph into a `Graph`. Undirected petgraph graphs give
+/// one edge per direction.
+pub fn from_petgraph<G, F>(graph: G, weight: F) -> Graph
+where
+    G: IntoEdges + NodeIndexable,
+    F: Fn(G::EdgeRef) -> usize,
+{
+    let adapter = PetgraphAdapter::new(graph, weight);
+    Graph::new((0..adapter.num_nodes()).map(|u| adapter.neighbors(u).collect()).collect())
+}
+
+pub fn to_petgraph(graph: &Graph) -> DiGraph<(), usize> {
+    let mut petgraph = DiGraph::with_capacity(graph.num_nodes(), graph.num_edges());
+    for _ in 0..graph.num_nodes() {
+        petgraph.add_node(());
+    }
+    for (u, edges) in graph.adj_list.iter().enumerate() {
+        for &(v, weight) in edges {
+            petgraph.add_edge(NodeIndex::new(u), NodeIndex::new(v), weight);
+        }
+    }
+    petgraph
+}
+
+/// `Csr` allows only one edge per node pair, so parallel edges keep the
+/// cheapest weight.
+pub fn to_csr(graph: &Graph) -> Csr<(), usize> {
+    let mut edges: Vec<(u32, u32, usize)> = graph.adj_list.iter().enumerate()
+        .flat_map(|(u, edges)| edges.iter().map(move |&(v, weight)| (u as u32, v as u32, weight)))
+        .collect();
+    edges.sort_unstable();
+    edges.dedup_by_key(|&mut (u, v, _)| (u, v));
+
+    let mut csr = Csr::from_sorted_edges(&edges).expect("edges are sorted and unique");
+    while csr.node_count() < graph.num_nodes() {
+        csr.add_node(());
+    }
+    csr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bidirectional_dijkstra, parallel_bidirectional_dijkstra, parallel_dijkstra, sequential_dijkstra};
+    use petgraph::algo::dijkstra;
+    use petgraph::graph::UnGraph;
+    use petgraph::stable_graph::StableDiGraph;
+
+    fn sample() -> Graph {
+        Graph::new(vec![
+            vec![(1, 5), (2, 1), (3, 10)],     // Node 0
+            vec![(0, 5), (2, 3), (4, 1)],      // Node 1
+            vec![(0, 1), (1, 3), (3, 4), (4, 8)],  // Node 2
+            vec![(0, 10), (2, 4), (4, 2)],     // Node 3
+            vec![(1, 1), (2, 8), (3, 2), (5, 6)],  // Node 4
+            vec![(4, 6)],                      // Node 5
+            vec![],                            // Node 6, isolated
+        ])
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let graph = sample();
+        let petgraph = to_petgraph(&graph);
+        assert_eq!(petgraph.node_count(), 7);
+        assert_eq!(petgraph.edge_weights().sum::<usize>(), 80);
+        let back = from_petgraph(&petgraph, |e| *e.weight());
+        for (u, edges) in graph.adj_list.iter().enumerate() {
+            let mut read = back.adj_list[u].clone();
+            read.sort();
+            assert_eq!(&read, edges);
+        }
+
+        let csr = to_csr(&graph);
+        assert_eq!(csr.node_count(), 7);
+        assert_eq!(from_petgraph(&csr, |e| *e.weight()).adj_list, graph.adj_list);
+    }
+
+    #[test]
+    fn test_searches_on_petgraph() {
+        let graph = sample();
+        let petgraph = to_petgraph(&graph);
+        let adapter = PetgraphAdapter::new(&petgraph, |e: petgraph::graph::EdgeReference<usize>| *e.weight());
+        let expected = sequential_dijkstra(&graph.adj_list, 0, 5);
+        assert_eq!(expected.0, 11);
+        assert_eq!(sequential_dijkstra(&adapter, 0, 5), expected);
+        assert_eq!(bidirectional_dijkstra(&adapter, 0, 5).0, 11);
+        assert_eq!(parallel_dijkstra(&adapter, 0, 5).0, 11);
+        assert_eq!(parallel_bidirectional_dijkstra(&adapter, 0, 5).0, 11);
+        assert_eq!(sequential_dijkstra(&adapter, 0, 6).0, usize::MAX);
+
+        let petgraph_dist = dijkstra(&petgraph, NodeIndex::new(0), None, |e| *e.weight());
+        assert_eq!(petgraph_dist[&NodeIndex::new(5)], 11);
+
+        let csr = to_csr(&graph);
+        let adapter = PetgraphAdapter::new(&csr, |e: petgraph::csr::EdgeReference<usize, _, _>| *e.weight());
+        assert_eq!(bidirectional_dijkstra(&adapter, 0, 5).0, 11);
+    }
+
+    #[test]
+    fn test_undirected_and_stable_graphs() {
+        let mut ungraph = UnGraph::<&str, u32>::new_undirected();
+        let a = ungraph.add_node("a");
+        let b = ungraph.add_node("b");
+        let c = ungraph.add_node("c");
+        ungraph.add_edge(a, b, 2);
+        ungraph.add_edge(c, b, 3);
+        let adapter = PetgraphAdapter::new(&ungraph, |e: petgraph::graph::EdgeReference<u32>| *e.weight() as usize);
+        assert_eq!(sequential_dijkstra(&adapter, 2, 0), (5, vec![2, 1, 0]));
+        assert_eq!(from_petgraph(&ungraph, |e| *e.weight() as usize).num_edges(), 4);
+
+        // Removing a node leaves a hole in a `StableGraph`'s index space.
+        let mut stable = StableDiGraph::<(), usize>::new();
+        let nodes: Vec<_> = (0..4).map(|_| stable.add_node(())).collect();
+        stable.add_edge(nodes[0], nodes[1], 1);
+        stable.add_edge(nodes[1], nodes[3], 1);
+        stable.add_edge(nodes[0], nodes[3], 5);
+        stable.remove_node(nodes[2]);
+        let adapter = PetgraphAdapter::new(&stable, |e: petgraph::stable_graph::EdgeReference<usize>| *e.weight());
+        assert_eq!(sequential_dijkstra(&adapter, 0, 3), (2, vec![0, 1, 3]));
+    }
+}
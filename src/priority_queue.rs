@@ -0,0 +1,516 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A min-priority queue of node ids `0..num_nodes` keyed by distance, with
+/// decrease-key. A node is queued at most once; after it is popped it may be
+/// pushed again.
+pub trait PriorityQueue {
+    fn new(num_nodes: usize) -> Self;
+
+    /// Queues `node` with `key`, or lowers its key if it is already queued
+    /// with a larger one. A key no lower than the queued one is ignored.
+    fn push_or_decrease(&mut self, node: usize, key: usize);
+
+    /// Removes the node with the smallest key and returns `(key, node)`.
+    fn pop(&mut self) -> Option<(usize, usize)>;
+
+    /// The `(key, node)` `pop` would return. Takes `&mut self` so lazy
+    /// queues can drop stale entries on the way.
+    fn peek(&mut self) -> Option<(usize, usize)>;
+
+    fn is_empty(&mut self) -> bool {
+        self.peek().is_none()
+    }
+}
+
+const NOT_QUEUED: usize = usize::MAX;
+
+/// `std::collections::BinaryHeap` with lazy deletion: a decrease pushes a
+/// second entry and the outdated one is skipped when it surfaces. This is
+/// what the searches used before the queue became pluggable, down to the
+/// tie-break: of two equal keys, the larger node id is popped first.
+#[derive(Clone, Debug)]
+pub struct LazyBinaryHeap {
+    heap: BinaryHeap<(Reverse<usize>, usize)>,
+    keys: Vec<usize>,
+}
+
+impl LazyBinaryHeap {
+    fn skip_stale(&mut self) {
+        while let Some(&(Reverse(key), node)) = self.heap.peek() {
+            if self.keys[node] == key {
+                break;
+            }
+            self.heap.pop();
+        }
+    }
+}
+
+impl PriorityQueue for LazyBinaryHeap {
+    fn new(num_nodes: usize) -> Self {
+        LazyBinaryHeap { heap: BinaryHeap::new(), keys: vec![NOT_QUEUED; num_nodes] }
+    }
+
+    fn push_or_decrease(&mut self, node: usize, key: usize) {
+        if key < self.keys[node] {
+            self.keys[node] = key;
+            self.heap.push((Reverse(key), node));
+        }
+    }
+
+    fn pop(&mut self) -> Option<(usize, usize)> {
+        self.skip_stale();
+        let (Reverse(key), node) = self.heap.pop()?;
+        self.keys[node] = NOT_QUEUED;
+        Some((key, node))
+    }
+
+    fn peek(&mut self) -> Option<(usize, usize)> {
+        self.skip_stale();
+        self.heap.peek().map(|&(Reverse(key), node)| (key, node))
+    }
+}
+
+/// Indexed `D`-ary heap: each node is stored once and its position is
+/// tracked, so decrease-key sifts it up in place. Wider nodes make the heap
+/// shallower at the cost of more comparisons per level.
+#[derive(Clone, Debug)]
+pub struct DaryHeap<const D: usize> {
+    heap: Vec<(usize, usize)>,
+    positions: Vec<usize>,
+}
+
+pub type IndexedBinaryHeap = DaryHeap<2>;
+pub type QuaternaryHeap = DaryHeap<4>;
+
+impl<const D: usize> DaryHeap<D> {
+    fn place(&mut self, index: usize, entry: (usize, usize)) {
+        self.heap[index] = entry;
+        self.positions[entry.1] = index;
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        let entry = self.heap[index];
+        while index > 0 {
+            let parent = (index - 1) / D;
+            if self.heap[parent].0 <= entry.0 {
+                break;
+            }
+            self.place(index, self.heap[parent]);
+            index = parent;
+        }
+        self.place(index, entry);
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        let entry = self.heap[index];
+        loop {
+            let first = index * D + 1;
+            let Some(child) = (first..(first + D).min(self.heap.len())).min_by_key(|&child| self.heap[child].0) else {
+                break;
+            };
+            if self.heap[child].0 >= entry.0 {
+                break;
+            }
+            self.place(index, self.heap[child]);
+            index = child;
+        }
+        self.place(index, entry);
+    }
+}
+
+impl<const D: usize> PriorityQueue for DaryHeap<D> {
+    fn new(num_nodes: usize) -> Self {
+        DaryHeap { heap: Vec::new(), positions: vec![NOT_QUEUED; num_nodes] }
+    }
+
+    fn push_or_decrease(&mut self, node: usize, key: usize) {
+        match self.positions[node] {
+            NOT_QUEUED => {
+                self.heap.push((key, node));
+                self.sift_up(self.heap.len() - 1);
+            }
+            index if key < self.heap[index].0 => {
+                self.heap[index].0 = key;
+                self.sift_up(index);
+            }
+            _ => {}
+        }
+    }
+
+    fn pop(&mut self) -> Option<(usize, usize)> {
+        let last = self.heap.pop()?;
+        let top = if self.heap.is_empty() {
+            last
+        } else {
+            let top = self.heap[0];
+            self.place(0, last);
+            self.sift_down(0);
+            top
+        };
+        self.positions[top.1] = NOT_QUEUED;
+        Some(top)
+    }
+
+    fn peek(&mut self) -> Option<(usize, usize)> {
+        self.heap.first().copied()
+    }
+}
+
+const NONE: usize = usize::MAX;
+
+#[derive(Copy, Clone, Debug)]
+struct PairingNode {
+    key: usize,
+    child: usize,
+    next: usize,
+    /// Parent for a first child, left sibling otherwise; `NONE` for the root.
+    prev: usize,
+    queued: bool,
+}
+
+/// Pairing heap over a node-indexed arena. Push and decrease-key are O(1)
+/// (cut the subtree and link it with the root); pop pairs up the root's
+/// children in two passes.
+#[derive(Clone, Debug)]
+pub struct PairingHeap {
+    nodes: Vec<PairingNode>,
+    root: usize,
+    /// Reused by `merge_pairs` so popping does not allocate.
+    pairs: Vec<usize>,
+}
+
+impl PairingHeap {
+    /// Makes the root with the larger key the first child of the other.
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        if a == NONE {
+            return b;
+        }
+        if b == NONE {
+            return a;
+        }
+        let (parent, child) = if self.nodes[b].key < self.nodes[a].key { (b, a) } else { (a, b) };
+        let first = self.nodes[parent].child;
+        self.nodes[child].next = first;
+        self.nodes[child].prev = parent;
+        if first != NONE {
+            self.nodes[first].prev = child;
+        }
+        self.nodes[parent].child = child;
+        parent
+    }
+
+    fn cut(&mut self, node: usize) {
+        let PairingNode { prev, next, .. } = self.nodes[node];
+        if self.nodes[prev].child == node {
+            self.nodes[prev].child = next;
+        } else {
+            self.nodes[prev].next = next;
+        }
+        if next != NONE {
+            self.nodes[next].prev = prev;
+        }
+        self.nodes[node].next = NONE;
+        self.nodes[node].prev = NONE;
+    }
+
+    fn merge_pairs(&mut self, first: usize) -> usize {
+        let mut pairs = std::mem::take(&mut self.pairs);
+        let mut current = first;
+        while current != NONE {
+            let second = self.nodes[current].next;
+            let after = if second == NONE { NONE } else { self.nodes[second].next };
+            for node in [current, second] {
+                if node != NONE {
+                    self.nodes[node].next = NONE;
+                    self.nodes[node].prev = NONE;
+                }
+            }
+            pairs.push(self.link(current, second));
+            current = after;
+        }
+        let mut root = NONE;
+        while let Some(tree) = pairs.pop() {
+            root = self.link(tree, root);
+        }
+        self.pairs = pairs;
+        root
+    }
+}
+
+impl PriorityQueue for PairingHeap {
+    fn new(num_nodes: usize) -> Self {
+        let empty = PairingNode { key: 0, child: NONE, next: NONE, prev: NONE, queued: false };
+        PairingHeap { nodes: vec![empty; num_nodes], root: NONE, pairs: Vec::new() }
+    }
+
+    fn push_or_decrease(&mut self, node: usize, key: usize) {
+        if self.nodes[node].queued {
+            if key >= self.nodes[node].key {
+                return;
+            }
+            self.nodes[node].key = key;
+            if node != self.root {
+                self.cut(node);
+                self.root = self.link(self.root, node);
+            }
+        } else {
+            self.nodes[node] = PairingNode { key, child: NONE, next: NONE, prev: NONE, queued: true };
+            self.root = self.link(self.root, node);
+        }
+    }
+
+    fn pop(&mut self) -> Option<(usize, usize)> {
+        let root = self.root;
+        if root == NONE {
+            return None;
+        }
+        self.root = self.merge_pairs(self.nodes[root].child);
+        self.nodes[root].child = NONE;
+        self.nodes[root].queued = false;
+        Some((self.nodes[root].key, root))
+    }
+
+    fn peek(&mut self) -> Option<(usize, usize)> {
+        (self.root != NONE).then(|| (self.nodes[self.root].key, self.root))
+    }
+}
+
+/// Monotone radix heap for integer keys: keys may never drop below the last
+/// popped one, which holds for Dijkstra with non-negative weights. Entries
+/// sit in buckets by the highest bit in which they differ from the last
+/// popped key, so each entry moves at most once per bit. Decrease-key is lazy.
+#[derive(Clone, Debug)]
+pub struct RadixHeap {
+    buckets: Vec<Vec<(usize, usize)>>,
+    keys: Vec<usize>,
+    last: usize,
+}
+
+impl RadixHeap {
+    fn bucket(&self, key: usize) -> usize {
+        (usize::BITS - (key ^ self.last).leading_zeros()) as usize
+    }
+
+    /// Moves the smallest live key into bucket 0.
+    fn refill(&mut self) {
+        loop {
+            while let Some(&(key, node)) = self.buckets[0].last() {
+                if self.keys[node] == key {
+                    return;
+                }
+                self.buckets[0].pop();
+            }
+            let Some(index) = (1..self.buckets.len()).find(|&i| !self.buckets[i].is_empty()) else {
+                return;
+            };
+            let entries = std::mem::take(&mut self.buckets[index]);
+            let live = entries.iter().filter(|&&(key, node)| self.keys[node] == key).map(|&(key, _)| key).min();
+            if let Some(min) = live {
+                self.last = min;
+                for (key, node) in entries {
+                    if self.keys[node] == key {
+                        let bucket = self.bucket(key);
+                        self.buckets[bucket].push((key, node));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl PriorityQueue for RadixHeap {
+    fn new(num_nodes: usize) -> Self {
+        RadixHeap { buckets: vec![Vec::new(); usize::BITS as usize + 1], keys: vec![NOT_QUEUED; num_nodes], last: 0 }
+    }
+
+    fn push_or_decrease(&mut self, node: usize, key: usize) {
+        debug_assert!(key >= self.last, "radix heap keys must not drop below the last popped key");
+        if key < self.keys[node] {
+            self.keys[node] = key;
+            let bucket = self.bucket(key);
+            self.buckets[bucket].push((key, node));
+        }
+    }
+
+    fn pop(&mut self) -> Option<(usize, usize)> {
+        self.refill();
+        let (key, node) = self.buckets[0].pop()?;
+        self.keys[node] = NOT_QUEUED;
+        Some((key, node))
+    }
+
+    fn peek(&mut self) -> Option<(usize, usize)> {
+        self.refill();
+        self.buckets[0].last().copied()
+    }
+}
+
+/// The queue implementations, selectable at runtime.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum QueueKind {
+    #[default]
+    BinaryHeap,
+    IndexedBinary,
+    Quaternary,
+    Pairing,
+    Radix,
+}
+
+impl QueueKind {
+    pub const ALL: [QueueKind; 5] = [
+        QueueKind::BinaryHeap,
+        QueueKind::IndexedBinary,
+        QueueKind::Quaternary,
+        QueueKind::Pairing,
+        QueueKind::Radix,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QueueKind::BinaryHeap => "binary-heap",
+            QueueKind::IndexedBinary => "indexed-binary",
+            QueueKind::Quaternary => "quaternary",
+            QueueKind::Pairing => "pairing",
+            QueueKind::Radix => "radix",
+        }
+    }
+}
+
+impl fmt::Display for QueueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for QueueKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        QueueKind::ALL.iter()
+            .find(|queue| queue.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown queue `{}` (expected one of: binary-heap, indexed-binary, quaternary, pairing, radix)", s))
+    }
+}
+
+/// Calls the generic search `$search::<_, Q>($args)` with the queue type `Q`
+/// that `$queue: QueueKind` names. Searches generic over the queue alone are
+/// written `$search::<Q>($args)`.
+macro_rules! with_queue {
+    ($queue:expr, $search:ident::<Q>($($arg:expr),* $(,)?)) => {
+        match $queue {
+            $crate::priority_queue::QueueKind::BinaryHeap => $search::<$crate::priority_queue::LazyBinaryHeap>($($arg),*),
+            $crate::priority_queue::QueueKind::IndexedBinary => $search::<$crate::priority_queue::IndexedBinaryHeap>($($arg),*),
+            $crate::priority_queue::QueueKind::Quaternary => $search::<$crate::priority_queue::QuaternaryHeap>($($arg),*),
+            $crate::priority_queue::QueueKind::Pairing => $search::<$crate::priority_queue::PairingHeap>($($arg),*),
+            $crate::priority_queue::QueueKind::Radix => $search::<$crate::priority_queue::RadixHeap>($($arg),*),
+        }
+    };
+    ($queue:expr, $search:ident($($arg:expr),* $(,)?)) => {
+        match $queue {
+            $crate::priority_queue::QueueKind::BinaryHeap => $search::<_, $crate::priority_queue::LazyBinaryHeap>($($arg),*),
+            $crate::priority_queue::QueueKind::IndexedBinary => $search::<_, $crate::priority_queue::IndexedBinaryHeap>($($arg),*),
+            $crate::priority_queue::QueueKind::Quaternary => $search::<_, $crate::priority_queue::QuaternaryHeap>($($arg),*),
+            $crate::priority_queue::QueueKind::Pairing => $search::<_, $crate::priority_queue::PairingHeap>($($arg),*),
+            $crate::priority_queue::QueueKind::Radix => $search::<_, $crate::priority_queue::RadixHeap>($($arg),*),
+        }
+    };
+}
+pub(crate) use with_queue;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Random monotone workload, as Dijkstra produces it: every pushed key is
+    /// at least the last popped one. Each pop must return a smallest key.
+    fn check_against_model<Q: PriorityQueue>(seed: u64) {
+        let n = 300;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut queue = Q::new(n);
+        let mut keys = vec![usize::MAX; n];
+        let mut last = 0;
+        for step in 0..6_000 {
+            if step < 5_000 && rng.gen_bool(0.6) {
+                let node = rng.gen_range(0..n);
+                let key = last + rng.gen_range(0..1_000);
+                queue.push_or_decrease(node, key);
+                keys[node] = keys[node].min(key);
+            } else {
+                let min = keys.iter().copied().min().unwrap();
+                match queue.pop() {
+                    Some((key, node)) => {
+                        assert_eq!((key, keys[node]), (min, min));
+                        keys[node] = usize::MAX;
+                        last = key;
+                    }
+                    None => assert_eq!(min, usize::MAX),
+                }
+            }
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_queues_match_model() {
+        for seed in 0..5 {
+            check_against_model::<LazyBinaryHeap>(seed);
+            check_against_model::<IndexedBinaryHeap>(seed);
+            check_against_model::<QuaternaryHeap>(seed);
+            check_against_model::<DaryHeap<8>>(seed);
+            check_against_model::<PairingHeap>(seed);
+            check_against_model::<RadixHeap>(seed);
+        }
+    }
+
+    #[test]
+    fn test_decrease_key() {
+        fn check<Q: PriorityQueue>() {
+            let mut queue = Q::new(4);
+            queue.push_or_decrease(0, 10);
+            queue.push_or_decrease(1, 20);
+            queue.push_or_decrease(2, 30);
+            queue.push_or_decrease(2, 5);
+            queue.push_or_decrease(1, 25);  // Not lower, ignored
+            assert_eq!(queue.peek(), Some((5, 2)));
+            assert_eq!(queue.pop(), Some((5, 2)));
+            queue.push_or_decrease(1, 7);
+            assert_eq!(queue.pop(), Some((7, 1)));
+            assert_eq!(queue.pop(), Some((10, 0)));
+            queue.push_or_decrease(0, 12);  // Popped nodes may come back
+            assert_eq!(queue.pop(), Some((12, 0)));
+            assert_eq!(queue.pop(), None);
+        }
+        check::<LazyBinaryHeap>();
+        check::<IndexedBinaryHeap>();
+        check::<QuaternaryHeap>();
+        check::<PairingHeap>();
+        check::<RadixHeap>();
+    }
+
+    #[test]
+    fn test_binary_heap_breaks_ties_toward_larger_ids() {
+        let mut queue = LazyBinaryHeap::new(3);
+        queue.push_or_decrease(0, 4);
+        queue.push_or_decrease(2, 4);
+        queue.push_or_decrease(1, 4);
+        assert_eq!(queue.pop(), Some((4, 2)));
+        assert_eq!(queue.pop(), Some((4, 1)));
+        assert_eq!(queue.pop(), Some((4, 0)));
+    }
+
+    #[test]
+    fn test_names_round_trip() {
+        for queue in QueueKind::ALL {
+            assert_eq!(queue.name().parse::<QueueKind>(), Ok(queue));
+        }
+        assert_eq!(QueueKind::default(), QueueKind::BinaryHeap);
+        assert!("fibonacci".parse::<QueueKind>().is_err());
+    }
+}
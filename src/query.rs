@@ -0,0 +1,105 @@
+use crate::algorithm::Algorithm;
+use crate::graph::Adjacency;
+
+/// A point-to-point query, e.g. one line of a batch file.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Query {
+    pub start: usize,
+    pub goal: usize,
+}
+
+impl Query {
+    pub fn new(start: usize, goal: usize) -> Self {
+        Query { start, goal }
+    }
+
+    pub fn run<G: Adjacency + Sync + ?Sized>(&self, graph: &G, algorithm: Algorithm) -> PathResult {
+        algorithm.run(graph, self.start, self.goal).into()
+    }
+}
+
+/// The result of a search. Unlike the `(usize::MAX, vec![])` tuples the
+/// searches return, an unreachable goal has `cost: None`, which survives
+/// formats such as JSON that cannot hold `usize::MAX` exactly.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathResult {
+    pub cost: Option<usize>,
+    pub path: Vec<usize>,
+}
+
+impl PathResult {
+    pub fn is_reachable(&self) -> bool {
+        self.cost.is_some()
+    }
+}
+
+impl From<(usize, Vec<usize>)> for PathResult {
+    fn from((cost, path): (usize, Vec<usize>)) -> Self {
+        let cost = (cost != usize::MAX).then_some(cost);
+        PathResult { cost, path }
+    }
+}
+
+impl From<PathResult> for (usize, Vec<usize>) {
+    fn from(result: PathResult) -> Self {
+        (result.cost.unwrap_or(usize::MAX), result.path)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::edge_list::IdMap;
+    use crate::graph::{Graph, UndirectedGraph};
+    use crate::turn_costs::{Turn, TurnCostTable};
+
+    #[test]
+    fn test_graph_json_round_trip() {
+        let mut turn_costs = TurnCostTable::new();
+        turn_costs.forbid(0, 1, 0);
+        turn_costs.set_cost(0, 1, 2, 7);
+        let graph = Graph::new(vec![vec![(1, 2)], vec![(0, 2), (2, 1)], vec![]])
+            .with_turn_costs(turn_costs)
+            .with_coordinates(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.5)]);
+
+        let json = serde_json::to_string(&graph).unwrap();
+        assert!(!json.contains("rev_adj_list"));
+        let read: Graph = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.adj_list, graph.adj_list);
+        assert_eq!(read.rev_adj_list, graph.rev_adj_list);
+        assert_eq!(read.coordinates, graph.coordinates);
+        assert_eq!(read.turn_costs.get(0, 1, 2), Turn::Cost(7));
+        assert_eq!(read.turn_costs.get(0, 1, 0), Turn::Forbidden);
+
+        let minimal: Graph = serde_json::from_str(r#"{"adj_list": [[[1, 4]], []]}"#).unwrap();
+        assert_eq!(minimal.rev_adj_list, vec![vec![], vec![(0, 4)]]);
+        assert!(serde_json::from_str::<Graph>(r#"{"adj_list": [[[5, 4]]]}"#).is_err());
+        assert!(serde_json::from_str::<Graph>(r#"{"adj_list": [[]], "coordinates": []}"#).is_err());
+
+        let undirected = UndirectedGraph::from_edges(2, [(0, 1, 3)]);
+        let read: UndirectedGraph = serde_json::from_str(&serde_json::to_string(&undirected).unwrap()).unwrap();
+        assert_eq!(read.adj_list, undirected.adj_list);
+        assert!(serde_json::from_str::<UndirectedGraph>(r#"{"adj_list": [[[1, 3]], [[0, 3], [2, 1]]]}"#).is_err());
+    }
+
+    #[test]
+    fn test_query_and_result_json() {
+        let graph = Graph::new(vec![vec![(1, 2)], vec![], vec![]]);
+        let query: Query = serde_json::from_str(r#"{"start": 0, "goal": 1}"#).unwrap();
+        let result = query.run(&graph, Algorithm::Bidirectional);
+        assert_eq!(serde_json::to_string(&result).unwrap(), r#"{"cost":2,"path":[0,1]}"#);
+
+        let unreachable = Query::new(0, 2).run(&graph, Algorithm::Sequential);
+        assert_eq!(serde_json::to_string(&unreachable).unwrap(), r#"{"cost":null,"path":[]}"#);
+        assert_eq!(<(usize, Vec<usize>)>::from(unreachable), (usize::MAX, vec![]));
+
+        assert_eq!(serde_json::to_string(&Algorithm::ParallelBidirectional).unwrap(), r#""parallel-bidirectional""#);
+        assert_eq!(serde_json::from_str::<Algorithm>(r#""parallel""#).unwrap(), Algorithm::Parallel);
+
+        let labels: IdMap = serde_json::from_str(r#"["Market", "Harbour"]"#).unwrap();
+        assert_eq!(labels.id("Harbour"), Some(1));
+        assert!(serde_json::from_str::<IdMap>(r#"["a", "a"]"#).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::timetable::Timetable;
+
+#[derive(Copy, Clone)]
+enum Leg {
+    Ride(usize),
+    Walk(usize),
+}
+
+/// Earliest-arrival query with RAPTOR. Round `k` finds the best arrivals using
+/// at most `k` trips; rounds stop once no stop improves. Returns the same
+/// shape as `csa_earliest_arrival`.
+pub fn raptor_earliest_arrival(timetable: &Timetable, source: usize, target: usize, departure: usize) -> (usize, Vec<usize>) {
+    if source == target {
+        return (departure, vec![source]);
+    }
+
+    let num_stops = timetable.stops.len();
+    let mut best = vec![usize::MAX; num_stops];
+    let mut rounds: Vec<Vec<usize>> = Vec::new();
+    let mut legs: Vec<Vec<Option<Leg>>> = Vec::new();
+
+    let mut arrival = vec![usize::MAX; num_stops];
+    let mut round_legs = vec![None; num_stops];
+    let mut marked = vec![false; num_stops];
+    arrival[source] = departure;
+    best[source] = departure;
+    marked[source] = true;
+    for &(stop, walk) in &timetable.footpaths[source] {
+        arrival[stop] = departure.saturating_add(walk);
+        best[stop] = arrival[stop];
+        round_legs[stop] = Some(Leg::Walk(source));
+        marked[stop] = true;
+    }
+    rounds.push(arrival);
+    legs.push(round_legs);
+
+    loop {
+        // Earliest index at which each route is touched by a marked stop.
+        let mut queue = vec![usize::MAX; timetable.routes.len()];
+        for stop in (0..num_stops).filter(|&stop| marked[stop]) {
+            for &(route, index) in &timetable.routes_by_stop[stop] {
+                queue[route] = queue[route].min(index);
+            }
+        }
+        marked.iter_mut().for_each(|m| *m = false);
+
+        let previous = rounds.last().unwrap();
+        let mut arrival = previous.clone();
+        let mut round_legs = vec![None; num_stops];
+
+        for (route, &start_index) in queue.iter().enumerate() {
+            if start_index == usize::MAX {
+                continue;
+            }
+            let route = &timetable.routes[route];
+            let mut trip: Option<usize> = None;
+            let mut board = 0;
+            for (i, &stop) in route.stops.iter().enumerate().skip(start_index) {
+                if let Some(t) = trip {
+                    let time = route.trips[t][i].0;
+                    if time < best[stop].min(best[target]) {
+                        arrival[stop] = time;
+                        best[stop] = time;
+                        round_legs[stop] = Some(Leg::Ride(board));
+                        marked[stop] = true;
+                    }
+                }
+                let ready = previous[stop];
+                if ready != usize::MAX && trip.is_none_or(|t| ready <= route.trips[t][i].1) {
+                    let end = trip.map_or(route.trips.len(), |t| t + 1);
+                    let earliest = (0..end).find(|&t| route.trips[t][i].1 >= ready);
+                    if earliest.is_some() && earliest != trip {
+                        trip = earliest;
+                        board = stop;
+                    }
+                }
+            }
+        }
+
+        let reached: Vec<usize> = (0..num_stops).filter(|&stop| marked[stop]).collect();
+        for stop in reached {
+            for &(other, walk) in &timetable.footpaths[stop] {
+                let time = arrival[stop].saturating_add(walk);
+                if time < best[other].min(best[target]) {
+                    arrival[other] = time;
+                    best[other] = time;
+                    round_legs[other] = Some(Leg::Walk(stop));
+                    marked[other] = true;
+                }
+            }
+        }
+
+        rounds.push(arrival);
+        legs.push(round_legs);
+        if !marked.iter().any(|&m| m) {
+            break;
+        }
+    }
+
+    if best[target] == usize::MAX {
+        return (usize::MAX, Vec::new());
+    }
+    (best[target], reconstruct_journey(target, &legs))
+}
+
+fn reconstruct_journey(target: usize, legs: &[Vec<Option<Leg>>]) -> Vec<usize> {
+    let mut path = vec![target];
+    let mut current = target;
+    let mut round = legs.len() - 1;
+    loop {
+        match legs[round][current] {
+            Some(Leg::Walk(stop)) => {
+                path.push(stop);
+                current = stop;
+            }
+            Some(Leg::Ride(stop)) => {
+                path.push(stop);
+                current = stop;
+                round -= 1;
+            }
+            None if round > 0 => round -= 1,
+            None => break,
+        }
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csa::csa_earliest_arrival;
+    use crate::timetable::{sample_timetable, Stop, StopTime};
+
+    #[test]
+    fn test_walk_transfer_beats_direct_trip() {
+        let timetable = sample_timetable();
+        let (arrival, path) = raptor_earliest_arrival(&timetable, 0, 2, 90);
+        assert_eq!(arrival, 210);  // T1 arrives at 3 at 150, walk 60
+        assert_eq!(path, vec![0, 3, 2]);
+    }
+
+    #[test]
+    fn test_trip_transfer() {
+        let timetable = sample_timetable();
+        let (arrival, path) = raptor_earliest_arrival(&timetable, 0, 4, 90);
+        assert_eq!(arrival, 300);
+        assert_eq!(path, vec![0, 1, 4]);
+    }
+
+    #[test]
+    fn test_matches_csa() {
+        let timetable = sample_timetable();
+        for departure in [0, 90, 100, 110, 125, 215, 500] {
+            for target in 0..5 {
+                assert_eq!(
+                    raptor_earliest_arrival(&timetable, 0, target, departure).0,
+                    csa_earliest_arrival(&timetable, 0, target, departure).0,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_overtaking_trips() {
+        // The express leaves 0 after the slow trip but overtakes it before 1
+        let stops = (0..3).map(|i| Stop { id: format!("S{}", i), name: String::new() }).collect();
+        let trips = vec!["slow".to_string(), "express".to_string()];
+        let st = |trip, stop, arrival, departure, sequence| StopTime { trip, stop, arrival, departure, sequence };
+        let stop_times = vec![
+            st(0, 0, 100, 100, 1), st(0, 1, 200, 210, 2), st(0, 2, 500, 500, 3),
+            st(1, 0, 110, 110, 1), st(1, 1, 150, 150, 2), st(1, 2, 200, 200, 3),
+        ];
+        let timetable = Timetable::new(stops, trips, stop_times, vec![Vec::new(); 3]);
+        assert_eq!(timetable.routes.len(), 2);
+        for (source, departure) in [(0, 90), (0, 105), (1, 140), (1, 160)] {
+            assert_eq!(
+                raptor_earliest_arrival(&timetable, source, 2, departure),
+                csa_earliest_arrival(&timetable, source, 2, departure),
+            );
+        }
+        assert_eq!(raptor_earliest_arrival(&timetable, 1, 2, 140), (200, vec![1, 2]));
+    }
+}
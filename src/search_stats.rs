@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use crate::selection::Selection;
+
+/// Work counters filled in by the `*_with_stats` searches. Atomic, so the
+/// parallel searches can share one instance between their threads.
+#[derive(Debug, Default)]
+pub struct SearchStats {
+    settled: AtomicUsize,
+    selection: Mutex<Option<Selection>>,
+}
+
+impl SearchStats {
+    pub fn new() -> Self {
+        SearchStats::default()
+    }
+
+    /// Nodes taken off a queue and expanded, summed over both directions of
+    /// bidirectional searches and over all threads of parallel ones.
+    pub fn settled(&self) -> usize {
+        self.settled.load(Ordering::Relaxed)
+    }
+
+    /// The search `shortest_path_with_stats` picked last, if it was used.
+    pub fn selection(&self) -> Option<Selection> {
+        self.selection.lock().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        self.settled.store(0, Ordering::Relaxed);
+        *self.selection.lock().unwrap() = None;
+    }
+
+    pub(crate) fn record_selection(&self, selection: Selection) {
+        *self.selection.lock().unwrap() = Some(selection);
+    }
+}
+
+pub(crate) fn record_settled(stats: Option<&SearchStats>) {
+    if let Some(stats) = stats {
+        stats.settled.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{grid, Weights};
+    use crate::{bidirectional_dijkstra_with_stats, parallel_dijkstra_with_stats, sequential_dijkstra_with_stats, ParallelConfig};
+
+    #[test]
+    fn test_counts_settled_nodes() {
+        let line = vec![vec![(1, 1)], vec![(2, 1)], vec![(3, 1)], vec![]];
+        let stats = SearchStats::new();
+        assert_eq!(sequential_dijkstra_with_stats(&line, &stats, 0, 3).0, 3);
+        assert_eq!(stats.settled(), 3);  // The goal itself is not expanded
+        stats.reset();
+        assert_eq!(stats.settled(), 0);
+
+        let graph = grid(50, 50, Weights::Constant(1), 1);
+        let (start, goal) = (25 * 50 + 10, 25 * 50 + 40);
+        sequential_dijkstra_with_stats(&graph, &stats, start, goal);
+        let sequential = stats.settled();
+        stats.reset();
+        bidirectional_dijkstra_with_stats(&graph, &stats, start, goal);
+        assert!(stats.settled() < sequential);
+        stats.reset();
+        parallel_dijkstra_with_stats(&graph, &stats, &ParallelConfig::default(), start, goal);
+        assert!(stats.settled() >= sequential);  // Summed over threads
+    }
+}
@@ -0,0 +1,239 @@
+use crate::algorithm::Algorithm;
+use crate::graph::{Adjacency, Neighbors};
+use crate::search_stats::SearchStats;
+
+/// Nodes looked at when estimating the degree.
+const SAMPLE: usize = 1024;
+
+/// Below this many nodes (or with no edges at all) the plain search wins:
+/// there is nothing for a second search direction to save.
+pub const SEQUENTIAL_MAX_NODES: usize = 16;
+/// The parallel bidirectional search only pays for its locking on large,
+/// sparse graphs where both directions settle many nodes before meeting,
+/// and only with enough cores to run both sides next to the caller's work.
+pub const PARALLEL_MIN_NODES: usize = 1_000_000;
+pub const PARALLEL_MAX_DEGREE: f64 = 8.0;
+pub const PARALLEL_MIN_CORES: usize = 4;
+/// Dial's bucket queue wins over both heaps on road-like meshes, where the
+/// search settles much of the graph before bidirectional search can meet in
+/// the middle. Their degrees are nearly uniform (dispersion around 0.3 for
+/// `road_network`, against about 1 for random and far more for scale-free
+/// graphs), and its ring of buckets stays small for small weights. On random
+/// graphs bidirectional search settles so few nodes that it stays ahead even
+/// with weights 1..=10; the `dial` benchmark group compares the two.
+pub const DIAL_MAX_WEIGHT: usize = 1_000;
+pub const DIAL_MAX_DISPERSION: f64 = 0.5;
+
+/// The graph characteristics `shortest_path` bases its choice on. The degree
+/// is estimated from up to 1024 evenly spaced nodes; the weight range is
+/// read from every edge, since a single heavy edge rules out Dial's algorithm.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphProfile {
+    pub nodes: usize,
+    pub estimated_edges: usize,
+    pub average_degree: f64,
+    /// Variance of the out-degree over its mean: near 0 for grids and road
+    /// networks, about 1 for uniform random graphs.
+    pub degree_dispersion: f64,
+    /// Smallest and largest edge weight, `None` for a graph without edges.
+    pub weight_range: Option<(usize, usize)>,
+    /// Threads of the rayon pool the query would run on.
+    pub cores: usize,
+}
+
+impl GraphProfile {
+    pub fn of<G: Neighbors + ?Sized>(graph: &G) -> Self {
+        let nodes = graph.num_nodes();
+        let step = nodes.div_ceil(SAMPLE).max(1);
+        let (mut sampled, mut degrees, mut squares) = (0, 0, 0);
+        for node in (0..nodes).step_by(step) {
+            sampled += 1;
+            let degree = graph.degree(node);
+            degrees += degree;
+            squares += degree * degree;
+        }
+        let weight_range = (0..nodes)
+            .flat_map(|node| graph.neighbors(node).map(|(_, weight)| weight))
+            .fold(None, |range, weight| match range {
+                Some((min, max)) => Some((weight.min(min), weight.max(max))),
+                None => Some((weight, weight)),
+            });
+        let average_degree = if sampled == 0 { 0.0 } else { degrees as f64 / sampled as f64 };
+        let variance = if sampled == 0 { 0.0 } else { squares as f64 / sampled as f64 - average_degree * average_degree };
+        let degree_dispersion = if average_degree > 0.0 { variance.max(0.0) / average_degree } else { 0.0 };
+        GraphProfile {
+            nodes,
+            estimated_edges: (average_degree * nodes as f64).round() as usize,
+            average_degree,
+            degree_dispersion,
+            weight_range,
+            cores: rayon::current_num_threads(),
+        }
+    }
+}
+
+/// The search `shortest_path` picked, and why.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selection {
+    pub algorithm: Algorithm,
+    pub reason: &'static str,
+    pub profile: GraphProfile,
+}
+
+impl Selection {
+    pub fn for_profile(profile: GraphProfile) -> Self {
+        // `parallel_dijkstra` runs the same search on every task and keeps
+        // the best answer, so it is never faster than `sequential_dijkstra`
+        // and is never picked.
+        let (algorithm, reason) = if profile.nodes <= SEQUENTIAL_MAX_NODES || profile.weight_range.is_none() {
+            (Algorithm::Sequential, "tiny or edgeless graph")
+        } else if profile.weight_range.is_some_and(|(_, max)| max <= DIAL_MAX_WEIGHT)
+            && profile.degree_dispersion <= DIAL_MAX_DISPERSION
+        {
+            (Algorithm::Dial, "small integer weights on a mesh-like graph")
+        } else if profile.nodes >= PARALLEL_MIN_NODES
+            && profile.average_degree <= PARALLEL_MAX_DEGREE
+            && profile.cores >= PARALLEL_MIN_CORES
+        {
+            (Algorithm::ParallelBidirectional, "large sparse graph with spare cores")
+        } else if profile.cores < PARALLEL_MIN_CORES {
+            (Algorithm::Bidirectional, "too few cores for the parallel searches")
+        } else {
+            (Algorithm::Bidirectional, "graph too small or dense for the parallel searches")
+        };
+        Selection { algorithm, reason, profile }
+    }
+
+    /// The largest edge weight of the profiled graph, if it has edges.
+    pub fn max_weight(&self) -> Option<usize> {
+        self.profile.weight_range.map(|(_, max)| max)
+    }
+}
+
+pub fn select_algorithm<G: Neighbors + ?Sized>(graph: &G) -> Selection {
+    Selection::for_profile(GraphProfile::of(graph))
+}
+
+/// Shortest path from `start` to `goal` with whichever search suits `graph`
+/// best; see `select_algorithm`. Dial's algorithm is picked only when the
+/// largest weight is at most `DIAL_MAX_WEIGHT` *and* the degree dispersion is
+/// at most `DIAL_MAX_DISPERSION`: small weights alone are not enough, since
+/// on random graphs bidirectional search settles so few nodes that it is
+/// over ten times faster than Dial's on `gnm` with weights 1..=10 (see the
+/// `dial` benchmark group). Profiling reads every edge, so for many
+/// queries on one graph call `select_algorithm` once and pass its result to
+/// `shortest_path_with_selection`, which also tells which search ran.
+/// `shortest_path_with_stats` records the choice instead.
+pub fn shortest_path<G: Adjacency + Sync + ?Sized>(graph: &G, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    shortest_path_with_selection(graph, &select_algorithm(graph), start, goal)
+}
+
+/// `shortest_path` with the search `selection` picked for `graph`, without
+/// profiling it again.
+pub fn shortest_path_with_selection<G: Adjacency + Sync + ?Sized>(graph: &G, selection: &Selection, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    match selection.algorithm {
+        // The profile already holds the largest weight Dial's algorithm needs
+        Algorithm::Dial => crate::dial::search(graph, None, None, selection.max_weight(), start, goal),
+        algorithm => algorithm.run(graph, start, goal),
+    }
+}
+
+/// `shortest_path` that counts its work in `stats` and records the search it
+/// picked there (see `SearchStats::selection`).
+pub fn shortest_path_with_stats<G: Adjacency + Sync + ?Sized>(graph: &G, stats: &SearchStats, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    let selection = select_algorithm(graph);
+    let algorithm = selection.algorithm;
+    let max_weight = selection.max_weight();
+    stats.record_selection(selection);
+    match algorithm {
+        Algorithm::Dial => crate::dial::search(graph, None, Some(stats), max_weight, start, goal),
+        algorithm => algorithm.run_with_stats(graph, stats, start, goal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{gnm, grid, road_network, RoadOptions, Weights};
+    use crate::sequential_dijkstra;
+
+    #[test]
+    fn test_profile() {
+        let graph = grid(100, 100, Weights::Uniform(3, 7), 1);
+        let profile = GraphProfile::of(&graph);
+        assert_eq!(profile.nodes, 10_000);
+        assert!(profile.average_degree > 3.0 && profile.average_degree <= 4.0, "{:?}", profile);
+        assert!(profile.degree_dispersion < 0.1);
+        let (min, max) = profile.weight_range.unwrap();
+        assert!(3 <= min && max <= 7);
+
+        let empty: Vec<Vec<(usize, usize)>> = vec![vec![]; 50];
+        assert_eq!(GraphProfile::of(&empty).weight_range, None);
+        assert_eq!(GraphProfile::of(&Vec::<Vec<(usize, usize)>>::new()).average_degree, 0.0);
+    }
+
+    #[test]
+    fn test_choices() {
+        let profile = |nodes, average_degree, cores| GraphProfile {
+            nodes,
+            estimated_edges: (nodes as f64 * average_degree) as usize,
+            average_degree,
+            degree_dispersion: 1.0,
+            weight_range: Some((1, 100)),
+            cores,
+        };
+        assert_eq!(Selection::for_profile(profile(10, 2.0, 8)).algorithm, Algorithm::Sequential);
+        assert_eq!(Selection::for_profile(profile(10_000, 4.0, 8)).algorithm, Algorithm::Bidirectional);
+        assert_eq!(Selection::for_profile(profile(2_000_000, 4.0, 1)).algorithm, Algorithm::Bidirectional);
+        assert_eq!(Selection::for_profile(profile(2_000_000, 40.0, 8)).algorithm, Algorithm::Bidirectional);
+        assert_eq!(Selection::for_profile(profile(2_000_000, 4.0, 8)).algorithm, Algorithm::ParallelBidirectional);
+        assert_eq!(Selection::for_profile(GraphProfile { weight_range: None, ..profile(10_000, 0.0, 8) }).algorithm, Algorithm::Sequential);
+
+        let mesh = GraphProfile { degree_dispersion: 0.2, ..profile(100_000, 4.0, 8) };
+        assert_eq!(Selection::for_profile(mesh.clone()).algorithm, Algorithm::Dial);
+        assert_eq!(Selection::for_profile(GraphProfile { weight_range: Some((1, 5_000)), ..mesh }).algorithm, Algorithm::Bidirectional);
+    }
+
+    #[test]
+    fn test_picks_dial_for_road_networks() {
+        let road = road_network(&RoadOptions::new(100, 100), 2);
+        let selection = select_algorithm(&road);
+        assert_eq!(selection.algorithm, Algorithm::Dial, "{:?}", selection);
+        assert!(selection.profile.degree_dispersion < DIAL_MAX_DISPERSION);
+        let random = gnm(10_000, 40_000, Weights::Uniform(1, 10), 2);
+        assert_eq!(select_algorithm(&random).algorithm, Algorithm::Bidirectional);
+    }
+
+    #[test]
+    fn test_max_weight_is_exact() {
+        // Node 1 is not among the sampled nodes, but its heavy edge counts
+        let mut graph = grid(100, 100, Weights::Uniform(1, 5), 3);
+        let selection = select_algorithm(&graph);
+        assert_eq!(selection.algorithm, Algorithm::Dial);
+        assert_eq!(shortest_path(&graph, 0, 9_999).0, sequential_dijkstra(&graph, 0, 9_999).0);
+        assert_eq!(shortest_path_with_selection(&graph, &selection, 0, 9_999).0, sequential_dijkstra(&graph, 0, 9_999).0);
+        graph.insert_edge(1, 2, 5_000);
+        let selection = select_algorithm(&graph);
+        assert_eq!(selection.max_weight(), Some(5_000));
+        assert_eq!(selection.algorithm, Algorithm::Bidirectional);
+        assert_eq!(shortest_path(&graph, 0, 9_999).0, sequential_dijkstra(&graph, 0, 9_999).0);
+    }
+
+    #[test]
+    fn test_shortest_path_records_selection() {
+        let graph = gnm(2_000, 8_000, Weights::Uniform(1, 99), 9);
+        let stats = SearchStats::new();
+        let selection = select_algorithm(&graph);
+        for goal in [1, 500, 1999] {
+            assert_eq!(shortest_path(&graph, 0, goal).0, sequential_dijkstra(&graph, 0, goal).0);
+            assert_eq!(shortest_path_with_selection(&graph, &selection, 0, goal).0, sequential_dijkstra(&graph, 0, goal).0);
+            assert_eq!(shortest_path_with_stats(&graph, &stats, 0, goal).0, sequential_dijkstra(&graph, 0, goal).0);
+        }
+        let selection = stats.selection().unwrap();
+        assert_eq!(selection.algorithm, select_algorithm(&graph).algorithm);
+        assert_eq!(selection.profile.nodes, 2_000);
+        assert!(stats.settled() > 0);
+        stats.reset();
+        assert_eq!(stats.selection(), None);
+    }
+}
@@ -1,39 +1,44 @@
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use rand::{distributions::{Distribution, Uniform}, SeedableRng, rngs::StdRng, Rng};
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: usize,
+use crate::graph::Neighbors;
+use crate::overlay::{effective_weight, is_banned, Overlay};
+use crate::priority_queue::{with_queue, LazyBinaryHeap, PriorityQueue, QueueKind};
+use crate::search_stats::{record_settled, SearchStats};
+
+pub fn sequential_dijkstra<G: Neighbors + ?Sized>(graph: &G, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, None, start, goal)
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
+/// `sequential_dijkstra` with the bans and weight overrides of `overlay`.
+pub fn sequential_dijkstra_with_overlay<G: Neighbors + ?Sized>(graph: &G, overlay: &Overlay, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, Some(overlay), None, start, goal)
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+/// `sequential_dijkstra` that counts its work in `stats`.
+pub fn sequential_dijkstra_with_stats<G: Neighbors + ?Sized>(graph: &G, stats: &SearchStats, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    search::<_, LazyBinaryHeap>(graph, None, Some(stats), start, goal)
+}
+
+/// `sequential_dijkstra` on the priority queue `queue` instead of the default
+/// `BinaryHeap`.
+pub fn sequential_dijkstra_with_queue<G: Neighbors + ?Sized>(graph: &G, queue: QueueKind, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    with_queue!(queue, search(graph, None, None, start, goal))
 }
 
-pub fn sequential_dijkstra(graph: &[Vec<(usize, usize)>], start: usize, goal: usize) -> (usize, Vec<usize>) {
+pub(crate) fn search<G: Neighbors + ?Sized, Q: PriorityQueue>(graph: &G, overlay: Option<&Overlay>, stats: Option<&SearchStats>, start: usize, goal: usize) -> (usize, Vec<usize>) {
+    if is_banned(overlay, start) || is_banned(overlay, goal) {
+        return (usize::MAX, Vec::new());
+    }
     if start == goal {
         return (0, vec![start]);
     }
 
-    let mut dist = vec![usize::MAX; graph.len()];
-    let mut heap = BinaryHeap::new();
-    let mut prev = vec![None; graph.len()];
+    let mut dist = vec![usize::MAX; graph.num_nodes()];
+    let mut heap = Q::new(graph.num_nodes());
+    let mut prev = vec![None; graph.num_nodes()];
 
     dist[start] = 0;
-    heap.push(State { cost: 0, position: start });
+    heap.push_or_decrease(start, 0);
 
-    while let Some(State { cost, position }) = heap.pop() {
+    while let Some((cost, position)) = heap.pop() {
         if position == goal {
             let path = reconstruct_path(goal, &prev);
             return (cost, path);
@@ -42,12 +47,16 @@ pub fn sequential_dijkstra(graph: &[Vec<(usize, usize)>], start: usize, goal: us
         if cost > dist[position] {
             continue;
         }
+        record_settled(stats);
 
-        for &(neighbor, weight) in &graph[position] {
+        for (i, (neighbor, weight)) in graph.neighbors(position).enumerate() {
+            let Some(weight) = effective_weight(overlay, position, i, neighbor, weight) else {
+                continue;
+            };
             let next_cost = cost.saturating_add(weight);
             if next_cost < dist[neighbor] {
                 dist[neighbor] = next_cost;
-                heap.push(State { cost: next_cost, position: neighbor });
+                heap.push_or_decrease(neighbor, next_cost);
                 prev[neighbor] = Some(position);
             }
         }
@@ -56,6 +65,53 @@ pub fn sequential_dijkstra(graph: &[Vec<(usize, usize)>], start: usize, goal: us
     (usize::MAX, Vec::new())
 }
 
+/// Single-source variant of `sequential_dijkstra` without a goal. Returns the
+/// distance to every node (`usize::MAX` if unreachable) and the predecessor tree.
+pub fn sequential_sssp<G: Neighbors + ?Sized>(graph: &G, start: usize) -> (Vec<usize>, Vec<Option<usize>>) {
+    sssp::<_, LazyBinaryHeap>(graph, None, start)
+}
+
+pub fn sequential_sssp_with_overlay<G: Neighbors + ?Sized>(graph: &G, overlay: &Overlay, start: usize) -> (Vec<usize>, Vec<Option<usize>>) {
+    sssp::<_, LazyBinaryHeap>(graph, Some(overlay), start)
+}
+
+/// `sequential_sssp` on the priority queue `queue`.
+pub fn sequential_sssp_with_queue<G: Neighbors + ?Sized>(graph: &G, queue: QueueKind, start: usize) -> (Vec<usize>, Vec<Option<usize>>) {
+    with_queue!(queue, sssp(graph, None, start))
+}
+
+fn sssp<G: Neighbors + ?Sized, Q: PriorityQueue>(graph: &G, overlay: Option<&Overlay>, start: usize) -> (Vec<usize>, Vec<Option<usize>>) {
+    let mut dist = vec![usize::MAX; graph.num_nodes()];
+    let mut heap = Q::new(graph.num_nodes());
+    let mut prev = vec![None; graph.num_nodes()];
+    if is_banned(overlay, start) {
+        return (dist, prev);
+    }
+
+    dist[start] = 0;
+    heap.push_or_decrease(start, 0);
+
+    while let Some((cost, position)) = heap.pop() {
+        if cost > dist[position] {
+            continue;
+        }
+
+        for (i, (neighbor, weight)) in graph.neighbors(position).enumerate() {
+            let Some(weight) = effective_weight(overlay, position, i, neighbor, weight) else {
+                continue;
+            };
+            let next_cost = cost.saturating_add(weight);
+            if next_cost < dist[neighbor] {
+                dist[neighbor] = next_cost;
+                heap.push_or_decrease(neighbor, next_cost);
+                prev[neighbor] = Some(position);
+            }
+        }
+    }
+
+    (dist, prev)
+}
+
 fn reconstruct_path(goal: usize, prev: &[Option<usize>]) -> Vec<usize> {
     let mut path = Vec::new();
     let mut current = Some(goal);
@@ -67,6 +123,7 @@ fn reconstruct_path(goal: usize, prev: &[Option<usize>]) -> Vec<usize> {
     path
 }
 
+#[allow(dead_code)]
 fn main() {
     let graph = vec![
         vec![(1, 7), (2, 9), (5, 14)], // edges from node 0
@@ -82,33 +139,10 @@ fn main() {
 }
 
 
-fn generate_large_graph(nodes: usize, edges_per_node: usize, max_weight: usize, seed: u64) -> Vec<Vec<(usize, usize)>> {
-    let mut rng = StdRng::seed_from_u64(seed);
-    let weight_dist = Uniform::from(1..=max_weight);
-
-    let mut graph = vec![Vec::new(); nodes];
-    for node in 0..nodes {
-        // Each node will have a random number of edges up to `edges_per_node`
-        let num_edges = rng.gen_range(1..=edges_per_node);
-        for _ in 0..num_edges {
-            let target = rng.gen_range(0..nodes);
-            let weight = weight_dist.sample(&mut rng);
-            // Ensure no self-loops or duplicate edges
-            if target != node && !graph[node].iter().any(|&(t, _)| t == target) {
-                graph[node].push((target, weight));
-            }
-        }
-    }
-
-    graph
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::distributions::{Distribution, Uniform};
-    use rand::rngs::StdRng;
-    use rand::SeedableRng;
+    use crate::generators::{self, Weights};
     use std::time::Instant;
 
     #[test]
@@ -185,10 +219,10 @@ mod tests {
         let max_weight = 100; // Maximum weight of 100
         let seed = 42; // Fixed seed for reproducibility
 
-        let graph = generate_large_graph(nodes, edges_per_node, max_weight, seed);
+        let graph = generators::gnm(nodes, nodes * edges_per_node / 2, Weights::Uniform(1, max_weight), seed).adj_list;
 
         let start = 0;
-        let goal = Some(nodes - nodes/3); // Assuming we want to find path from node 0 to the last node
+        let _goal = Some(nodes - nodes/3); // Assuming we want to find path from node 0 to the last node
         let start_time = std::time::Instant::now();
         let (cost, path) = sequential_dijkstra(&graph, start, nodes - 1);
         let duration = start_time.elapsed();
@@ -196,21 +230,7 @@ mod tests {
         println!("Test Huge Graph - Time elapsed: {:?}, Cost: {}, Path Length: {}", duration, cost, path.len());
     }
     fn generate_random_graph(nodes: usize, edges: usize) -> Vec<Vec<(usize, usize)>> {
-        let mut graph = vec![Vec::new(); nodes];
-        let mut rng = StdRng::seed_from_u64(42); // Seed for reproducibility
-        let range = Uniform::from(0..nodes);
-        let weight_range = Uniform::from(1..100); // Random weights between 1 and 100
-
-        for _ in 0..edges {
-            let u = range.sample(&mut rng);
-            let v = range.sample(&mut rng);
-            if u != v {
-                let weight = weight_range.sample(&mut rng);
-                graph[u].push((v, weight));
-            }
-        }
-
-        graph
+        generators::gnm(nodes, edges, Weights::Uniform(1, 99), 42).adj_list
     }
 
     #[test]
@@ -263,28 +283,28 @@ mod tests {
         println!("Cost: {}, Path length: {}", cost, path.len());
     }
 
-}
-
-/*
-successes:
-
----- standard_dijkstra::tests::test_simple_graph stdout ----
-Test Simple Graph - Time elapsed: 260.708µs
-
----- standard_dijkstra::tests::test_complex_graph stdout ----
-Test Complex Graph - Time elapsed: 338.416µs
-
----- standard_dijkstra::tests::test_larger_graph stdout ----
-Test Larger Graph - Time elapsed: 291.708µs
-
----- standard_dijkstra::tests::test_very_complex_graph stdout ----
-Test Very Complex Graph - Time elapsed: 98.209µs
-
----- standard_dijkstra::tests::test_disconnected_graph stdout ----
-Test Disconnected Graph - Time elapsed: 334.25µs
-
----- standard_dijkstra::tests::test_huge_graph stdout ----
-Test Huge Graph - Time elapsed: 252.426083ms, Cost: 54, Path Length: 13
+    #[test]
+    fn test_sssp_matches_point_to_point() {
+        let graph = generate_random_graph(1000, 5000);
+        let (dist, prev) = sequential_sssp(&graph, 0);
+        for goal in (1..1000).step_by(37) {
+            let (cost, path) = sequential_dijkstra(&graph, 0, goal);
+            assert_eq!(dist[goal], cost);
+            if cost != usize::MAX {
+                assert_eq!(reconstruct_path(goal, &prev), path);
+            }
+        }
+    }
 
+    #[test]
+    fn test_sssp_with_every_queue() {
+        let graph = generate_random_graph(1000, 5000);
+        let (dist, _) = sequential_sssp(&graph, 0);
+        for queue in QueueKind::ALL {
+            let (queue_dist, queue_prev) = sequential_sssp_with_queue(&graph, queue, 0);
+            assert_eq!(queue_dist, dist);
+            assert_eq!(crate::verify_tree(&graph, 0, &queue_dist, &queue_prev), Ok(()));
+        }
+    }
 
- */
\ No newline at end of file
+}
@@ -0,0 +1,369 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use crate::edge_list::split_fields;
+
+/// Longest walk, in seconds, that `Timetable::new` turns into a footpath.
+pub const DEFAULT_MAX_WALK: usize = 20 * 60;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stop {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StopTime {
+    pub trip: usize,
+    pub stop: usize,
+    pub arrival: usize,
+    pub departure: usize,
+    pub sequence: usize,
+}
+
+/// One vehicle hop between two consecutive stops of a trip. Times are seconds
+/// after midnight of the service day.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Connection {
+    pub trip: usize,
+    pub dep_stop: usize,
+    pub arr_stop: usize,
+    pub dep_time: usize,
+    pub arr_time: usize,
+}
+
+/// Trips sharing the same stop sequence, as used by RAPTOR. `trips[t][i]` is
+/// the `(arrival, departure)` of trip `t` at `stops[i]`, sorted by departure
+/// at every stop: no trip overtakes another. Trips that do overtake go to
+/// separate routes over the same stops.
+#[derive(Clone, Debug)]
+pub struct Route {
+    pub stops: Vec<usize>,
+    pub trips: Vec<Vec<(usize, usize)>>,
+}
+
+pub struct Timetable {
+    pub stops: Vec<Stop>,
+    pub trip_ids: Vec<String>,
+    /// All connections sorted by departure time.
+    pub connections: Vec<Connection>,
+    pub routes: Vec<Route>,
+    /// `(route, index)` pairs of every route serving a stop.
+    pub routes_by_stop: Vec<Vec<(usize, usize)>>,
+    /// Walking graph between stops, with weights in seconds.
+    pub walking: Vec<Vec<(usize, usize)>>,
+    /// Every stop reachable on foot within the maximum walk, with its shortest
+    /// walking time over `walking`.
+    pub footpaths: Vec<Vec<(usize, usize)>>,
+    stop_index: HashMap<String, usize>,
+}
+
+impl Timetable {
+    /// `with_max_walk` with walks of up to `DEFAULT_MAX_WALK`.
+    pub fn new(stops: Vec<Stop>, trip_ids: Vec<String>, stop_times: Vec<StopTime>, walking: Vec<Vec<(usize, usize)>>) -> Self {
+        Timetable::with_max_walk(stops, trip_ids, stop_times, walking, DEFAULT_MAX_WALK)
+    }
+
+    /// Builds the connections, routes and footpaths. Footpaths only reach
+    /// stops within `max_walk` seconds, which keeps them small on a city-wide
+    /// walking graph.
+    pub fn with_max_walk(stops: Vec<Stop>, trip_ids: Vec<String>, mut stop_times: Vec<StopTime>, walking: Vec<Vec<(usize, usize)>>, max_walk: usize) -> Self {
+        let stop_index = stops.iter().enumerate().map(|(i, stop)| (stop.id.clone(), i)).collect();
+
+        stop_times.sort_by_key(|st| (st.trip, st.sequence));
+
+        let mut connections = Vec::new();
+        let mut sequence_index: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut sequences: Vec<Route> = Vec::new();
+        for trip_times in stop_times.chunk_by(|a, b| a.trip == b.trip) {
+            for hop in trip_times.windows(2) {
+                connections.push(Connection {
+                    trip: hop[0].trip,
+                    dep_stop: hop[0].stop,
+                    arr_stop: hop[1].stop,
+                    dep_time: hop[0].departure,
+                    arr_time: hop[1].arrival,
+                });
+            }
+
+            let sequence: Vec<usize> = trip_times.iter().map(|st| st.stop).collect();
+            let times = trip_times.iter().map(|st| (st.arrival, st.departure)).collect();
+            let group = *sequence_index.entry(sequence.clone()).or_insert_with(|| {
+                sequences.push(Route { stops: sequence, trips: Vec::new() });
+                sequences.len() - 1
+            });
+            sequences[group].trips.push(times);
+        }
+        connections.sort_by_key(|c| (c.dep_time, c.arr_time));
+
+        // Each trip joins the first route of its stop sequence that it runs
+        // behind at every stop, or starts a new one.
+        let mut routes: Vec<Route> = Vec::new();
+        for mut group in sequences {
+            group.trips.sort_by_key(|times| times[0].1);
+            let first = routes.len();
+            for times in group.trips {
+                let behind = |route: &Route| route.trips.last().unwrap().iter().zip(&times).all(|(before, after)| before <= after);
+                match routes[first..].iter_mut().find(|route| behind(route)) {
+                    Some(route) => route.trips.push(times),
+                    None => routes.push(Route { stops: group.stops.clone(), trips: vec![times] }),
+                }
+            }
+        }
+
+        let mut routes_by_stop = vec![Vec::new(); stops.len()];
+        for (r, route) in routes.iter().enumerate() {
+            for (i, &stop) in route.stops.iter().enumerate() {
+                routes_by_stop[stop].push((r, i));
+            }
+        }
+
+        let mut walking = walking;
+        walking.resize(stops.len(), Vec::new());
+        let mut dist = vec![usize::MAX; stops.len()];
+        let footpaths = (0..stops.len()).map(|stop| walk_within(&walking, stop, max_walk, &mut dist)).collect();
+
+        Timetable {
+            stops,
+            trip_ids,
+            connections,
+            routes,
+            routes_by_stop,
+            walking,
+            footpaths,
+            stop_index,
+        }
+    }
+
+    /// Loads `stops.txt`, `trips.txt` and `stop_times.txt` from a GTFS-style
+    /// directory. If present, `transfers.txt` entries between distinct stops
+    /// with a `min_transfer_time` become edges of the walking graph.
+    pub fn load_gtfs<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let dir = dir.as_ref();
+
+        let mut stops = Vec::new();
+        let mut stop_index = HashMap::new();
+        for row in read_csv(&dir.join("stops.txt"))?.rows() {
+            let id = row.get("stop_id")?.to_string();
+            let name = row.get("stop_name").unwrap_or("").to_string();
+            stop_index.insert(id.clone(), stops.len());
+            stops.push(Stop { id, name });
+        }
+
+        let mut trip_ids = Vec::new();
+        let mut trip_index = HashMap::new();
+        for row in read_csv(&dir.join("trips.txt"))?.rows() {
+            let id = row.get("trip_id")?.to_string();
+            trip_index.insert(id.clone(), trip_ids.len());
+            trip_ids.push(id);
+        }
+
+        let mut stop_times = Vec::new();
+        for row in read_csv(&dir.join("stop_times.txt"))?.rows() {
+            let trip = lookup(&trip_index, row.get("trip_id")?)?;
+            let stop = lookup(&stop_index, row.get("stop_id")?)?;
+            let arrival = parse_time(row.get("arrival_time")?)?;
+            let departure = parse_time(row.get("departure_time")?)?;
+            let sequence = parse_number(row.get("stop_sequence")?)?;
+            stop_times.push(StopTime { trip, stop, arrival, departure, sequence });
+        }
+
+        let mut walking = vec![Vec::new(); stops.len()];
+        let transfers = dir.join("transfers.txt");
+        if transfers.exists() {
+            for row in read_csv(&transfers)?.rows() {
+                let from = lookup(&stop_index, row.get("from_stop_id")?)?;
+                let to = lookup(&stop_index, row.get("to_stop_id")?)?;
+                match row.get("min_transfer_time") {
+                    Ok(time) if from != to && !time.is_empty() => walking[from].push((to, parse_number(time)?)),
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Timetable::new(stops, trip_ids, stop_times, walking))
+    }
+
+    pub fn stop_index(&self, id: &str) -> Option<usize> {
+        self.stop_index.get(id).copied()
+    }
+}
+
+/// Stops within `max_walk` of `source`, sorted by stop. `dist` must be all
+/// `usize::MAX` and is left that way, so one buffer serves every stop and
+/// each search only touches the stops it reaches.
+fn walk_within(walking: &[Vec<(usize, usize)>], source: usize, max_walk: usize, dist: &mut [usize]) -> Vec<(usize, usize)> {
+    if walking[source].is_empty() {
+        return Vec::new();
+    }
+    let mut reached = vec![source];
+    let mut heap = BinaryHeap::from([Reverse((0, source))]);
+    dist[source] = 0;
+    while let Some(Reverse((cost, stop))) = heap.pop() {
+        if cost > dist[stop] {
+            continue;
+        }
+        for &(other, walk) in &walking[stop] {
+            let next_cost = cost.saturating_add(walk);
+            if next_cost <= max_walk && next_cost < dist[other] {
+                if dist[other] == usize::MAX {
+                    reached.push(other);
+                }
+                dist[other] = next_cost;
+                heap.push(Reverse((next_cost, other)));
+            }
+        }
+    }
+
+    let mut footpaths: Vec<(usize, usize)> = reached.iter()
+        .filter(|&&stop| stop != source)
+        .map(|&stop| (stop, dist[stop]))
+        .collect();
+    footpaths.sort_unstable();
+    for stop in reached {
+        dist[stop] = usize::MAX;
+    }
+    footpaths
+}
+
+struct CsvRow<'a> {
+    header: &'a HashMap<String, usize>,
+    fields: &'a [String],
+}
+
+impl CsvRow<'_> {
+    fn get(&self, column: &str) -> io::Result<&str> {
+        self.header.get(column)
+            .and_then(|&i| self.fields.get(i))
+            .map(|field| field.as_str())
+            .ok_or_else(|| invalid_data(format!("missing column `{}`", column)))
+    }
+}
+
+struct CsvFile {
+    header: HashMap<String, usize>,
+    rows: Vec<Vec<String>>,
+}
+
+impl CsvFile {
+    fn rows(&self) -> impl Iterator<Item = CsvRow<'_>> {
+        self.rows.iter().map(move |fields| CsvRow { header: &self.header, fields })
+    }
+}
+
+fn read_csv(path: &Path) -> io::Result<CsvFile> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let header = match lines.next() {
+        Some(line) => split_fields(line?.trim_start_matches('\u{feff}'), Some(',')),
+        None => Vec::new(),
+    };
+    let header = header.into_iter().enumerate().map(|(i, name)| (name, i)).collect();
+    let mut rows = Vec::new();
+    for line in lines {
+        let line = line?;
+        if !line.trim().is_empty() {
+            rows.push(split_fields(&line, Some(',')));
+        }
+    }
+    Ok(CsvFile { header, rows })
+}
+
+fn lookup(index: &HashMap<String, usize>, id: &str) -> io::Result<usize> {
+    index.get(id).copied().ok_or_else(|| invalid_data(format!("unknown id `{}`", id)))
+}
+
+/// Parses `HH:MM:SS` into seconds. Hours may exceed 23 for trips running past
+/// midnight, as allowed by GTFS.
+pub fn parse_time(time: &str) -> io::Result<usize> {
+    let parts: Vec<&str> = time.split(':').collect();
+    if parts.len() != 3 {
+        return Err(invalid_data(format!("invalid time `{}`", time)));
+    }
+    let h = parse_number(parts[0])?;
+    let m = parse_number(parts[1])?;
+    let s = parse_number(parts[2])?;
+    Ok(h * 3600 + m * 60 + s)
+}
+
+fn parse_number(value: &str) -> io::Result<usize> {
+    value.trim().parse().map_err(|_| invalid_data(format!("invalid number `{}`", value)))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Small timetable shared by the CSA and RAPTOR tests.
+#[cfg(test)]
+pub(crate) fn sample_timetable() -> Timetable {
+    // Line T0: 0 -> 1 -> 2, slow. Line T1: 0 -> 3, fast, then walk 3 -> 2.
+    // Line T2: 1 -> 4 leaves 1 right after T0 arrives.
+    let stops = (0..5).map(|i| Stop { id: format!("S{}", i), name: String::new() }).collect();
+    let trips = vec!["T0".to_string(), "T1".to_string(), "T2".to_string()];
+    let st = |trip, stop, arrival, departure, sequence| StopTime { trip, stop, arrival, departure, sequence };
+    let stop_times = vec![
+        st(0, 0, 100, 100, 1), st(0, 1, 200, 210, 2), st(0, 2, 400, 400, 3),
+        st(1, 0, 120, 120, 1), st(1, 3, 150, 150, 2),
+        st(2, 1, 220, 220, 1), st(2, 4, 300, 300, 2),
+    ];
+    let mut walking = vec![Vec::new(); 5];
+    walking[3].push((2, 60));
+    Timetable::new(stops, trips, stop_times, walking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("08:05:30").unwrap(), 8 * 3600 + 5 * 60 + 30);
+        assert_eq!(parse_time("25:00:00").unwrap(), 25 * 3600);  // Past midnight
+        assert!(parse_time("8:05").is_err());
+    }
+
+    #[test]
+    fn test_load_gtfs() {
+        let dir = std::env::temp_dir().join(format!("timetable_gtfs_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("stops.txt"), "stop_id,stop_name\nA,\"Central, North\"\nB,Market\nC,Harbour\n").unwrap();
+        fs::write(dir.join("trips.txt"), "route_id,service_id,trip_id\nR1,WK,T1\nR1,WK,T2\n").unwrap();
+        fs::write(dir.join("stop_times.txt"), "trip_id,arrival_time,departure_time,stop_id,stop_sequence\n\
+            T1,08:00:00,08:00:00,A,1\nT1,08:10:00,08:11:00,B,2\nT2,09:00:00,09:00:00,A,1\nT2,09:10:00,09:11:00,B,2\n").unwrap();
+        fs::write(dir.join("transfers.txt"), "from_stop_id,to_stop_id,transfer_type,min_transfer_time\nB,C,2,300\nB,B,2,60\n").unwrap();
+
+        let timetable = Timetable::load_gtfs(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(timetable.stops.len(), 3);
+        assert_eq!(timetable.stops[0].name, "Central, North");
+        assert_eq!(timetable.connections.len(), 2);
+        assert_eq!(timetable.routes.len(), 1);  // T1 and T2 share a stop sequence
+        assert_eq!(timetable.routes[0].trips.len(), 2);
+        let b = timetable.stop_index("B").unwrap();
+        let c = timetable.stop_index("C").unwrap();
+        assert_eq!(timetable.walking[b], vec![(c, 300)]);
+    }
+
+    #[test]
+    fn test_footpath_closure() {
+        let stops = (0..3).map(|i| Stop { id: i.to_string(), name: String::new() }).collect();
+        let walking = vec![vec![(1, 60)], vec![(2, 120)], vec![]];
+        let timetable = Timetable::new(stops, Vec::new(), Vec::new(), walking);
+        assert_eq!(timetable.footpaths[0], vec![(1, 60), (2, 180)]);
+        assert!(timetable.footpaths[2].is_empty());
+    }
+
+    #[test]
+    fn test_footpaths_stop_at_max_walk() {
+        let stops = (0..4).map(|i| Stop { id: i.to_string(), name: String::new() }).collect();
+        let walking = vec![vec![(1, 60), (3, 500)], vec![(2, 120), (0, 60)], vec![(3, 60)], vec![]];
+        let timetable = Timetable::with_max_walk(stops, Vec::new(), Vec::new(), walking, 200);
+        assert_eq!(timetable.footpaths[0], vec![(1, 60), (2, 180)]);
+        assert_eq!(timetable.footpaths[1], vec![(0, 60), (2, 120), (3, 180)]);
+        assert_eq!(timetable.footpaths[2], vec![(3, 60)]);
+    }
+}
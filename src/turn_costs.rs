@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Turn {
+    Forbidden,
+    Cost(usize),
+}
+
+/// Turn restrictions and penalties keyed by `(from, via, to)` node triples.
+/// Turns that are not listed are allowed at no extra cost. Parallel edges
+/// between the same two nodes share their entries: a turn cannot be
+/// restricted on one of them only.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TurnCostTable {
+    #[cfg_attr(feature = "serde", serde(with = "turn_entries"))]
+    turns: Turns,
+}
+
+type Turns = HashMap<(usize, usize, usize), Turn>;
+
+impl TurnCostTable {
+    pub fn new() -> Self {
+        TurnCostTable { turns: HashMap::new() }
+    }
+
+    pub fn forbid(&mut self, from: usize, via: usize, to: usize) {
+        self.turns.insert((from, via, to), Turn::Forbidden);
+    }
+
+    pub fn set_cost(&mut self, from: usize, via: usize, to: usize, cost: usize) {
+        self.turns.insert((from, via, to), Turn::Cost(cost));
+    }
+
+    pub fn get(&self, from: usize, via: usize, to: usize) -> Turn {
+        self.turns.get(&(from, via, to)).copied().unwrap_or(Turn::Cost(0))
+    }
+
+    pub fn len(&self) -> usize {
+        self.turns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.turns.is_empty()
+    }
+}
+
+/// Stores the table as a list of `(from, via, to, turn)` entries, since
+/// formats like JSON only allow string map keys.
+#[cfg(feature = "serde")]
+mod turn_entries {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use super::{Turn, Turns};
+
+    pub fn serialize<S: Serializer>(turns: &Turns, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<_> = turns.iter().map(|(&(from, via, to), &turn)| (from, via, to, turn)).collect();
+        entries.sort_by_key(|&(from, via, to, _)| (from, via, to));
+        serializer.collect_seq(entries)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Turns, D::Error> {
+        let entries = Vec::<(usize, usize, usize, Turn)>::deserialize(deserializer)?;
+        Ok(entries.into_iter().map(|(from, via, to, turn)| ((from, via, to), turn)).collect())
+    }
+}
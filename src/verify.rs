@@ -0,0 +1,254 @@
+use std::collections::VecDeque;
+use std::fmt;
+use crate::graph::Neighbors;
+
+/// Why a search result failed verification.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VerifyError {
+    NodeOutOfRange { node: usize, num_nodes: usize },
+    /// `usize::MAX` (unreachable) came with a non-empty path.
+    PathForUnreachable,
+    WrongEndpoints { start: usize, goal: usize },
+    MissingEdge { from: usize, to: usize },
+    CostMismatch { reported: usize, actual: usize },
+    WrongLength { expected: usize, actual: usize },
+    SourceNotZero { distance: usize },
+    /// `dist[to] > dist[from] + weight`: the edge would give a shorter path.
+    NotRelaxed { from: usize, to: usize, weight: usize },
+    /// A finite distance no chain of tight edges from the source explains.
+    Unsupported { node: usize },
+    /// A predecessor that is not a tight edge into `node`.
+    BadPredecessor { node: usize },
+    /// Following `prev` from `node` goes round a cycle instead of reaching
+    /// the source, which tight edges allow when the cycle weighs zero.
+    PredecessorCycle { node: usize },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::NodeOutOfRange { node, num_nodes } => {
+                write!(f, "node {} is out of range for a graph with {} nodes", node, num_nodes)
+            }
+            VerifyError::PathForUnreachable => write!(f, "unreachable goal with a non-empty path"),
+            VerifyError::WrongEndpoints { start, goal } => write!(f, "path does not run from {} to {}", start, goal),
+            VerifyError::MissingEdge { from, to } => write!(f, "path uses missing edge {} -> {}", from, to),
+            VerifyError::CostMismatch { reported, actual } => {
+                write!(f, "reported cost {} but the path costs {}", reported, actual)
+            }
+            VerifyError::WrongLength { expected, actual } => {
+                write!(f, "expected {} entries, got {}", expected, actual)
+            }
+            VerifyError::SourceNotZero { distance } => write!(f, "source has distance {}", distance),
+            VerifyError::NotRelaxed { from, to, weight } => {
+                write!(f, "edge {} -> {} (weight {}) gives a shorter path to {}", from, to, weight, to)
+            }
+            VerifyError::Unsupported { node } => {
+                write!(f, "distance of node {} is not reached by tight edges from the source", node)
+            }
+            VerifyError::BadPredecessor { node } => write!(f, "predecessor of node {} is not a tight edge", node),
+            VerifyError::PredecessorCycle { node } => {
+                write!(f, "predecessors of node {} form a cycle that misses the source", node)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Checks that `path` runs from `start` to `goal` along edges of `graph` and
+/// costs exactly `cost`, taking the cheapest of parallel edges. An
+/// unreachable result (`usize::MAX`) must have an empty path. This does not
+/// show the path is shortest; see `verify_distances` for that.
+pub fn verify_path<G: Neighbors + ?Sized>(graph: &G, start: usize, goal: usize, cost: usize, path: &[usize]) -> Result<(), VerifyError> {
+    if cost == usize::MAX {
+        return if path.is_empty() { Ok(()) } else { Err(VerifyError::PathForUnreachable) };
+    }
+    if path.first() != Some(&start) || path.last() != Some(&goal) {
+        return Err(VerifyError::WrongEndpoints { start, goal });
+    }
+    for &node in path {
+        check_node(graph, node)?;
+    }
+    let mut actual = 0usize;
+    for pair in path.windows(2) {
+        let weight = graph.neighbors(pair[0])
+            .filter(|&(to, _)| to == pair[1])
+            .map(|(_, weight)| weight)
+            .min()
+            .ok_or(VerifyError::MissingEdge { from: pair[0], to: pair[1] })?;
+        actual = actual.saturating_add(weight);
+    }
+    if actual != cost {
+        return Err(VerifyError::CostMismatch { reported: cost, actual });
+    }
+    Ok(())
+}
+
+/// Checks that `dist` holds the shortest-path distances from `source`
+/// (`usize::MAX` for unreachable nodes), whichever algorithm produced them:
+/// no edge can shorten any distance, and every finite distance is reached
+/// from the source by a chain of tight edges (`dist[v] == dist[u] + w`).
+/// Together these certify optimality in O(n + m).
+pub fn verify_distances<G: Neighbors + ?Sized>(graph: &G, source: usize, dist: &[usize]) -> Result<(), VerifyError> {
+    let num_nodes = graph.num_nodes();
+    if dist.len() != num_nodes {
+        return Err(VerifyError::WrongLength { expected: num_nodes, actual: dist.len() });
+    }
+    check_node(graph, source)?;
+    if dist[source] != 0 {
+        return Err(VerifyError::SourceNotZero { distance: dist[source] });
+    }
+
+    for from in 0..num_nodes {
+        if dist[from] == usize::MAX {
+            continue;
+        }
+        for (to, weight) in graph.neighbors(from) {
+            if dist[to] > dist[from].saturating_add(weight) {
+                return Err(VerifyError::NotRelaxed { from, to, weight });
+            }
+        }
+    }
+
+    // Breadth-first over tight edges only. Checking each node for some tight
+    // incoming edge is not enough: a zero-weight cycle would support itself.
+    let mut reached = vec![false; num_nodes];
+    reached[source] = true;
+    let mut queue = VecDeque::from([source]);
+    while let Some(from) = queue.pop_front() {
+        for (to, weight) in graph.neighbors(from) {
+            if !reached[to] && dist[from].saturating_add(weight) == dist[to] {
+                reached[to] = true;
+                queue.push_back(to);
+            }
+        }
+    }
+    match (0..num_nodes).find(|&node| dist[node] != usize::MAX && !reached[node]) {
+        Some(node) => Err(VerifyError::Unsupported { node }),
+        None => Ok(()),
+    }
+}
+
+/// `verify_distances`, plus a check that every predecessor in `prev` (as
+/// returned by `sequential_sssp`) is a tight edge and that the predecessors
+/// form a tree rooted at `source`, so that following `prev` back from any
+/// reachable node gives a shortest path.
+pub fn verify_tree<G: Neighbors + ?Sized>(graph: &G, source: usize, dist: &[usize], prev: &[Option<usize>]) -> Result<(), VerifyError> {
+    verify_distances(graph, source, dist)?;
+    if prev.len() != dist.len() {
+        return Err(VerifyError::WrongLength { expected: dist.len(), actual: prev.len() });
+    }
+    for (node, &parent) in prev.iter().enumerate() {
+        let tight = match parent {
+            None => node == source || dist[node] == usize::MAX,
+            Some(parent) => {
+                check_node(graph, parent)?;
+                node != source
+                    && dist[parent] != usize::MAX
+                    && graph.neighbors(parent).any(|(to, weight)| to == node && dist[parent].saturating_add(weight) == dist[node])
+            }
+        };
+        if !tight {
+            return Err(VerifyError::BadPredecessor { node });
+        }
+    }
+
+    // Every chain is tight, so it can only miss the source by going round a
+    // zero-weight cycle. Walk each chain once, marking the nodes on it.
+    const UNSEEN: u8 = 0;
+    const ON_CHAIN: u8 = 1;
+    const ROOTED: u8 = 2;
+    let mut state = vec![UNSEEN; prev.len()];
+    state[source] = ROOTED;
+    for (node, &distance) in dist.iter().enumerate() {
+        if distance == usize::MAX {
+            continue;
+        }
+        let mut current = node;
+        while state[current] == UNSEEN {
+            state[current] = ON_CHAIN;
+            current = prev[current].expect("tight chains only end at the source");
+        }
+        if state[current] == ON_CHAIN {
+            return Err(VerifyError::PredecessorCycle { node: current });
+        }
+        let mut current = node;
+        while state[current] == ON_CHAIN {
+            state[current] = ROOTED;
+            current = prev[current].expect("tight chains only end at the source");
+        }
+    }
+    Ok(())
+}
+
+fn check_node<G: Neighbors + ?Sized>(graph: &G, node: usize) -> Result<(), VerifyError> {
+    if node < graph.num_nodes() {
+        Ok(())
+    } else {
+        Err(VerifyError::NodeOutOfRange { node, num_nodes: graph.num_nodes() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{gnm, Weights};
+    use crate::{parallel_dijkstra, sequential_sssp};
+
+    #[test]
+    fn test_verify_path() {
+        let graph = vec![vec![(1, 2), (1, 5), (2, 9)], vec![(2, 3)], vec![]];
+        assert_eq!(verify_path(&graph, 0, 2, 5, &[0, 1, 2]), Ok(()));
+        assert_eq!(verify_path(&graph, 0, 2, 9, &[0, 2]), Ok(()));
+        assert_eq!(verify_path(&graph, 2, 2, 0, &[2]), Ok(()));
+        assert_eq!(verify_path(&graph, 2, 0, usize::MAX, &[]), Ok(()));
+        assert_eq!(verify_path(&graph, 2, 0, usize::MAX, &[2]), Err(VerifyError::PathForUnreachable));
+        assert_eq!(verify_path(&graph, 0, 2, 8, &[0, 1, 2]), Err(VerifyError::CostMismatch { reported: 8, actual: 5 }));
+        assert_eq!(verify_path(&graph, 0, 2, 3, &[0, 2, 1]), Err(VerifyError::WrongEndpoints { start: 0, goal: 2 }));
+        assert_eq!(verify_path(&graph, 1, 2, 3, &[1, 0, 2]), Err(VerifyError::MissingEdge { from: 1, to: 0 }));
+        assert_eq!(verify_path(&graph, 0, 7, 3, &[0, 7]), Err(VerifyError::NodeOutOfRange { node: 7, num_nodes: 3 }));
+    }
+
+    #[test]
+    fn test_verify_distances() {
+        let graph = gnm(2_000, 8_000, Weights::Uniform(0, 20), 4);
+        let (dist, prev) = sequential_sssp(&graph, 0);
+        assert_eq!(verify_tree(&graph, 0, &dist, &prev), Ok(()));
+        let goal = (0..dist.len()).rev().find(|&node| dist[node] != usize::MAX).unwrap();
+        let (cost, path) = parallel_dijkstra(&graph, 0, goal);
+        assert_eq!(verify_path(&graph, 0, goal, cost, &path), Ok(()));
+
+        // A distance too large is caught by an edge into it
+        let mut wrong = dist.clone();
+        wrong[goal] += 1;
+        assert!(matches!(verify_distances(&graph, 0, &wrong), Err(VerifyError::NotRelaxed { to, .. }) if to == goal));
+
+        let mut wrong_prev = prev.clone();
+        wrong_prev[goal] = Some(goal);
+        assert_eq!(verify_tree(&graph, 0, &dist, &wrong_prev), Err(VerifyError::BadPredecessor { node: goal }));
+        assert_eq!(verify_distances(&graph, 0, &dist[1..]), Err(VerifyError::WrongLength { expected: 2_000, actual: 1_999 }));
+    }
+
+    #[test]
+    fn test_unsupported_distances() {
+        // 1 and 2 are unreachable from 0 but support each other's distance
+        let graph = vec![vec![], vec![(2, 0)], vec![(1, 0)]];
+        assert_eq!(verify_distances(&graph, 0, &[0, usize::MAX, usize::MAX]), Ok(()));
+        assert_eq!(verify_distances(&graph, 0, &[0, 5, 5]), Err(VerifyError::Unsupported { node: 1 }));
+
+        // Too small a distance has no tight edge leading to it
+        let line = vec![vec![(1, 2)], vec![(2, 3)], vec![]];
+        assert_eq!(verify_distances(&line, 0, &[0, 2, 5]), Ok(()));
+        assert_eq!(verify_distances(&line, 0, &[0, 2, 4]), Err(VerifyError::Unsupported { node: 2 }));
+        assert_eq!(verify_distances(&line, 0, &[1, 2, 5]), Err(VerifyError::SourceNotZero { distance: 1 }));
+    }
+
+    #[test]
+    fn test_predecessor_cycle() {
+        // Every predecessor is tight, but 1 and 2 point at each other
+        let graph = vec![vec![(1, 0)], vec![(2, 0)], vec![(1, 0)]];
+        assert_eq!(verify_tree(&graph, 0, &[0, 0, 0], &[None, Some(0), Some(1)]), Ok(()));
+        assert_eq!(verify_tree(&graph, 0, &[0, 0, 0], &[None, Some(2), Some(1)]), Err(VerifyError::PredecessorCycle { node: 1 }));
+    }
+}